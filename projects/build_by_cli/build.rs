@@ -1 +1,31 @@
-fn main() {}
+use build_by_script::config::{PROJECT_FILE, ProjectConfig};
+use build_helper::{ParserBuilder, module_name};
+use std::{error::Error, fs::read_to_string};
+
+fn main() -> Result<(), Box<dyn Error>> {
+    println!("cargo:rerun-if-changed={PROJECT_FILE}");
+    let config = ProjectConfig::load(PROJECT_FILE)?;
+    for warning in &config.warnings {
+        println!("cargo:warning={warning}");
+    }
+    let excludes = config.excludes.iter().map(|p| glob::Pattern::new(p)).collect::<Result<Vec<_>, _>>()?;
+    for include in &config.includes {
+        for grammar in glob::glob(&config.root.join(include).to_string_lossy())? {
+            let grammar = grammar?;
+            let relative = grammar.strip_prefix(&config.root).unwrap_or(&grammar);
+            if excludes.iter().any(|p| p.matches_path(relative)) {
+                continue;
+            }
+            println!("cargo:rerun-if-changed={}", grammar.display());
+            let module = module_name(&grammar).ok_or_else(|| format!("{}: invalid grammar file name", grammar.display()))?;
+            let options = config.grammar_options(&module);
+            // the parsers are committed so they can be browsed, the build only checks they are up to date
+            let mut builder = ParserBuilder::new(module).checked_in(options.output).serde(options.serde);
+            for derive in &options.derives {
+                builder = builder.derive(derive);
+            }
+            builder.generate(&read_to_string(&grammar)?).map_err(|e| format!("{}: {e}", grammar.display()))?;
+        }
+    }
+    Ok(())
+}
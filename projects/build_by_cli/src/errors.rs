@@ -1 +1,39 @@
+use crate::json5::Json5Rule;
+use std::fmt::{Display, Formatter};
+use yggdrasil_rt::{YggdrasilError, YggdrasilErrorKind};
 
+/// Errors raised by the parsers generated from `Yggdrasil.json5`
+#[derive(Debug, Clone)]
+pub enum Error {
+    /// The rule is a helper of the generated parser and cannot start a parse, see [`Json5Rule::parsable_rules`]
+    NotParsable {
+        /// The rejected rule
+        rule: Json5Rule,
+    },
+    /// The input was rejected by the json5 grammar
+    Json5Error(YggdrasilError<Json5Rule>),
+}
+
+/// Result type of this crate
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl From<YggdrasilError<Json5Rule>> for Error {
+    fn from(value: YggdrasilError<Json5Rule>) -> Self {
+        match value.variant {
+            // the patched `parse_cst` names the helper rule it was started from
+            YggdrasilErrorKind::InvalidNode { expect } if !expect.is_parsable() => Self::NotParsable { rule: expect },
+            _ => Self::Json5Error(value),
+        }
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotParsable { rule } => write!(f, "`{rule:?}` is a helper rule, a parse cannot start from it"),
+            Self::Json5Error(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
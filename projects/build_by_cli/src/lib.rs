@@ -4,4 +4,10 @@
 #![doc(html_logo_url = "https://raw.githubusercontent.com/oovm/shape-rs/dev/projects/images/Trapezohedron.svg")]
 #![doc(html_favicon_url = "https://raw.githubusercontent.com/oovm/shape-rs/dev/projects/images/Trapezohedron.svg")]
 
+pub use crate::errors::{Error, Result};
+pub use yggdrasil_rt::{YggdrasilNode, YggdrasilParser};
+
 mod errors;
+/// Parser generated from `grammars/json5.ygg`
+#[allow(missing_copy_implementations)]
+pub mod json5;
@@ -1,4 +1,64 @@
+use build_by_cli::{
+    Error,
+    json5::{Json5Parser, Json5Rule, NestingExceeded, ValueNode},
+};
+use yggdrasil_rt::{YggdrasilNode, YggdrasilParser};
+
 #[test]
 fn ready() {
     println!("it works!")
 }
+
+#[test]
+fn test_object() {
+    let cst = Json5Parser::parse_cst("{int: 1, bool: [true, false,], 'k': null,}", Json5Rule::Value).unwrap();
+    let ValueNode::Object(object) = ValueNode::from_cst(cst).unwrap()
+    else {
+        panic!("expected an object")
+    };
+    assert_eq!(object.object_pair.len(), 3);
+    // every element is followed by a comma in this grammar
+    assert!(Json5Parser::parse_cst("{int: 1}", Json5Rule::Value).is_err());
+    assert!(Json5Parser::parse_cst("[1, 2]", Json5Rule::Value).is_err());
+}
+
+#[test]
+fn test_helper_rule() {
+    let error = Error::from(Json5Parser::parse_cst("' '", Json5Rule::IgnoreText).unwrap_err());
+    assert!(matches!(error, Error::NotParsable { rule: Json5Rule::IgnoreText }));
+    assert!(matches!(Error::from(Json5Parser::parse_cst("]", Json5Rule::Value).unwrap_err()), Error::Json5Error(_)));
+}
+
+#[test]
+fn test_nesting_limit() {
+    let deep = "[".repeat(100_000);
+    // `Value` and `Array` are both recursive, every `[` opens two levels
+    assert_eq!(Json5Parser::parse_cst_nested(&deep, Json5Rule::Value, 64).unwrap_err(), NestingExceeded { depth: 65, offset: 32 });
+    // `Value`, `Object`, `ObjectPair`, `Value`, `Array` inside `[` and the `Value` tried for an element of `[]`
+    assert!(Json5Parser::parse_cst_nested("[[1,], {a: [],},]", Json5Rule::Value, 9).unwrap().is_ok());
+    let error = Json5Parser::parse_cst_nested("[[1,], {a: [],},]", Json5Rule::Value, 8).unwrap_err();
+    assert_eq!(error, NestingExceeded { depth: 9, offset: 12 });
+    // the limit only holds for the nested call
+    assert!(Json5Parser::parse_cst("[[[1,],],]", Json5Rule::Value).is_ok());
+}
+
+#[test]
+fn test_strings() {
+    // a string holds at most one character, strings are keys but not values
+    for input in ["''", "'a'", "'\\''"] {
+        assert!(Json5Parser::parse_cst(input, Json5Rule::String).is_ok(), "{input}");
+    }
+    for input in ["'unterminated", "'ab'", "\"double\""] {
+        assert!(Json5Parser::parse_cst(input, Json5Rule::String).is_err(), "{input}");
+    }
+    assert!(Json5Parser::parse_cst("{'a': 1,}", Json5Rule::Value).is_ok());
+    assert!(Json5Parser::parse_cst("['a',]", Json5Rule::Value).is_err());
+}
+
+#[test]
+fn test_project_derives() {
+    // `derives: ["PartialEq", "Eq"]` in `Yggdrasil.json5`
+    let parse = |input| ValueNode::from_cst(Json5Parser::parse_cst(input, Json5Rule::Value).unwrap()).unwrap();
+    assert_eq!(parse("[1, 2,]"), parse("[1, 2,]"));
+    assert_ne!(parse("[1, 2,]"), parse("[1, 22,]"));
+}
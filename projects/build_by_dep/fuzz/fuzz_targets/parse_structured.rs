@@ -0,0 +1,14 @@
+#![no_main]
+
+use build_by_script::Json5Value;
+use build_by_script_fuzz::Document;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|document: Document| {
+    let input = document.to_string();
+    build_by_script_fuzz::check_input(&input);
+    match Json5Value::parse(&input) {
+        Ok(value) => assert_eq!(value.span.end as usize, input.len(), "trailing input in {input:?}"),
+        Err(e) => panic!("printed document rejected: {e}\n{input}"),
+    }
+});
@@ -0,0 +1,148 @@
+//! Invariants shared by the fuzz targets.
+
+use arbitrary::Arbitrary;
+use build_by_script::{
+    DEFAULT_MAX_DEPTH, Json5Kind, Json5Value, ParseLimits,
+    json5::{Json5Parser, Json5Rule, ValueNode},
+};
+use std::{
+    fmt::{Display, Formatter, Write},
+    ops::Range,
+};
+use yggdrasil_rt::{TokenPair, YggdrasilNode, YggdrasilParser};
+
+/// Run `input` through every parsable rule and the decoders, panics on a broken invariant.
+///
+/// The parser counts the nesting it enters, deep input fails with a typed error instead of overflowing the stack.
+pub fn check_input(input: &str) {
+    // helper rules must fail to match, not abort
+    assert!(Json5Parser::parse_cst(input, Json5Rule::IgnoreText).is_err());
+    assert!(Json5Parser::parse_cst(input, Json5Rule::IgnoreRegex).is_err());
+    for rule in Json5Rule::parsable_rules() {
+        let Ok(cst) = Json5Parser::parse_cst_bounded(input, *rule, DEFAULT_MAX_DEPTH) else {
+            continue;
+        };
+        let mut consumed = 0;
+        for pair in cst.clone() {
+            let span = pair.get_span();
+            // a successful parse starts at the beginning and its roots follow each other
+            assert_eq!(span.start(), consumed, "{rule:?} root does not continue the consumed input");
+            consumed = span.end();
+            check_pair(input, pair, 0..input.len());
+        }
+        if *rule == Json5Rule::Value {
+            let _ = ValueNode::from_cst(cst);
+        }
+    }
+    if let Ok(value) = Json5Value::parse_limited(input, &ParseLimits::default()) {
+        check_value(input, &value);
+    }
+}
+
+fn check_pair(input: &str, pair: TokenPair<Json5Rule>, parent: Range<usize>) {
+    let span = pair.get_span();
+    let (start, end) = (span.start(), span.end());
+    assert!(parent.start <= start && start <= end && end <= parent.end, "span {start}..{end} escapes {parent:?}");
+    assert!(input.is_char_boundary(start) && input.is_char_boundary(end), "span {start}..{end} splits a character");
+    for child in pair.into_inner() {
+        check_pair(input, child, start..end);
+    }
+}
+
+fn check_value(input: &str, value: &Json5Value) {
+    let Range { start, end } = value.span;
+    assert!(start <= end && end as usize <= input.len(), "value span {start}..{end} escapes the input");
+    match &value.kind {
+        Json5Kind::Array(items) => items.iter().for_each(|item| check_value(input, item)),
+        Json5Kind::Object(pairs) => pairs.iter().for_each(|(_, item)| check_value(input, item)),
+        _ => {}
+    }
+}
+
+/// A well-formed document, printed in the subset of JSON5 the grammar accepts
+#[derive(Debug, Arbitrary)]
+pub enum Document {
+    /// `null`
+    Null,
+    /// `true` or `false`
+    Boolean(bool),
+    /// A decimal integer
+    Integer(i32),
+    /// A double quoted string
+    String(String),
+    /// `[...]`
+    Array(Vec<Document>),
+    /// `{...}` with quoted or unquoted keys
+    Object(Vec<(Key, Document)>),
+}
+
+/// Key of a [`Document::Object`] pair
+#[derive(Debug, Arbitrary)]
+pub enum Key {
+    /// `0`, `42`
+    Integer(u16),
+    /// Printed as `k<n>`
+    Identifier(u16),
+    /// A double quoted string
+    Quoted(String),
+}
+
+/// Deeper levels are printed as `null` to stay under [`DEFAULT_MAX_DEPTH`]
+const MAX_DEPTH: usize = 32;
+
+impl Display for Document {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.write(f, 0)
+    }
+}
+
+impl Document {
+    fn write(&self, f: &mut Formatter<'_>, depth: usize) -> std::fmt::Result {
+        match self {
+            _ if depth > MAX_DEPTH => f.write_str("null"),
+            Self::Null => f.write_str("null"),
+            Self::Boolean(b) => write!(f, "{b}"),
+            Self::Integer(i) => write!(f, "{i}"),
+            Self::String(s) => write_string(f, s),
+            Self::Array(items) => {
+                f.write_char('[')?;
+                for (index, item) in items.iter().enumerate() {
+                    if index > 0 {
+                        f.write_str(", ")?;
+                    }
+                    item.write(f, depth + 1)?;
+                }
+                f.write_char(']')
+            }
+            Self::Object(pairs) => {
+                f.write_char('{')?;
+                for (index, (key, value)) in pairs.iter().enumerate() {
+                    if index > 0 {
+                        f.write_str(", ")?;
+                    }
+                    match key {
+                        Key::Integer(i) => write!(f, "{i}")?,
+                        Key::Identifier(i) => write!(f, "k{i}")?,
+                        Key::Quoted(s) => write_string(f, s)?,
+                    }
+                    f.write_str(": ")?;
+                    value.write(f, depth + 1)?;
+                }
+                f.write_char('}')
+            }
+        }
+    }
+}
+
+fn write_string(f: &mut Formatter<'_>, text: &str) -> std::fmt::Result {
+    f.write_char('"')?;
+    for c in text.chars() {
+        match c {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+            c => f.write_char(c)?,
+        }
+    }
+    f.write_char('"')
+}
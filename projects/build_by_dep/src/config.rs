@@ -0,0 +1,310 @@
+//! Typed model of the `Yggdrasil.json5` project file.
+//!
+//! ```json5
+//! {
+//!     export: "src/",
+//!     language: "rs",
+//!     includes: ["grammars/*.ygg"],
+//!     excludes: [],
+//!     derives: ["PartialEq", "Eq"],
+//!     grammars: {
+//!         json5: { output: "src/json5", derives: ["PartialEq", "Eq", "Default"], serde: false },
+//!     },
+//! }
+//! ```
+//!
+//! Relative paths are resolved against the directory of the project file, diagnostics carry the line and column of
+//! the offending value. A key given twice is a warning, the last value wins. `wasm: true` is rejected, the generator
+//! has no `wasm-bindgen` bindings to emit.
+
+use crate::{Error, Json5Key, Json5Kind, Json5Value, value::literal_offset};
+use std::{
+    collections::BTreeMap,
+    fmt::{Display, Formatter},
+    ops::Range,
+    path::{Path, PathBuf},
+};
+
+/// Default name of the project file
+pub const PROJECT_FILE: &str = "Yggdrasil.json5";
+
+/// The parsed and validated project file
+#[derive(Clone, Debug, PartialEq)]
+pub struct ProjectConfig {
+    /// Directory of the project file, every relative path is resolved against it
+    pub root: PathBuf,
+    /// Directory that receives one module per grammar
+    pub export: PathBuf,
+    /// Language of the generated code
+    pub language: TargetLanguage,
+    /// Globs selecting grammar files, relative to `root`
+    pub includes: Vec<String>,
+    /// Globs removing grammar files from `includes`, relative to `root`
+    pub excludes: Vec<String>,
+    /// Extra derives on every generated node of every grammar
+    pub derives: Vec<String>,
+    /// Per grammar overrides, keyed by module name
+    pub grammars: BTreeMap<String, GrammarConfig>,
+    /// Non fatal findings, such as unknown keys
+    pub warnings: Vec<ConfigDiagnostic>,
+}
+
+/// Languages the project file can target
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum TargetLanguage {
+    /// `rs` or `rust`
+    Rust,
+}
+
+/// Overrides of a single grammar, unset fields fall back to the project defaults
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct GrammarConfig {
+    /// Output directory of the module, resolved against the project root
+    pub output: Option<PathBuf>,
+    /// Extra derives on every generated node, in place of the project ones
+    pub derives: Option<Vec<String>>,
+    /// Keep the `serde` derives of the nodes, see [`ParserBuilder::serde`](build_helper::ParserBuilder::serde)
+    pub serde: Option<bool>,
+}
+
+/// Effective options of a single grammar after applying the overrides
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GrammarOptions {
+    /// Output directory of the module
+    pub output: PathBuf,
+    /// Extra derives on every generated node
+    pub derives: Vec<String>,
+    /// Keep the `serde` derives of the nodes, see [`ParserBuilder::serde`](build_helper::ParserBuilder::serde)
+    pub serde: bool,
+}
+
+/// A finding in the project file, located by line and column
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConfigDiagnostic {
+    /// Path of the project file
+    pub file: PathBuf,
+    /// 1-based line
+    pub line: usize,
+    /// 1-based column, in characters
+    pub column: usize,
+    /// What is wrong
+    pub message: String,
+}
+
+impl Display for ConfigDiagnostic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}:{}: {}", self.file.display(), self.line, self.column, self.message)
+    }
+}
+
+impl std::error::Error for ConfigDiagnostic {}
+
+impl ProjectConfig {
+    /// Read and validate the project file at `path`.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, ConfigDiagnostic> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path).map_err(|e| ConfigDiagnostic {
+            file: path.to_path_buf(),
+            line: 1,
+            column: 1,
+            message: e.to_string(),
+        })?;
+        Self::parse(&text, path)
+    }
+    /// Validate the project file content, `path` is used to resolve relative paths and to locate diagnostics.
+    pub fn parse<P: AsRef<Path>>(text: &str, path: P) -> Result<Self, ConfigDiagnostic> {
+        let reader = Reader { text, file: path.as_ref().to_path_buf() };
+        let document = Json5Value::parse(text).map_err(|e| {
+            let at = e.offset().unwrap_or_default() as u32;
+            match e {
+                // the runtime renders a source excerpt, the diagnostic only needs its position
+                Error::SyntaxError(_) => reader.error(&(at..at), "invalid json5 syntax"),
+                _ => reader.error(&(at..at), e.to_string()),
+            }
+        })?;
+        reader.read_project(&document)
+    }
+    /// Effective options of the grammar generating the module `name`.
+    pub fn grammar_options(&self, name: &str) -> GrammarOptions {
+        let config = self.grammars.get(name).cloned().unwrap_or_default();
+        GrammarOptions {
+            output: config.output.unwrap_or_else(|| self.export.join(name)),
+            derives: config.derives.unwrap_or_else(|| self.derives.clone()),
+            serde: config.serde.unwrap_or(true),
+        }
+    }
+}
+
+struct Reader<'i> {
+    text: &'i str,
+    file: PathBuf,
+}
+
+impl<'i> Reader<'i> {
+    fn error(&self, span: &Range<u32>, message: impl Into<String>) -> ConfigDiagnostic {
+        let offset = (span.start as usize).min(self.text.len());
+        let before = &self.text[..offset];
+        let line = before.matches('\n').count() + 1;
+        let column = before.rsplit('\n').next().unwrap_or_default().chars().count() + 1;
+        ConfigDiagnostic { file: self.file.clone(), line, column, message: message.into() }
+    }
+    fn root(&self) -> PathBuf {
+        match self.file.parent() {
+            Some(parent) => parent.to_path_buf(),
+            None => PathBuf::new(),
+        }
+    }
+    fn read_project(&self, document: &Json5Value) -> Result<ProjectConfig, ConfigDiagnostic> {
+        let root = self.root();
+        let mut config = ProjectConfig {
+            export: root.clone(),
+            root,
+            language: TargetLanguage::Rust,
+            includes: vec![],
+            excludes: vec![],
+            derives: vec![],
+            grammars: BTreeMap::new(),
+            warnings: vec![],
+        };
+        let pairs = self.object(document)?;
+        self.duplicates(pairs, &mut config.warnings);
+        for (key, value) in pairs {
+            match key.name.as_str() {
+                "export" => config.export = config.root.join(self.string(value)?),
+                "language" => config.language = self.language(value)?,
+                "includes" => config.includes = self.globs(value)?,
+                "excludes" => config.excludes = self.globs(value)?,
+                "derives" => config.derives = self.identifiers(value)?,
+                "grammars" => {
+                    let grammars = self.object(value)?;
+                    self.duplicates(grammars, &mut config.warnings);
+                    for (name, value) in grammars {
+                        let grammar = self.read_grammar(value, &config.root, &mut config.warnings)?;
+                        config.grammars.insert(name.name.clone(), grammar);
+                    }
+                }
+                _ => {
+                    let expected = ["export", "language", "includes", "excludes", "derives", "grammars"];
+                    config.warnings.push(self.unknown_key(key, &expected))
+                }
+            }
+        }
+        Ok(config)
+    }
+    fn read_grammar(
+        &self,
+        value: &Json5Value,
+        root: &Path,
+        warnings: &mut Vec<ConfigDiagnostic>,
+    ) -> Result<GrammarConfig, ConfigDiagnostic> {
+        let mut grammar = GrammarConfig::default();
+        let pairs = self.object(value)?;
+        self.duplicates(pairs, warnings);
+        for (key, value) in pairs {
+            match key.name.as_str() {
+                "output" => grammar.output = Some(root.join(self.string(value)?)),
+                "derives" => grammar.derives = Some(self.identifiers(value)?),
+                "serde" => grammar.serde = Some(self.boolean(value)?),
+                "wasm" => {
+                    if self.boolean(value)? {
+                        return Err(self.error(&value.span, "`wasm` bindings are not generated, expected `false`"));
+                    }
+                }
+                _ => warnings.push(self.unknown_key(key, &["output", "derives", "serde", "wasm"])),
+            }
+        }
+        Ok(grammar)
+    }
+    /// Warn about every key repeated in `pairs`, where the last value wins
+    fn duplicates(&self, pairs: &[(Json5Key, Json5Value)], warnings: &mut Vec<ConfigDiagnostic>) {
+        for (index, (key, _)) in pairs.iter().enumerate() {
+            if pairs[..index].iter().any(|(first, _)| first.name == key.name) {
+                warnings.push(self.error(&key.span, format!("duplicate key `{}`, the last value is used", key.name)));
+            }
+        }
+    }
+    fn unknown_key(&self, key: &Json5Key, expected: &[&str]) -> ConfigDiagnostic {
+        self.error(&key.span, format!("unknown key `{}`, expected one of `{}`", key.name, expected.join("`, `")))
+    }
+    fn object<'v>(&self, value: &'v Json5Value) -> Result<&'v [(Json5Key, Json5Value)], ConfigDiagnostic> {
+        match &value.kind {
+            Json5Kind::Object(pairs) => Ok(pairs),
+            _ => Err(self.error(&value.span, "expected an object")),
+        }
+    }
+    fn string<'v>(&self, value: &'v Json5Value) -> Result<&'v str, ConfigDiagnostic> {
+        value.as_str().ok_or_else(|| self.error(&value.span, "expected a string"))
+    }
+    fn boolean(&self, value: &Json5Value) -> Result<bool, ConfigDiagnostic> {
+        value.as_bool().ok_or_else(|| self.error(&value.span, "expected `true` or `false`"))
+    }
+    fn strings<'v>(&self, value: &'v Json5Value) -> Result<Vec<(&'v str, &'v Range<u32>)>, ConfigDiagnostic> {
+        let items = value.as_array().ok_or_else(|| self.error(&value.span, "expected an array of strings"))?;
+        items.iter().map(|item| Ok((self.string(item)?, &item.span))).collect()
+    }
+    fn language(&self, value: &Json5Value) -> Result<TargetLanguage, ConfigDiagnostic> {
+        match self.string(value)? {
+            "rs" | "rust" => Ok(TargetLanguage::Rust),
+            other => Err(self.error(&value.span, format!("unsupported language `{other}`, expected `rs` or `rust`"))),
+        }
+    }
+    fn globs(&self, value: &Json5Value) -> Result<Vec<String>, ConfigDiagnostic> {
+        let mut out = vec![];
+        for (glob, span) in self.strings(value)? {
+            if let Err((offset, message)) = check_glob(glob) {
+                // escapes make the literal longer than the glob
+                let literal = &self.text[span.start as usize..span.end as usize];
+                let at = span.start + literal_offset(literal, offset) as u32;
+                return Err(self.error(&(at..at), format!("invalid glob `{glob}`: {message}")));
+            }
+            out.push(glob.to_string());
+        }
+        Ok(out)
+    }
+    fn identifiers(&self, value: &Json5Value) -> Result<Vec<String>, ConfigDiagnostic> {
+        let mut out = vec![];
+        for (name, span) in self.strings(value)? {
+            let valid = name.split("::").all(|part| {
+                let mut chars = part.chars();
+                matches!(chars.next(), Some(c) if c == '_' || c.is_alphabetic())
+                    && chars.all(|c| c == '_' || c.is_alphanumeric())
+            });
+            if !valid {
+                return Err(self.error(span, format!("`{name}` is not a derive path")));
+            }
+            out.push(name.to_string());
+        }
+        Ok(out)
+    }
+}
+
+/// Check a glob against the syntax accepted by the `glob` crate, returns the byte offset of the first error.
+fn check_glob(glob: &str) -> Result<(), (usize, &'static str)> {
+    if glob.is_empty() {
+        return Err((0, "empty pattern"));
+    }
+    let bytes = glob.as_bytes();
+    let mut offset = 0;
+    while offset < bytes.len() {
+        match bytes[offset] {
+            b'*' if bytes.get(offset + 1) == Some(&b'*') => {
+                let before = offset == 0 || matches!(bytes[offset - 1], b'/' | b'\\');
+                let after = matches!(bytes.get(offset + 2), None | Some(b'/' | b'\\'));
+                if !(before && after) {
+                    return Err((offset, "`**` must form a whole path component"));
+                }
+                offset += 2;
+            }
+            b'[' => {
+                // a `]` right after `[` or `[!` is a member of the class
+                let first = offset + 1 + usize::from(bytes.get(offset + 1) == Some(&b'!'));
+                match bytes.get(first + 1..).and_then(|rest| rest.iter().position(|b| *b == b']')) {
+                    Some(close) => offset = first + close + 2,
+                    None => return Err((offset, "unclosed character class")),
+                }
+            }
+            _ => offset += 1,
+        }
+    }
+    Ok(())
+}
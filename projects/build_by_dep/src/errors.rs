@@ -1,6 +1,139 @@
-#[derive(Debug, Copy, Clone)]
+use crate::json5::Json5Rule;
+use std::{
+    fmt::{Display, Formatter},
+    time::Duration,
+};
+use yggdrasil_rt::{InputLocation, YggdrasilError, YggdrasilErrorKind};
+
+/// Errors raised by the guarded parse entries of this crate
+#[derive(Debug, Clone)]
 pub enum Error {
-    UnknownError
+    /// Unclassified failure
+    UnknownError,
+    /// The input is longer than the configured limit
+    InputTooLarge {
+        /// Length of the input in bytes
+        size: usize,
+        /// Maximum length allowed
+        limit: usize,
+    },
+    /// Arrays or objects are nested deeper than the configured limit
+    NestingTooDeep {
+        /// Depth reached at `offset`
+        depth: usize,
+        /// Maximum depth allowed
+        limit: usize,
+        /// Byte offset of the bracket that exceeded the limit
+        offset: usize,
+    },
+    /// A string literal or identifier is longer than the configured limit
+    StringTooLong {
+        /// Length of the literal in bytes
+        length: usize,
+        /// Maximum length allowed
+        limit: usize,
+        /// Byte offset where the literal starts
+        offset: usize,
+    },
+    /// A number literal is longer than the configured limit
+    NumberTooLong {
+        /// Length of the literal in bytes
+        length: usize,
+        /// Maximum length allowed
+        limit: usize,
+        /// Byte offset where the literal starts
+        offset: usize,
+    },
+    /// The parser built more CST nodes than the configured limit
+    TooManyNodes {
+        /// Nodes counted when the limit was hit
+        count: usize,
+        /// Maximum count allowed
+        limit: usize,
+        /// Byte offset where the node over the limit ends
+        offset: usize,
+    },
+    /// The parser made more rule calls than the configured limit
+    TooManySteps {
+        /// Maximum count allowed
+        limit: usize,
+        /// Byte offset of the rule call over the limit
+        offset: usize,
+    },
+    /// The parse ran longer than [`ParseLimits::reject_after`](crate::ParseLimits::reject_after) and was stopped
+    TookTooLong {
+        /// Time spent when the parser stopped
+        elapsed: Duration,
+        /// Time allowed
+        limit: Duration,
+        /// Byte offset the parser stopped at
+        offset: usize,
+    },
+    /// The rule is a helper of the generated parser and cannot start a parse, see [`Json5Rule::parsable_rules`]
+    NotParsable {
+        /// The rejected rule
+        rule: Json5Rule,
+    },
+    /// The input was rejected by the grammar
+    SyntaxError(YggdrasilError<Json5Rule>),
 }
 
+/// Result type of the guarded parse entries
 pub type Result<T> = std::result::Result<T, Error>;
+
+impl Error {
+    /// Byte offset in the input the error points at, `None` for errors about the whole input
+    pub fn offset(&self) -> Option<usize> {
+        match self {
+            Self::NestingTooDeep { offset, .. }
+            | Self::StringTooLong { offset, .. }
+            | Self::NumberTooLong { offset, .. }
+            | Self::TooManyNodes { offset, .. }
+            | Self::TooManySteps { offset, .. }
+            | Self::TookTooLong { offset, .. } => Some(*offset),
+            Self::SyntaxError(e) => match e.location {
+                InputLocation::Pos(offset) | InputLocation::Span((offset, _)) => Some(offset),
+            },
+            _ => None,
+        }
+    }
+}
+
+impl From<YggdrasilError<Json5Rule>> for Error {
+    fn from(value: YggdrasilError<Json5Rule>) -> Self {
+        match value.variant {
+            // the patched `parse_cst` names the helper rule it was started from
+            YggdrasilErrorKind::InvalidNode { expect } if !expect.is_parsable() => Self::NotParsable { rule: expect },
+            _ => Self::SyntaxError(value),
+        }
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnknownError => f.write_str("unknown error"),
+            Self::InputTooLarge { size, limit } => write!(f, "input too large: {size} bytes exceeds limit {limit}"),
+            Self::NestingTooDeep { depth, limit, offset } => {
+                write!(f, "nesting too deep: depth {depth} exceeds limit {limit} at offset {offset}")
+            }
+            Self::StringTooLong { length, limit, offset } => {
+                write!(f, "string too long: {length} bytes exceeds limit {limit} at offset {offset}")
+            }
+            Self::NumberTooLong { length, limit, offset } => {
+                write!(f, "number too long: {length} bytes exceeds limit {limit} at offset {offset}")
+            }
+            Self::TooManyNodes { count, limit, offset } => {
+                write!(f, "too many nodes: {count} exceeds limit {limit} at offset {offset}")
+            }
+            Self::TooManySteps { limit, offset } => write!(f, "too many steps: limit {limit} exceeded at offset {offset}"),
+            Self::TookTooLong { elapsed, limit, offset } => {
+                write!(f, "parse took too long: {elapsed:?} exceeds {limit:?}, stopped at offset {offset}")
+            }
+            Self::NotParsable { rule } => write!(f, "`{rule:?}` is a helper rule, a parse cannot start from it"),
+            Self::SyntaxError(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
@@ -4,6 +4,17 @@
 #![doc(html_logo_url = "https://raw.githubusercontent.com/oovm/shape-rs/dev/projects/images/Trapezohedron.svg")]
 #![doc(html_favicon_url = "https://raw.githubusercontent.com/oovm/shape-rs/dev/projects/images/Trapezohedron.svg")]
 
+pub use crate::{
+    errors::{Error, Result},
+    limits::{DEFAULT_MAX_DEPTH, ParseLimits},
+    value::{Json5Key, Json5Kind, Json5Value},
+};
 pub use yggdrasil_rt::{YggdrasilNode, YggdrasilParser};
 
-pub mod json5;
+pub mod config;
+mod errors;
+mod limits;
+mod value;
+
+build_helper::include_parser!("json5");
+build_helper::include_parser!("settings");
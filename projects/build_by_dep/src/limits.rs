@@ -0,0 +1,181 @@
+use crate::{
+    Error, Result,
+    json5::{BudgetExceeded, Json5Parser, Json5Rule, ParseBudget, ValueNode},
+};
+use std::time::{Duration, Instant};
+use yggdrasil_rt::{TokenPair, TokenTree, YggdrasilNode};
+
+/// Default nesting limit of [`ParseLimits`], 128 nested arrays or objects, deep enough for any hand-written document
+pub const DEFAULT_MAX_DEPTH: usize = 256;
+
+/// Resource caps applied around the generated parser.
+///
+/// The input size is checked before the parser runs and the nesting while it runs, every running `Value`, `Object`,
+/// `ObjectPair` or `Array` counts one level, two per array or object, so adversarial inputs such as `[[[[...` are
+/// rejected with a typed [`Error`] instead of exhausting the stack. The rule calls, the nodes and the time are
+/// counted while the parser runs too, it stops at the first rule over a cap instead of finishing the document. Literal
+/// lengths are read from the tree the parser built, which agrees with the grammar by construction.
+///
+/// ```
+/// use build_by_script::{json5::Json5Parser, ParseLimits};
+///
+/// let limits = ParseLimits::default().with_max_input_bytes(1024).with_max_depth(16);
+/// assert!(Json5Parser::parse_value_limited("[1, 2, 3]", &limits).is_ok());
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ParseLimits {
+    /// Maximum length of the whole input, in bytes
+    pub max_input_bytes: usize,
+    /// Maximum nesting levels, each array or object costs two, one for its `Value` and one for itself
+    pub max_depth: usize,
+    /// Maximum length of a single string literal or identifier, in bytes, quotes included
+    pub max_string_length: usize,
+    /// Maximum length of a single number literal, in bytes
+    pub max_number_length: usize,
+    /// Maximum number of CST nodes
+    pub max_nodes: usize,
+    /// Maximum number of rule calls, the failed ones included, which bounds the parse time without a clock
+    pub max_steps: usize,
+    /// Stop a parse running longer, `None` disables the clock which is unavailable on some wasm targets
+    pub reject_after: Option<Duration>,
+}
+
+impl Default for ParseLimits {
+    fn default() -> Self {
+        Self {
+            max_input_bytes: 16 * 1024 * 1024,
+            max_depth: DEFAULT_MAX_DEPTH,
+            max_string_length: 1024 * 1024,
+            max_number_length: 256,
+            max_nodes: 1_000_000,
+            max_steps: 100_000_000,
+            reject_after: None,
+        }
+    }
+}
+
+impl ParseLimits {
+    /// Limits that only guard the stack, every other cap is disabled
+    pub const fn unlimited() -> Self {
+        Self {
+            max_input_bytes: usize::MAX,
+            max_depth: DEFAULT_MAX_DEPTH,
+            max_string_length: usize::MAX,
+            max_number_length: usize::MAX,
+            max_nodes: usize::MAX,
+            max_steps: usize::MAX,
+            reject_after: None,
+        }
+    }
+    /// Set the maximum input length in bytes
+    pub const fn with_max_input_bytes(mut self, bytes: usize) -> Self {
+        self.max_input_bytes = bytes;
+        self
+    }
+    /// Set the maximum nesting depth
+    pub const fn with_max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = depth;
+        self
+    }
+    /// Set the maximum length of a string literal or identifier
+    pub const fn with_max_string_length(mut self, length: usize) -> Self {
+        self.max_string_length = length;
+        self
+    }
+    /// Set the maximum length of a number literal
+    pub const fn with_max_number_length(mut self, length: usize) -> Self {
+        self.max_number_length = length;
+        self
+    }
+    /// Set the maximum CST node count
+    pub const fn with_max_nodes(mut self, nodes: usize) -> Self {
+        self.max_nodes = nodes;
+        self
+    }
+    /// Set the maximum number of rule calls
+    pub const fn with_max_steps(mut self, steps: usize) -> Self {
+        self.max_steps = steps;
+        self
+    }
+    /// Set the parse time after which the document is rejected
+    pub const fn with_reject_after(mut self, elapsed: Duration) -> Self {
+        self.reject_after = Some(elapsed);
+        self
+    }
+}
+
+impl Json5Parser {
+    /// Parse `input` with the given rule, rejecting documents nested deeper than `max_depth`.
+    ///
+    /// The generated parser is plain recursive descent, so every array or object level costs several stack frames.
+    /// The parser counts the recursive rules it enters, adversarial inputs such as `[[[[...` are rejected with
+    /// [`Error::NestingTooDeep`] instead of overflowing the stack.
+    pub fn parse_cst_bounded(input: &str, rule: Json5Rule, max_depth: usize) -> Result<TokenTree<'_, Json5Rule>> {
+        Self::parse_cst_limited(input, rule, &ParseLimits::unlimited().with_max_depth(max_depth))
+    }
+    /// Parse `input` with the given rule under the given resource limits, `rule` must be one of
+    /// [`Json5Rule::parsable_rules`].
+    pub fn parse_cst_limited<'i>(input: &'i str, rule: Json5Rule, limits: &ParseLimits) -> Result<TokenTree<'i, Json5Rule>> {
+        if input.len() > limits.max_input_bytes {
+            return Err(Error::InputTooLarge { size: input.len(), limit: limits.max_input_bytes });
+        }
+        let start = limits.reject_after.map(|_| Instant::now());
+        let budget = ParseBudget {
+            max_steps: limits.max_steps,
+            max_nodes: limits.max_nodes,
+            deadline: start.zip(limits.reject_after).and_then(|(start, limit)| start.checked_add(limit)),
+        };
+        let cst = Self::with_budget(budget, || Self::parse_cst_nested(input, rule, limits.max_depth))
+            .map_err(|e| budget_error(e, limits, start))?
+            .map_err(|e| Error::NestingTooDeep { depth: e.depth, limit: limits.max_depth, offset: e.offset })??;
+        check_literals(&cst, limits)?;
+        Ok(cst)
+    }
+    /// Parse `input` as a [`ValueNode`] with the default [`ParseLimits`].
+    pub fn parse_value(input: &str) -> Result<ValueNode> {
+        Self::parse_value_limited(input, &ParseLimits::default())
+    }
+    /// Parse `input` as a [`ValueNode`] under the given resource limits.
+    pub fn parse_value_limited(input: &str, limits: &ParseLimits) -> Result<ValueNode> {
+        Ok(ValueNode::from_cst(Self::parse_cst_limited(input, Json5Rule::Value, limits)?)?)
+    }
+}
+
+fn budget_error(exceeded: BudgetExceeded, limits: &ParseLimits, start: Option<Instant>) -> Error {
+    match exceeded {
+        BudgetExceeded::Steps { offset } => Error::TooManySteps { limit: limits.max_steps, offset },
+        BudgetExceeded::Nodes { offset } => {
+            Error::TooManyNodes { count: limits.max_nodes.saturating_add(1), limit: limits.max_nodes, offset }
+        }
+        BudgetExceeded::Deadline { offset } => Error::TookTooLong {
+            elapsed: start.map(|start| start.elapsed()).unwrap_or_default(),
+            limit: limits.reject_after.unwrap_or_default(),
+            offset,
+        },
+    }
+}
+
+/// Measure the literals of a tree in document order, without recursion.
+///
+/// Identifiers share the string length cap, integer keys the number length cap.
+fn check_literals(cst: &TokenTree<Json5Rule>, limits: &ParseLimits) -> Result<()> {
+    let mut pending: Vec<TokenPair<Json5Rule>> = cst.clone().collect();
+    pending.reverse();
+    while let Some(pair) = pending.pop() {
+        let span = pair.get_span();
+        let (length, offset) = (span.end() - span.start(), span.start());
+        match pair.get_rule() {
+            Json5Rule::String | Json5Rule::Identifier if length > limits.max_string_length => {
+                return Err(Error::StringTooLong { length, limit: limits.max_string_length, offset });
+            }
+            Json5Rule::Number | Json5Rule::Integer if length > limits.max_number_length => {
+                return Err(Error::NumberTooLong { length, limit: limits.max_number_length, offset });
+            }
+            _ => {}
+        }
+        let start = pending.len();
+        pending.extend(pair.into_inner());
+        pending[start..].reverse();
+    }
+    Ok(())
+}
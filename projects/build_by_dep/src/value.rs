@@ -0,0 +1,333 @@
+use crate::{
+    ParseLimits, Result,
+    json5::{Json5Parser, Json5Rule},
+};
+use std::{borrow::Cow, ops::Range, str::CharIndices};
+use yggdrasil_rt::{TokenPair, YggdrasilError, YggdrasilNode};
+
+/// An owned JSON5 document, every value remembers where it came from.
+///
+/// The generated AST keeps the shape of the grammar but drops the text of strings and the spans of union nodes.
+/// This tree is read from the same CST and decodes literals against the input, so configuration readers can
+/// consume a document without walking [`TokenPair`]s themselves.
+///
+/// ```
+/// use build_by_script::{Json5Kind, Json5Value};
+///
+/// let value = Json5Value::parse("{name: 'json5', tags: [\"a\", \"b\"]}").unwrap();
+/// assert_eq!(value.get("name").and_then(Json5Value::as_str), Some("json5"));
+/// assert!(matches!(value.get("tags").map(|v| &v.kind), Some(Json5Kind::Array(items)) if items.len() == 2));
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Json5Value {
+    /// The decoded value
+    pub kind: Json5Kind,
+    /// Byte range of the value in the input
+    pub span: Range<u32>,
+}
+
+/// The decoded variants of a [`Json5Value`]
+#[derive(Clone, Debug, PartialEq)]
+pub enum Json5Kind {
+    /// `null`
+    Null,
+    /// `true` or `false`
+    Boolean(bool),
+    /// Any number literal
+    Number(f64),
+    /// A quoted string with escapes resolved
+    String(String),
+    /// `[...]`
+    Array(Vec<Json5Value>),
+    /// `{...}`, pairs are kept in source order
+    Object(Vec<(Json5Key, Json5Value)>),
+}
+
+/// Key of an object pair
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Json5Key {
+    /// The decoded key
+    pub name: String,
+    /// Byte range of the key in the input
+    pub span: Range<u32>,
+}
+
+impl Json5Value {
+    /// Parse and decode a document with the default [`ParseLimits`].
+    pub fn parse(input: &str) -> Result<Self> {
+        Self::parse_limited(input, &ParseLimits::default())
+    }
+    /// Parse and decode a document under the given resource limits.
+    pub fn parse_limited(input: &str, limits: &ParseLimits) -> Result<Self> {
+        let cst = Json5Parser::parse_cst_limited(input, Json5Rule::Value, limits)?;
+        let view = ValueView::from_cst(cst)?;
+        view.decode(input)
+    }
+    /// Look up the value of the first pair named `key`, if this is an object.
+    pub fn get(&self, key: &str) -> Option<&Json5Value> {
+        match &self.kind {
+            Json5Kind::Object(pairs) => pairs.iter().find(|(k, _)| k.name == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+    /// The decoded text, if this is a string.
+    pub fn as_str(&self) -> Option<&str> {
+        match &self.kind {
+            Json5Kind::String(s) => Some(s),
+            _ => None,
+        }
+    }
+    /// The elements, if this is an array.
+    pub fn as_array(&self) -> Option<&[Json5Value]> {
+        match &self.kind {
+            Json5Kind::Array(items) => Some(items),
+            _ => None,
+        }
+    }
+    /// The boolean, if this is `true` or `false`.
+    pub fn as_bool(&self) -> Option<bool> {
+        match &self.kind {
+            Json5Kind::Boolean(b) => Some(*b),
+            _ => None,
+        }
+    }
+}
+
+/// Undecoded view of a `Value` pair, literals are kept as spans until the input is at hand.
+#[derive(Clone, Debug)]
+struct ValueView {
+    kind: ViewKind,
+    span: Range<u32>,
+}
+
+#[derive(Clone, Debug)]
+enum ViewKind {
+    Null,
+    Boolean(bool),
+    Number,
+    String,
+    Array(Vec<ValueView>),
+    Object(Vec<(KeyView, ValueView)>),
+}
+
+#[derive(Clone, Debug)]
+struct KeyView {
+    quoted: bool,
+    span: Range<u32>,
+}
+
+#[derive(Clone, Debug)]
+struct PairView(KeyView, ValueView);
+
+#[derive(Clone, Debug)]
+struct ItemsView<T>(Vec<T>);
+
+#[derive(Clone, Debug)]
+struct SpanView(Range<u32>);
+
+fn span_of(pair: &TokenPair<Json5Rule>) -> Range<u32> {
+    let span = pair.get_span();
+    Range { start: span.start() as u32, end: span.end() as u32 }
+}
+
+impl YggdrasilNode for ValueView {
+    type Rule = Json5Rule;
+
+    fn get_range(&self) -> Option<Range<usize>> {
+        Some(Range { start: self.span.start as usize, end: self.span.end as usize })
+    }
+    fn from_pair(pair: TokenPair<Self::Rule>) -> std::result::Result<Self, YggdrasilError<Self::Rule>> {
+        let span = span_of(&pair);
+        let kind = if let Ok(ItemsView(pairs)) = pair.take_tagged_one::<ItemsView<PairView>>(Cow::Borrowed("object")) {
+            ViewKind::Object(pairs.into_iter().map(|PairView(k, v)| (k, v)).collect())
+        }
+        else if let Ok(ItemsView(items)) = pair.take_tagged_one::<ItemsView<ValueView>>(Cow::Borrowed("array")) {
+            ViewKind::Array(items)
+        }
+        else if pair.take_tagged_one::<SpanView>(Cow::Borrowed("string")).is_ok() {
+            ViewKind::String
+        }
+        else if pair.take_tagged_one::<SpanView>(Cow::Borrowed("number")).is_ok() {
+            ViewKind::Number
+        }
+        else if let Some(boolean) = pair.find_first_tag("boolean") {
+            // the branch tags are on the text matched inside `Boolean`, not on the `Value` pair
+            ViewKind::Boolean(boolean.find_first_tag("boolean_0").is_some())
+        }
+        else if pair.take_tagged_one::<SpanView>(Cow::Borrowed("null")).is_ok() {
+            ViewKind::Null
+        }
+        else {
+            return Err(YggdrasilError::invalid_node(Json5Rule::Value, pair.get_span()));
+        };
+        Ok(Self { kind, span })
+    }
+}
+
+impl YggdrasilNode for ItemsView<PairView> {
+    type Rule = Json5Rule;
+
+    fn get_range(&self) -> Option<Range<usize>> {
+        None
+    }
+    fn from_pair(pair: TokenPair<Self::Rule>) -> std::result::Result<Self, YggdrasilError<Self::Rule>> {
+        Ok(Self(pair.take_tagged_items::<PairView>(Cow::Borrowed("object_pair"))?))
+    }
+}
+
+impl YggdrasilNode for ItemsView<ValueView> {
+    type Rule = Json5Rule;
+
+    fn get_range(&self) -> Option<Range<usize>> {
+        None
+    }
+    fn from_pair(pair: TokenPair<Self::Rule>) -> std::result::Result<Self, YggdrasilError<Self::Rule>> {
+        Ok(Self(pair.take_tagged_items::<ValueView>(Cow::Borrowed("value"))?))
+    }
+}
+
+impl YggdrasilNode for PairView {
+    type Rule = Json5Rule;
+
+    fn get_range(&self) -> Option<Range<usize>> {
+        Some(Range { start: self.0.span.start as usize, end: self.1.span.end as usize })
+    }
+    fn from_pair(pair: TokenPair<Self::Rule>) -> std::result::Result<Self, YggdrasilError<Self::Rule>> {
+        Ok(Self(
+            pair.take_tagged_one::<KeyView>(Cow::Borrowed("object_key"))?,
+            pair.take_tagged_one::<ValueView>(Cow::Borrowed("value"))?,
+        ))
+    }
+}
+
+impl YggdrasilNode for KeyView {
+    type Rule = Json5Rule;
+
+    fn get_range(&self) -> Option<Range<usize>> {
+        Some(Range { start: self.span.start as usize, end: self.span.end as usize })
+    }
+    fn from_pair(pair: TokenPair<Self::Rule>) -> std::result::Result<Self, YggdrasilError<Self::Rule>> {
+        let quoted = pair.take_tagged_one::<SpanView>(Cow::Borrowed("string")).is_ok();
+        Ok(Self { quoted, span: span_of(&pair) })
+    }
+}
+
+impl YggdrasilNode for SpanView {
+    type Rule = Json5Rule;
+
+    fn get_range(&self) -> Option<Range<usize>> {
+        Some(Range { start: self.0.start as usize, end: self.0.end as usize })
+    }
+    fn from_pair(pair: TokenPair<Self::Rule>) -> std::result::Result<Self, YggdrasilError<Self::Rule>> {
+        Ok(Self(span_of(&pair)))
+    }
+}
+
+impl ValueView {
+    fn decode(self, input: &str) -> Result<Json5Value> {
+        let text = slice(input, &self.span);
+        let kind = match self.kind {
+            ViewKind::Null => Json5Kind::Null,
+            ViewKind::Boolean(b) => Json5Kind::Boolean(b),
+            ViewKind::Number => Json5Kind::Number(text.parse().unwrap_or(f64::NAN)),
+            ViewKind::String => Json5Kind::String(unescape(text)),
+            ViewKind::Array(items) => Json5Kind::Array(items.into_iter().map(|v| v.decode(input)).collect::<Result<_>>()?),
+            ViewKind::Object(pairs) => {
+                let mut out = Vec::with_capacity(pairs.len());
+                for (key, value) in pairs {
+                    let name = match key.quoted {
+                        true => unescape(slice(input, &key.span)),
+                        false => slice(input, &key.span).to_string(),
+                    };
+                    out.push((Json5Key { name, span: key.span }, value.decode(input)?));
+                }
+                Json5Kind::Object(out)
+            }
+        };
+        Ok(Json5Value { kind, span: self.span })
+    }
+}
+
+fn slice<'i>(input: &'i str, span: &Range<u32>) -> &'i str {
+    &input[span.start as usize..span.end as usize]
+}
+
+/// Strip the quotes of a string literal and resolve its escapes.
+fn unescape(literal: &str) -> String {
+    let mut out = String::with_capacity(literal.len());
+    decode_literal(literal, |_, c| out.push(c));
+    out
+}
+
+/// Byte offset in the string literal `literal` of the character at byte `decoded` of its value, the closing quote when
+/// `decoded` is past the end.
+pub(crate) fn literal_offset(literal: &str, decoded: usize) -> usize {
+    let (mut length, mut found) = (0, None);
+    decode_literal(literal, |offset, c| {
+        if found.is_none() && length >= decoded {
+            found = Some(offset)
+        }
+        length += c.len_utf8();
+    });
+    found.unwrap_or(literal.len().saturating_sub(1))
+}
+
+/// Call `f` with each decoded character of a string literal and the byte offset in `literal` of the text it comes from.
+fn decode_literal(literal: &str, mut f: impl FnMut(usize, char)) {
+    let inner = literal.get(1..literal.len().saturating_sub(1)).unwrap_or_default();
+    let mut chars = inner.char_indices();
+    while let Some((index, c)) = chars.next() {
+        // skip the opening quote
+        let offset = index + 1;
+        if c != '\\' {
+            f(offset, c);
+            continue;
+        }
+        match chars.next().map(|(_, c)| c) {
+            Some('b') => f(offset, '\u{8}'),
+            Some('f') => f(offset, '\u{c}'),
+            Some('n') => f(offset, '\n'),
+            Some('r') => f(offset, '\r'),
+            Some('t') => f(offset, '\t'),
+            Some('v') => f(offset, '\u{b}'),
+            Some('0') => f(offset, '\0'),
+            Some('x') => f(offset, read_code(&mut chars, 2)),
+            Some('u') => {
+                let code = read_hex(&mut chars, 4);
+                f(offset, unicode_escape(code, &mut chars))
+            }
+            // line continuations, `\r\n` is a single line terminator
+            Some('\r') => {
+                if chars.clone().next().is_some_and(|(_, c)| c == '\n') {
+                    chars.next();
+                }
+            }
+            Some('\n' | '\u{2028}' | '\u{2029}') => {}
+            Some(other) => f(offset, other),
+            None => f(offset, '\\'),
+        }
+    }
+}
+
+fn read_code(chars: &mut CharIndices, digits: usize) -> char {
+    read_hex(chars, digits).and_then(char::from_u32).unwrap_or(char::REPLACEMENT_CHARACTER)
+}
+
+fn read_hex(chars: &mut CharIndices, digits: usize) -> Option<u32> {
+    let code: String = chars.take(digits).map(|(_, c)| c).collect();
+    u32::from_str_radix(&code, 16).ok()
+}
+
+/// The character of `\u` followed by `code`, a high surrogate followed by `\u` and a low surrogate such as
+/// `\uD83D\uDE00` is one character, a lone surrogate is replaced.
+fn unicode_escape(code: Option<u32>, chars: &mut CharIndices) -> char {
+    if let Some(high @ 0xD800..=0xDBFF) = code {
+        let mut ahead = chars.clone();
+        let escaped = ahead.next().is_some_and(|(_, c)| c == '\\') && ahead.next().is_some_and(|(_, c)| c == 'u');
+        if let (true, Some(low @ 0xDC00..=0xDFFF)) = (escaped, read_hex(&mut ahead, 4)) {
+            *chars = ahead;
+            return char::from_u32(0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)).unwrap_or(char::REPLACEMENT_CHARACTER);
+        }
+    }
+    code.and_then(char::from_u32).unwrap_or(char::REPLACEMENT_CHARACTER)
+}
@@ -0,0 +1,222 @@
+use build_by_script::{
+    Error, Json5Kind, Json5Value,
+    json5::{Json5Parser, Json5Rule},
+};
+use std::{
+    collections::BTreeSet,
+    env::var_os,
+    fmt::Write,
+    fs::{read_dir, read_to_string, write},
+    path::{Path, PathBuf},
+};
+use yggdrasil_rt::{TokenPair, YggdrasilErrorKind, YggdrasilParser};
+
+pub(crate) const FIXTURES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/json5");
+
+/// Same variable as the build helper, `1` overwrites every snapshot
+const BLESS_VARIABLE: &str = "YGGDRASIL_BLESS";
+
+/// Outcome of a single fixture, rendered into snapshots
+enum Outcome {
+    Accepted { cst: String, ast: String },
+    Rejected { error: String },
+}
+
+#[test]
+fn test_fixtures() {
+    let root = Path::new(FIXTURES);
+    let known = known_failures(root);
+    let mut failures = vec![];
+    for (expect_pass, directory) in [(true, "pass"), (false, "fail")] {
+        for input in fixtures(&root.join(directory)) {
+            let name = format!("{directory}/{}", input.file_name().unwrap().to_string_lossy());
+            let text = read_to_string(&input).unwrap();
+            let outcome = run(&text);
+            let accepted = matches!(outcome, Outcome::Accepted { .. });
+            if known.contains(&name) {
+                if accepted == expect_pass {
+                    failures.push(format!("{name}: now conforms, remove it from known-failures.txt"));
+                }
+                continue;
+            }
+            if accepted != expect_pass {
+                let expected = if expect_pass { "accepted" } else { "rejected" };
+                failures.push(format!("{name}: expected to be {expected}\n{}", render(&outcome)));
+                continue;
+            }
+            match &outcome {
+                Outcome::Accepted { cst, ast } => {
+                    check_snapshot(&input.with_extension("cst"), cst, &mut failures);
+                    check_snapshot(&input.with_extension("ast"), ast, &mut failures);
+                }
+                Outcome::Rejected { error } => check_snapshot(&input.with_extension("error"), error, &mut failures),
+            }
+        }
+    }
+    for stale in known.iter().filter(|name| !root.join(name).is_file()) {
+        failures.push(format!("{stale}: listed in known-failures.txt but does not exist"));
+    }
+    assert!(failures.is_empty(), "{} fixture(s) failed:\n\n{}", failures.len(), failures.join("\n\n"));
+}
+
+/// Parse a whole document, the generated entry point stops after the first value so trailing input is checked here.
+fn run(input: &str) -> Outcome {
+    let value = match Json5Value::parse(input) {
+        Ok(o) => o,
+        Err(e) => return Outcome::Rejected { error: describe(&e) },
+    };
+    let end = value.span.end as usize;
+    if !input[end..].trim_start().is_empty() {
+        return Outcome::Rejected { error: format!("unexpected input after the value at offset {end}\n") };
+    }
+    let mut cst = String::new();
+    for pair in Json5Parser::parse_cst(input, Json5Rule::Value).expect("the value was decoded from this tree") {
+        dump_pair(pair, 0, &mut cst);
+    }
+    let mut ast = String::new();
+    dump_value(&value, 0, &mut ast);
+    Outcome::Accepted { cst, ast }
+}
+
+/// The message of a typed error, or the offset and the rules the parser tried there for a syntax error, the excerpt
+/// rendered by the runtime is left out.
+fn describe(error: &Error) -> String {
+    let Error::SyntaxError(syntax) = error
+    else {
+        return format!("{error}\n");
+    };
+    let mut out = format!("invalid json5 syntax at offset {}\n", error.offset().unwrap_or_default());
+    match &syntax.variant {
+        YggdrasilErrorKind::ParsingError { positives, negatives } => {
+            writeln!(out, "expected: {}", rule_names(positives)).unwrap();
+            if !negatives.is_empty() {
+                writeln!(out, "unexpected: {}", rule_names(negatives)).unwrap();
+            }
+        }
+        YggdrasilErrorKind::InvalidNode { expect } => writeln!(out, "invalid node: {}", expect.name()).unwrap(),
+        YggdrasilErrorKind::CustomError { message } => writeln!(out, "{message}").unwrap(),
+    }
+    out
+}
+
+/// Sorted names without repeats, the order the runtime tried them in is not part of the contract
+fn rule_names(rules: &[Json5Rule]) -> String {
+    let names: BTreeSet<_> = rules.iter().map(|rule| rule.name()).collect();
+    names.into_iter().collect::<Vec<_>>().join(", ")
+}
+
+/// One node per line as `Rule#tag@start..end`, indented by depth, the outline of the differential test.
+fn dump_pair(pair: TokenPair<Json5Rule>, depth: usize, out: &mut String) {
+    let span = pair.get_span();
+    write!(out, "{}{}", "  ".repeat(depth), pair.get_rule().name()).unwrap();
+    if let Some(tag) = pair.get_tag() {
+        write!(out, "#{tag}").unwrap();
+    }
+    writeln!(out, "@{}..{}", span.start(), span.end()).unwrap();
+    pair.into_inner().for_each(|child| dump_pair(child, depth + 1, out));
+}
+
+/// One node per line, indented by depth, so a mismatch shows up as a small line diff.
+fn dump_value(value: &Json5Value, depth: usize, out: &mut String) {
+    let indent = "  ".repeat(depth);
+    let span = &value.span;
+    let head = match &value.kind {
+        Json5Kind::Null => "null".to_string(),
+        Json5Kind::Boolean(b) => format!("boolean {b}"),
+        Json5Kind::Number(n) => format!("number {n}"),
+        Json5Kind::String(s) => format!("string {s:?}"),
+        Json5Kind::Array(_) => "array".to_string(),
+        Json5Kind::Object(_) => "object".to_string(),
+    };
+    writeln!(out, "{indent}{head} {span:?}").unwrap();
+    match &value.kind {
+        Json5Kind::Array(items) => items.iter().for_each(|item| dump_value(item, depth + 1, out)),
+        Json5Kind::Object(pairs) => {
+            for (key, value) in pairs {
+                writeln!(out, "{indent}  key {:?} {:?}", key.name, key.span).unwrap();
+                dump_value(value, depth + 2, out);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn render(outcome: &Outcome) -> String {
+    match outcome {
+        Outcome::Accepted { ast, .. } => ast.clone(),
+        Outcome::Rejected { error } => error.clone(),
+    }
+}
+
+pub(crate) fn fixtures(directory: &Path) -> Vec<PathBuf> {
+    let mut out: Vec<_> = read_dir(directory)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|e| e == "json5"))
+        .collect();
+    out.sort();
+    out
+}
+
+fn known_failures(root: &Path) -> BTreeSet<String> {
+    let text = read_to_string(root.join("known-failures.txt")).unwrap_or_default();
+    text.lines().map(str::trim).filter(|line| !line.is_empty() && !line.starts_with('#')).map(String::from).collect()
+}
+
+/// Compare against the snapshot, writing it when blessing or when it does not exist yet outside of CI.
+fn check_snapshot(path: &Path, actual: &str, failures: &mut Vec<String>) {
+    let bless = var_os(BLESS_VARIABLE).is_some_and(|v| v == "1");
+    let expected = match read_to_string(path) {
+        Ok(o) => o.replace("\r\n", "\n"),
+        Err(_) if var_os("CI").is_some() && !bless => {
+            failures.push(format!("{}: missing snapshot, run with `{BLESS_VARIABLE}=1`", path.display()));
+            return;
+        }
+        Err(_) => {
+            write(path, actual).unwrap();
+            return;
+        }
+    };
+    if expected == actual {
+        return;
+    }
+    if bless {
+        write(path, actual).unwrap();
+        return;
+    }
+    failures.push(format!(
+        "{}: snapshot mismatch, run with `{BLESS_VARIABLE}=1` to accept\n{}",
+        path.display(),
+        diff(&expected, actual)
+    ));
+}
+
+/// Line diff from the longest common subsequence, `-` for expected lines and `+` for actual lines.
+fn diff(expected: &str, actual: &str) -> String {
+    let old: Vec<_> = expected.lines().collect();
+    let new: Vec<_> = actual.lines().collect();
+    let mut table = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            table[i][j] = if old[i] == new[j] { table[i + 1][j + 1] + 1 } else { table[i + 1][j].max(table[i][j + 1]) };
+        }
+    }
+    let mut out = String::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            writeln!(out, "  {}", old[i]).unwrap();
+            i += 1;
+            j += 1;
+        }
+        else if j < new.len() && (i == old.len() || table[i][j + 1] >= table[i + 1][j]) {
+            writeln!(out, "+ {}", new[j]).unwrap();
+            j += 1;
+        }
+        else {
+            writeln!(out, "- {}", old[i]).unwrap();
+            i += 1;
+        }
+    }
+    out
+}
@@ -0,0 +1,67 @@
+use crate::conformance::{FIXTURES, fixtures};
+use build_by_script::json5::{Json5Parser, Json5Rule};
+use grammar_tools::{CstNode, GrammarFile, Interpreter};
+use std::{fmt::Write, fs::read_to_string, path::Path};
+use yggdrasil_rt::{TokenPair, YggdrasilParser};
+
+const GRAMMAR: &str = include_str!("../../grammars/json5.ygg");
+
+/// Extra inputs for shapes the fixtures do not cover
+const INPUTS: &[&str] = &["{a:1 ,b:2}", "[1,]", "\"\\u0041\"", "'raw'", "-0", "{}", "[ ]", "tru", "", " null"];
+
+/// The interpreter must accept and reject the same input as the generated parser, with the same tree.
+#[test]
+fn test_interpreter_matches_generated() {
+    let interpreter = Interpreter::new(&GrammarFile::parse(GRAMMAR).unwrap()).unwrap();
+    let root = Path::new(FIXTURES);
+    let mut inputs: Vec<(String, String)> = INPUTS.iter().map(|input| (format!("{input:?}"), input.to_string())).collect();
+    for directory in ["pass", "fail"] {
+        for path in fixtures(&root.join(directory)) {
+            let name = format!("{directory}/{}", path.file_name().unwrap().to_string_lossy());
+            inputs.push((name, read_to_string(&path).unwrap()));
+        }
+    }
+    let mut failures = vec![];
+    for (name, input) in &inputs {
+        for rule in Json5Rule::parsable_rules() {
+            let generated = match Json5Parser::parse_cst(input, *rule) {
+                Ok(o) => Some(o.into_iter().map(generated_outline).collect::<Vec<_>>().join("\n")),
+                Err(_) => None,
+            };
+            let interpreted = interpreter.parse(input, rule.name()).ok().map(|node| interpreted_outline(&node));
+            if generated != interpreted {
+                failures.push(format!("{name} as {rule:?}\ngenerated:   {generated:?}\ninterpreted: {interpreted:?}"));
+            }
+        }
+    }
+    assert!(failures.is_empty(), "{} difference(s):\n\n{}", failures.len(), failures.join("\n\n"));
+}
+
+/// `Rule#tag@start..end(children)`
+fn generated_outline(pair: TokenPair<Json5Rule>) -> String {
+    let span = pair.get_span();
+    let head = outline_head(pair.get_rule().name(), pair.get_tag().map(|tag| tag.to_string()), span.start(), span.end());
+    let children: Vec<_> = pair.into_inner().map(generated_outline).collect();
+    outline_join(head, children)
+}
+
+fn interpreted_outline(node: &CstNode) -> String {
+    let head = outline_head(&node.rule, node.tag.clone(), node.span.start, node.span.end);
+    outline_join(head, node.children.iter().map(interpreted_outline).collect())
+}
+
+fn outline_head(rule: &str, tag: Option<String>, start: usize, end: usize) -> String {
+    let mut out = rule.to_string();
+    if let Some(tag) = tag {
+        write!(out, "#{tag}").unwrap();
+    }
+    write!(out, "@{start}..{end}").unwrap();
+    out
+}
+
+fn outline_join(head: String, children: Vec<String>) -> String {
+    match children.is_empty() {
+        true => head,
+        false => format!("{head}({})", children.join(" ")),
+    }
+}
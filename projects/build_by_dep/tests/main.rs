@@ -1,23 +1,305 @@
-use build_by_script::json5::{Json5Parser, Json5Rule, ValueNode};
+use build_by_script::{
+    DEFAULT_MAX_DEPTH, Error, Json5Kind, Json5Value, ParseLimits,
+    config::{ProjectConfig, TargetLanguage},
+    json5::{self, Json5Parser, Json5Rule, RuleKind},
+    settings::{SettingsNode, SettingsParser, SettingsRule},
+};
+use std::{path::Path, time::Duration};
 use yggdrasil_rt::{YggdrasilNode, YggdrasilParser};
 
+mod conformance;
+mod differential;
+mod roundtrip;
+
 #[test]
 fn ready() {
     println!("it works!")
 }
 
 #[test]
-fn test_unicode() {
-    let cst = Json5Parser::parse_cst("{int: 1, bool: [true, false]}", Json5Rule::Value).unwrap();
-    println!("Short Form:\n{}", cst);
-    let first = ValueNode::from_cst(cst).unwrap();
-    println!("{:#?}", first)
+fn test_parsable_rules() {
+    assert!(Json5Rule::Value.is_parsable());
+    assert!(Json5Rule::parsable_rules().contains(&Json5Rule::StringEscape));
+    assert!(!Json5Rule::IgnoreText.is_parsable());
+    assert!(!Json5Rule::parsable_rules().contains(&Json5Rule::IgnoreRegex));
+    // helper rules are rejected with their name instead of aborting the process
+    let limits = ParseLimits::default();
+    for rule in [Json5Rule::IgnoreText, Json5Rule::IgnoreRegex] {
+        let error = Error::from(Json5Parser::parse_cst("'text'", rule).unwrap_err());
+        assert!(matches!(error, Error::NotParsable { rule: r } if r == rule));
+        assert!(matches!(Json5Parser::parse_cst_limited("x", rule, &limits), Err(Error::NotParsable { rule: r }) if r == rule));
+    }
+    assert!(matches!(Error::from(Json5Parser::parse_cst("]", Json5Rule::Value).unwrap_err()), Error::SyntaxError(_)));
+}
+
+#[test]
+fn test_rule_info() {
+    let grammar = include_str!("../grammars/json5.ygg");
+    assert_eq!(Json5Rule::RULE_INFO.len(), 17);
+    for (index, info) in Json5Rule::RULE_INFO.iter().enumerate() {
+        assert_eq!(info.rule as usize, index);
+        assert_eq!(Json5Rule::from_name(info.name), Some(info.rule));
+    }
+    let value = Json5Rule::Value.info();
+    assert_eq!((value.name, value.kind, value.entry), ("Value", RuleKind::Union, true));
+    assert!(grammar[value.span.clone()].starts_with("entry union Value {"));
+    assert_eq!(value.tags, ["object", "array", "string", "number", "boolean", "null"]);
+    assert_eq!(Json5Rule::ObjectPair.info().tags, ["object_key", "value"]);
+    assert_eq!(Json5Rule::StringEscape.info().tags, ["c"]);
+    assert!(Json5Rule::String.info().atomic);
+    assert!(Json5Rule::WhiteSpace.info().ignored);
+    assert!(grammar[Json5Rule::String.info().span.clone()].starts_with("@style(string)"));
+    let builtin = Json5Rule::IgnoreText.info();
+    assert_eq!((builtin.kind, builtin.span.clone()), (RuleKind::Builtin, 0..0));
+    assert_eq!(Json5Rule::from_name("Missing"), None);
+}
+
+#[test]
+fn test_imported_nodes() {
+    let input = "retries = 3\nserver = { timeout: 30, ports: [80, 443] }\n";
+    let settings = SettingsNode::from_cst(SettingsParser::parse_cst(input, SettingsRule::Settings).unwrap()).unwrap();
+    assert_eq!(settings.entry.len(), 2);
+    assert_eq!(settings.entry[1].key.span, 12..18);
+    // imported nodes are parsed again where their field starts, their spans are the ones of the whole input
+    let json5::ValueNode::Object(server) = &settings.entry[1].value
+    else {
+        panic!("expected an object")
+    };
+    assert_eq!(server.span, 21..54);
+    let timeout = &server.object_pair[0].span;
+    assert_eq!(&input[timeout.start as usize..timeout.end as usize], "timeout: 30");
+    assert!(matches!(&server.object_pair[1].value, json5::ValueNode::Array(ports) if ports.value.len() == 2));
+    assert!(SettingsParser::parse_cst("server = {\n}", SettingsRule::Settings).is_err());
+    // what they are parsed with
+    let array = json5::Json5Parser::parse_cst_at("é = [1]", 5, json5::Json5Rule::Value).unwrap();
+    assert_eq!(array.clone().next().unwrap().get_span().start(), 5);
+    assert!(json5::Json5Parser::parse_cst_at("é = [1]", 1, json5::Json5Rule::Value).is_err());
+}
+
+#[test]
+fn test_derive_annotation() {
+    // `@derive(Default)` on `class Array`
+    let empty = json5::ArrayNode::default();
+    assert!(empty.value.is_empty());
+    assert_eq!(empty.span, 0..0);
+}
+
+#[test]
+fn test_captured_escape() {
+    let input = "\\n";
+    let cst = Json5Parser::parse_cst(input, Json5Rule::StringEscape).unwrap();
+    let escape = json5::StringEscapeNode::from_cst(cst).unwrap();
+    assert_eq!(escape.c.span, 1..2);
+    assert_eq!(escape.c.text(input), "n");
+}
+
+#[test]
+fn test_redirect() {
+    // `class StringRaw -> StringText`, the union target gets a variant holding the redirected node
+    let cst = Json5Parser::parse_cst("raw text", Json5Rule::StringRaw).unwrap();
+    let text = json5::StringTextNode::from_cst(cst).unwrap();
+    assert!(matches!(&text, json5::StringTextNode::StringRaw(raw) if raw.span == (0..8)));
+    let cst = Json5Parser::parse_cst("\\n", Json5Rule::StringText).unwrap();
+    assert!(matches!(json5::StringTextNode::from_cst(cst).unwrap(), json5::StringTextNode::StringEscape(_)));
+    // the single quoted branch of `String` holds the target node
+    let _: fn(json5::StringNode) -> Option<json5::StringTextNode> = |string| match string {
+        json5::StringNode::String0(text) => Some(text),
+        json5::StringNode::String1(_) => None,
+    };
+}
+
+#[test]
+fn test_nesting_too_deep() {
+    let adversarial = "[".repeat(100_000);
+    match Json5Parser::parse_cst_bounded(&adversarial, Json5Rule::Value, DEFAULT_MAX_DEPTH) {
+        Err(Error::NestingTooDeep { depth, limit, offset }) => {
+            assert_eq!(depth, DEFAULT_MAX_DEPTH + 1);
+            assert_eq!(limit, DEFAULT_MAX_DEPTH);
+            // `Value` and `Array` open a level each
+            assert_eq!(offset, DEFAULT_MAX_DEPTH / 2);
+        }
+        other => panic!("expected nesting error, got {:?}", other.map(|_| ())),
+    }
+    let objects = "{a:".repeat(100_000);
+    assert!(matches!(Json5Parser::parse_value(&objects), Err(Error::NestingTooDeep { .. })));
+}
+
+#[test]
+fn test_nesting_within_limit() {
+    let input = format!("{}{}", "[".repeat(64), "]".repeat(64));
+    assert!(Json5Parser::parse_value(&input).is_ok());
+    let input = format!("{}{}", "[".repeat(8), "]".repeat(8));
+    assert!(matches!(
+        Json5Parser::parse_cst_bounded(&input, Json5Rule::Value, 4),
+        Err(Error::NestingTooDeep { offset: 2, .. })
+    ));
+}
+
+#[test]
+fn test_nesting_ignores_strings() {
+    let input = format!("['{}']", "[".repeat(1000));
+    assert!(!matches!(Json5Parser::parse_value(&input), Err(Error::NestingTooDeep { .. })));
+}
+
+#[test]
+fn test_nesting_counted_by_parser() {
+    // inputs a scanner of the raw text would read differently from the grammar
+    for prefix in ["['\\\\', ", "['\\', ", "/*'*/"] {
+        let input = format!("{prefix}{}", "[".repeat(100_000));
+        assert!(Json5Parser::parse_value(&input).is_err(), "{prefix}");
+    }
+    let input = format!("['\\\\', {}", "[".repeat(100_000));
+    assert!(matches!(Json5Parser::parse_value(&input), Err(Error::NestingTooDeep { offset: 134, .. })));
+}
+
+#[test]
+fn test_limit_input_size() {
+    let limits = ParseLimits::default().with_max_input_bytes(8);
+    assert!(matches!(
+        Json5Parser::parse_value_limited("[1, 2, 3, 4]", &limits),
+        Err(Error::InputTooLarge { size: 12, limit: 8 })
+    ));
+}
+
+#[test]
+fn test_limit_literal_length() {
+    let limits = ParseLimits::default().with_max_string_length(16).with_max_number_length(4);
+    let input = format!("['{}']", "a".repeat(1024));
+    assert!(matches!(Json5Parser::parse_value_limited(&input, &limits), Err(Error::StringTooLong { offset: 1, .. })));
+    let input = format!("{{{}: 1}}", "key".repeat(1024));
+    assert!(matches!(Json5Parser::parse_value_limited(&input, &limits), Err(Error::StringTooLong { offset: 1, .. })));
+    let input = format!("[1, {}]", "9".repeat(1024));
+    assert!(matches!(
+        Json5Parser::parse_value_limited(&input, &limits),
+        Err(Error::NumberTooLong { length: 1024, limit: 4, offset: 4 })
+    ));
+}
+
+#[test]
+fn test_limit_node_count() {
+    let limits = ParseLimits::default().with_max_nodes(100);
+    let input = format!("[{}]", "1,".repeat(10_000));
+    // the parser stops within the first hundred elements instead of reading the whole document
+    match Json5Parser::parse_value_limited(&input, &limits) {
+        Err(Error::TooManyNodes { count: 101, limit: 100, offset }) => assert!(offset < 200, "{offset}"),
+        other => panic!("expected node count error, got {:?}", other.map(|_| ())),
+    }
+    assert!(Json5Parser::parse_value_limited("[1,2,3]", &limits).is_ok());
+}
+
+#[test]
+fn test_limit_step_count() {
+    let limits = ParseLimits::default().with_max_steps(1000);
+    let input = format!("[{}]", "1,".repeat(10_000));
+    match Json5Parser::parse_value_limited(&input, &limits) {
+        Err(Error::TooManySteps { limit: 1000, offset }) => assert!(offset < 2000, "{offset}"),
+        other => panic!("expected step count error, got {:?}", other.map(|_| ())),
+    }
+    assert!(Json5Parser::parse_value_limited("[1,2,3]", &limits).is_ok());
+}
+
+#[test]
+fn test_limit_reject_after() {
+    let limits = ParseLimits::default().with_reject_after(Duration::ZERO);
+    let input = format!("[{}]", "true,".repeat(10_000));
+    // the clock is read before the first rule call
+    assert!(matches!(Json5Parser::parse_value_limited(&input, &limits), Err(Error::TookTooLong { offset: 0, .. })));
+    let limits = ParseLimits::default().with_reject_after(Duration::from_secs(3600));
+    assert!(Json5Parser::parse_value_limited("[true, false]", &limits).is_ok());
+}
+
+#[test]
+fn test_decode_value() {
+    let input = "{export: \"src/\", 'quoted key': 'raw', escapes: \"a\\tb\\u0041\\\"\", list: [1, null, true]}";
+    let value = Json5Value::parse(input).unwrap();
+    assert_eq!(value.get("export").and_then(Json5Value::as_str), Some("src/"));
+    assert_eq!(value.get("escapes").and_then(Json5Value::as_str), Some("a\tbA\""));
+    let list = value.get("list").and_then(Json5Value::as_array).unwrap();
+    assert_eq!(
+        list.iter().map(|v| v.kind.clone()).collect::<Vec<_>>(),
+        vec![Json5Kind::Number(1.0), Json5Kind::Null, Json5Kind::Boolean(true)]
+    );
+    assert_eq!(list[1].span, 73..77);
+    assert_eq!(value.get("quoted key").and_then(Json5Value::as_str), Some("raw"));
+    let booleans = Json5Value::parse("[true, false]").unwrap();
+    let booleans: Vec<_> = booleans.as_array().unwrap().iter().map(|v| v.as_bool()).collect();
+    assert_eq!(booleans, [Some(true), Some(false)]);
+}
+
+#[test]
+fn test_decode_escapes() {
+    let decode = |input: &str| Json5Value::parse(input).unwrap().as_str().map(str::to_string);
+    // a surrogate pair is one character, a lone surrogate is replaced
+    assert_eq!(decode("'\\uD83D\\uDE00'").as_deref(), Some("\u{1F600}"));
+    assert_eq!(decode("\"\\ud83d\\ude00!\"").as_deref(), Some("\u{1F600}!"));
+    assert_eq!(decode("'\\uD83Dx'").as_deref(), Some("\u{FFFD}x"));
+    assert_eq!(decode("'\\uDE00\\uD83D'").as_deref(), Some("\u{FFFD}\u{FFFD}"));
+    // a backslash before a line terminator continues the string on the next line
+    assert_eq!(decode("'a\\\nb'").as_deref(), Some("ab"));
+    assert_eq!(decode("'a\\\r\nb'").as_deref(), Some("ab"));
+    assert_eq!(decode("\"a\\\rb\"").as_deref(), Some("ab"));
+    assert_eq!(decode("'a\\\u{2028}b'").as_deref(), Some("ab"));
+}
+
+#[test]
+fn test_project_config() {
+    let text = include_str!("../../build_by_cli/Yggdrasil.json5");
+    let config = ProjectConfig::parse(text, "project/Yggdrasil.json5").unwrap();
+    assert_eq!(config.export, Path::new("project/src/"));
+    assert_eq!(config.language, TargetLanguage::Rust);
+    assert_eq!(config.includes, vec!["grammars/*.ygg"]);
+    assert!(config.warnings.is_empty());
+    let options = config.grammar_options("json5");
+    assert_eq!(options.output, Path::new("project/src/json5"));
+    assert!(options.serde);
+    assert_eq!(options.derives, vec!["PartialEq", "Eq"]);
+}
+
+#[test]
+fn test_project_config_overrides() {
+    let text = "{\n    export: \"src/\",\n    grammars: {\n        json5: {output: \"gen/json5\", derives: [\"PartialEq\", \"Eq\"], serde: false, color: 1},\n    },\n    colour: true,\n}";
+    let config = ProjectConfig::parse(text, "Yggdrasil.json5").unwrap();
+    let options = config.grammar_options("json5");
+    assert_eq!(options.output, Path::new("gen/json5"));
+    assert_eq!(options.derives, vec!["PartialEq", "Eq"]);
+    assert!(!options.serde);
+    let warnings: Vec<_> = config.warnings.iter().map(|w| (w.line, w.column)).collect();
+    assert_eq!(warnings, vec![(4, 82), (6, 5)]);
+    // the derives of a grammar replace the project ones
+    let text = "{derives: [\"PartialEq\"], grammars: {json5: {derives: []}}}";
+    let config = ProjectConfig::parse(text, "Yggdrasil.json5").unwrap();
+    assert_eq!(config.grammar_options("json5").derives, Vec::<String>::new());
+    assert_eq!(config.grammar_options("settings").derives, vec!["PartialEq"]);
+    // a repeated key is a warning, the last value wins
+    let text = "{export: \"a/\", export: \"b/\", grammars: {json5: {serde: true, serde: false}}}";
+    let config = ProjectConfig::parse(text, "Yggdrasil.json5").unwrap();
+    assert_eq!(config.export, Path::new("b/"));
+    assert!(!config.grammar_options("json5").serde);
+    let warnings: Vec<_> = config.warnings.iter().map(ToString::to_string).collect();
+    assert_eq!(warnings, vec![
+        "Yggdrasil.json5:1:16: duplicate key `export`, the last value is used",
+        "Yggdrasil.json5:1:62: duplicate key `serde`, the last value is used",
+    ]);
 }
 
 #[test]
-fn test_ascii() {
-    let cst = Json5Parser::parse_cst("[true, false, 1, 2, null]", Json5Rule::Value).unwrap();
-    println!("Short Form:\n{}", cst);
-    let first = ValueNode::from_cst(cst).unwrap();
-    println!("{:#?}", first)
+fn test_project_config_errors() {
+    let error =
+        ProjectConfig::parse("{\n    includes: [\n        \"grammars/[a-z.ygg\",\n    ],\n}", "Yggdrasil.json5").unwrap_err();
+    assert_eq!((error.line, error.column), (3, 19));
+    let error = ProjectConfig::parse("{\n    includes: [\"a/**b\"]\n}", "Yggdrasil.json5").unwrap_err();
+    assert_eq!((error.line, error.column), (2, 19));
+    // the column is in the literal, after the escape
+    let error = ProjectConfig::parse("{\n    includes: [\"a\\u002f**b\"]\n}", "Yggdrasil.json5").unwrap_err();
+    assert_eq!((error.line, error.column), (2, 24));
+    let error = ProjectConfig::parse("{\n  export: ,\n}", "Yggdrasil.json5").unwrap_err();
+    assert_eq!(error.to_string(), "Yggdrasil.json5:2:11: invalid json5 syntax");
+    let error = ProjectConfig::parse("{\n  language: \"ts\"\n}", "Yggdrasil.json5").unwrap_err();
+    assert_eq!(error.to_string(), "Yggdrasil.json5:2:13: unsupported language `ts`, expected `rs` or `rust`");
+    assert!(ProjectConfig::parse("{language: \"rust\"}", "Yggdrasil.json5").is_ok());
+    let error = ProjectConfig::parse("{grammars: {json5: {wasm: true}}}", "Yggdrasil.json5").unwrap_err();
+    assert_eq!(error.to_string(), "Yggdrasil.json5:1:27: `wasm` bindings are not generated, expected `false`");
+    assert!(ProjectConfig::parse("{grammars: {json5: {wasm: false}}}", "Yggdrasil.json5").is_ok());
+    let error = ProjectConfig::parse("{\n  grammars: {json5: {serde: \"yes\"}}\n}", "Yggdrasil.json5").unwrap_err();
+    assert_eq!((error.line, error.column), (2, 29));
 }
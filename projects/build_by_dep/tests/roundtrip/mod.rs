@@ -0,0 +1,253 @@
+use build_by_script::{Json5Kind, Json5Value};
+use proptest::prelude::*;
+use std::fmt::Write;
+
+/// The document model the generators produce, independent of the parser under test
+#[derive(Clone, Debug)]
+enum Doc {
+    Null,
+    Boolean(bool),
+    Number(i64),
+    String(String),
+    Array(Vec<Doc>),
+    Object(Vec<(Key, Doc)>),
+}
+
+/// Every key form of `ObjectKey`
+#[derive(Clone, Debug)]
+enum Key {
+    Integer(u32),
+    Identifier(String),
+    Quoted(String),
+}
+
+#[derive(Copy, Clone, Debug)]
+enum Escape {
+    /// `\n`, `\t` and friends where they exist, `\u` otherwise
+    Short,
+    /// `\x0a`
+    Hex,
+    /// `\u000a`
+    Unicode,
+}
+
+#[derive(Clone, Debug)]
+struct PrintOptions {
+    /// Spaces per level, `None` prints everything on one line
+    indent: Option<usize>,
+    /// Emit a comma after the last element of non-empty arrays and objects
+    trailing_comma: bool,
+    /// Prefer `'` over `"` where the string allows it
+    single_quotes: bool,
+    /// Print identifier and integer keys without quotes
+    unquoted_keys: bool,
+    /// Prefix positive numbers with `+`
+    explicit_plus: bool,
+    escape: Escape,
+    /// Separator between tokens, besides the indentation
+    space: &'static str,
+    /// Put a block and a line comment before every element
+    comments: bool,
+}
+
+/// Semantic content of a document, spans and key forms are forgotten
+#[derive(Clone, Debug, PartialEq)]
+enum Plain {
+    Null,
+    Boolean(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Plain>),
+    Object(Vec<(String, Plain)>),
+}
+
+impl Key {
+    fn name(&self) -> String {
+        match self {
+            Key::Integer(i) => i.to_string(),
+            Key::Identifier(s) | Key::Quoted(s) => s.clone(),
+        }
+    }
+}
+
+impl Doc {
+    fn plain(&self) -> Plain {
+        match self {
+            Doc::Null => Plain::Null,
+            Doc::Boolean(b) => Plain::Boolean(*b),
+            Doc::Number(n) => Plain::Number(*n as f64),
+            Doc::String(s) => Plain::String(s.clone()),
+            Doc::Array(items) => Plain::Array(items.iter().map(Doc::plain).collect()),
+            Doc::Object(pairs) => Plain::Object(pairs.iter().map(|(k, v)| (k.name(), v.plain())).collect()),
+        }
+    }
+}
+
+fn plain(value: &Json5Value) -> Plain {
+    match &value.kind {
+        Json5Kind::Null => Plain::Null,
+        Json5Kind::Boolean(b) => Plain::Boolean(*b),
+        Json5Kind::Number(n) => Plain::Number(*n),
+        Json5Kind::String(s) => Plain::String(s.clone()),
+        Json5Kind::Array(items) => Plain::Array(items.iter().map(plain).collect()),
+        Json5Kind::Object(pairs) => Plain::Object(pairs.iter().map(|(k, v)| (k.name.clone(), plain(v))).collect()),
+    }
+}
+
+fn print(doc: &Doc, options: &PrintOptions) -> String {
+    let mut out = String::new();
+    print_value(doc, options, 0, &mut out);
+    out
+}
+
+fn print_value(doc: &Doc, options: &PrintOptions, depth: usize, out: &mut String) {
+    match doc {
+        Doc::Null => out.push_str("null"),
+        Doc::Boolean(b) => write!(out, "{b}").unwrap(),
+        Doc::Number(n) if *n >= 0 && options.explicit_plus => write!(out, "+{n}").unwrap(),
+        Doc::Number(n) => write!(out, "{n}").unwrap(),
+        Doc::String(s) => print_string(s, options, out),
+        Doc::Array(items) => {
+            print_items(('[', ']'), items, options, depth, out, |item, out| print_value(item, options, depth + 1, out))
+        }
+        Doc::Object(pairs) => print_items(('{', '}'), pairs, options, depth, out, |(key, value), out| {
+            match key {
+                Key::Integer(i) if options.unquoted_keys => write!(out, "{i}").unwrap(),
+                Key::Identifier(s) if options.unquoted_keys => out.push_str(s),
+                _ => print_string(&key.name(), options, out),
+            }
+            out.push(':');
+            out.push_str(options.space);
+            print_value(value, options, depth + 1, out)
+        }),
+    }
+}
+
+fn print_items<T>(
+    (open, close): (char, char),
+    items: &[T],
+    options: &PrintOptions,
+    depth: usize,
+    out: &mut String,
+    mut print_item: impl FnMut(&T, &mut String),
+) {
+    out.push(open);
+    for (index, item) in items.iter().enumerate() {
+        if index > 0 {
+            out.push(',');
+        }
+        new_line(options, depth + 1, out);
+        if options.comments {
+            out.push_str("/* item */");
+            out.push_str(options.space);
+            out.push_str("// item\n");
+        }
+        print_item(item, out);
+    }
+    if !items.is_empty() {
+        if options.trailing_comma {
+            out.push(',');
+        }
+        new_line(options, depth, out);
+    }
+    out.push(close);
+}
+
+fn new_line(options: &PrintOptions, depth: usize, out: &mut String) {
+    match options.indent {
+        Some(width) => {
+            out.push('\n');
+            out.push_str(&" ".repeat(width * depth));
+        }
+        None => out.push_str(options.space),
+    }
+}
+
+/// The grammar cannot escape `'` inside single quotes, strings holding one always use double quotes.
+fn print_string(text: &str, options: &PrintOptions, out: &mut String) {
+    let quote = if options.single_quotes && !text.contains('\'') { '\'' } else { '"' };
+    out.push(quote);
+    for c in text.chars() {
+        match (c, options.escape) {
+            ('\\', _) => out.push_str("\\\\"),
+            (c, _) if c == quote => write!(out, "\\{c}").unwrap(),
+            ('\n', Escape::Short) => out.push_str("\\n"),
+            ('\r', Escape::Short) => out.push_str("\\r"),
+            ('\t', Escape::Short) => out.push_str("\\t"),
+            ('\u{8}', Escape::Short) => out.push_str("\\b"),
+            ('\u{c}', Escape::Short) => out.push_str("\\f"),
+            ('\u{b}', Escape::Short) => out.push_str("\\v"),
+            (c, Escape::Hex) if (c as u32) < 0x20 => write!(out, "\\x{:02x}", c as u32).unwrap(),
+            (c, _) if (c as u32) < 0x20 || c == '\u{2028}' || c == '\u{2029}' => write!(out, "\\u{:04x}", c as u32).unwrap(),
+            (c, _) => out.push(c),
+        }
+    }
+    out.push(quote);
+}
+
+fn text() -> impl Strategy<Value = String> {
+    let special =
+        prop::sample::select(vec!['"', '\'', '\\', '/', '\n', '\r', '\t', '\0', '\u{8}', '\u{2028}', 'é', '中', '😀']);
+    prop::collection::vec(prop_oneof![any::<char>(), special], 0..12).prop_map(|chars| chars.into_iter().collect())
+}
+
+fn key() -> impl Strategy<Value = Key> {
+    prop_oneof![
+        any::<u32>().prop_map(Key::Integer),
+        // reserved words included, `true` is a valid key
+        prop_oneof![Just("true".to_string()), "[_a-zA-Zéλß中я][_a-zA-Z0-9éλß中я]{0,8}"].prop_map(Key::Identifier),
+        text().prop_map(Key::Quoted),
+    ]
+}
+
+fn doc() -> impl Strategy<Value = Doc> {
+    // exactly representable as `f64`, so the decoded number compares equal
+    let number = -(1i64 << 53)..(1i64 << 53);
+    let leaf = prop_oneof![
+        Just(Doc::Null),
+        any::<bool>().prop_map(Doc::Boolean),
+        number.prop_map(Doc::Number),
+        text().prop_map(Doc::String),
+    ];
+    leaf.prop_recursive(4, 64, 6, |inner| {
+        prop_oneof![
+            prop::collection::vec(inner.clone(), 0..6).prop_map(Doc::Array),
+            prop::collection::vec((key(), inner), 0..6).prop_map(Doc::Object),
+        ]
+    })
+}
+
+fn options(comments: bool) -> impl Strategy<Value = PrintOptions> {
+    let indent = prop_oneof![Just(None), (1usize..5).prop_map(Some)];
+    let escape = prop_oneof![Just(Escape::Short), Just(Escape::Hex), Just(Escape::Unicode)];
+    // every separator is `\p{WhiteSpace}`, the only whitespace the grammar ignores
+    let space = prop::sample::select(vec!["", " ", "\t", "\r\n", "\u{a0}", "\u{2003}", "\u{2029}"]);
+    (indent, any::<[bool; 4]>(), escape, space).prop_map(move |(indent, flags, escape, space)| PrintOptions {
+        indent,
+        trailing_comma: flags[0],
+        single_quotes: flags[1],
+        unquoted_keys: flags[2],
+        explicit_plus: flags[3],
+        escape,
+        space,
+        comments,
+    })
+}
+
+proptest! {
+    #[test]
+    fn test_round_trip(doc in doc(), options in options(false)) {
+        let text = print(&doc, &options);
+        let value = Json5Value::parse(&text).map_err(|e| TestCaseError::fail(format!("{e}\n{text}")))?;
+        prop_assert_eq!(value.span.end as usize, text.len(), "trailing input in\n{}", text);
+        prop_assert_eq!(plain(&value), doc.plain(), "printed as\n{}", text);
+    }
+
+    #[test]
+    #[ignore = "comments are not part of the grammar yet"]
+    fn test_round_trip_comments(doc in doc(), options in options(true)) {
+        let text = print(&doc, &options);
+        let value = Json5Value::parse(&text).map_err(|e| TestCaseError::fail(format!("{e}\n{text}")))?;
+        prop_assert_eq!(plain(&value), doc.plain(), "printed as\n{}", text);
+    }
+}
@@ -0,0 +1,267 @@
+use crate::{
+    BuildError, Result, module_name,
+    patch::{ImportedModule, patch_module},
+};
+use grammar_tools::{GrammarError, GrammarFile};
+use std::{
+    collections::BTreeSet,
+    env::var_os,
+    ffi::OsString,
+    fs::{copy, create_dir_all, read, read_dir, remove_dir_all, remove_file, write},
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+use yggdrasil_shared::codegen::RustCodegen;
+
+/// Set this environment variable to `1` to overwrite stale checked in modules instead of failing the build
+pub const BLESS_VARIABLE: &str = "YGGDRASIL_BLESS";
+
+/// Where the generated module lives
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum OutputMode {
+    /// Write into `$OUT_DIR/<module>` and pull it in with [`include_parser!`](crate::include_parser)
+    OutDir,
+    /// Keep the module committed in the given directory, the build fails when the committed files are stale
+    CheckedIn(PathBuf),
+}
+
+/// Generate a rust module from a grammar inside a build script
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ParserBuilder {
+    module: String,
+    mode: OutputMode,
+    imports: Vec<(String, String)>,
+    derives: Vec<String>,
+    serde: bool,
+    build_dir: Option<PathBuf>,
+}
+
+impl ParserBuilder {
+    /// Generate the module `module`, into `OUT_DIR` by default
+    pub fn new<S: Into<String>>(module: S) -> Self {
+        Self { module: module.into(), mode: OutputMode::OutDir, imports: vec![], derives: vec![], serde: true, build_dir: None }
+    }
+    /// Write into `$OUT_DIR/<module>`
+    pub fn out_dir(mut self) -> Self {
+        self.mode = OutputMode::OutDir;
+        self
+    }
+    /// Keep the module committed in `directory` and check it is up to date
+    pub fn checked_in<P: Into<PathBuf>>(mut self, directory: P) -> Self {
+        self.mode = OutputMode::CheckedIn(directory.into());
+        self
+    }
+    /// Generate into `directory/<module>` instead of `$OUT_DIR/<module>`, for callers that are not build scripts
+    pub fn build_dir<P: Into<PathBuf>>(mut self, directory: P) -> Self {
+        self.build_dir = Some(directory.into());
+        self
+    }
+    /// Give the text of the grammar generating the module `module`, which `import` statements naming `<module>.ygg`
+    /// read.
+    ///
+    /// The imported module must be generated too, as a sibling of this one, its nodes are used in place of copies.
+    pub fn import<S: Into<String>>(mut self, module: S, grammar: &str) -> Self {
+        self.imports.push((module.into(), grammar.replace("\r\n", "\n")));
+        self
+    }
+    /// Name of the generated module
+    pub fn module(&self) -> &str {
+        &self.module
+    }
+    /// Where the generated module lives
+    pub fn mode(&self) -> &OutputMode {
+        &self.mode
+    }
+    /// Derive `derive`, such as `PartialEq`, on every node besides the derives of the generator and the `@derive`
+    /// annotations of the rule
+    pub fn derive<S: Into<String>>(mut self, derive: S) -> Self {
+        self.derives.push(derive.into());
+        self
+    }
+    /// The derives added to every node
+    pub fn derives(&self) -> &[String] {
+        &self.derives
+    }
+    /// Keep the `serde` derives of the nodes, which apply when the crate has a `serde` feature, on by default
+    pub fn serde(mut self, enabled: bool) -> Self {
+        self.serde = enabled;
+        self
+    }
+    /// Whether the nodes keep their `serde` derives
+    pub fn has_serde(&self) -> bool {
+        self.serde
+    }
+    /// Generate the module from the grammar text, returns the directory holding the usable module.
+    ///
+    /// Left-recursive grammars are rejected before anything is generated, the generated parser would never return and does
+    /// not grow seeds like the interpreter of `grammar_tools`.
+    pub fn generate(&self, grammar: &str) -> Result<PathBuf> {
+        // spans end up in the generated files, they must not depend on the line endings of the checkout
+        let grammar = &grammar.replace("\r\n", "\n");
+        let (file, imports) = self.read_grammar(grammar)?;
+        let out_dir = match &self.build_dir {
+            Some(directory) => directory.clone(),
+            None => PathBuf::from(var_os("OUT_DIR").ok_or(BuildError::MissingOutDir)?),
+        };
+        let staging = out_dir.join(&self.module);
+        // files patched in for the previous grammar, such as `nesting.rs`, must not outlive it
+        match remove_dir_all(&staging) {
+            Err(e) if e.kind() != ErrorKind::NotFound => return Err(BuildError::io(&staging)(e)),
+            _ => {}
+        }
+        // `operators` rules are unknown to the code generator, it sees the plain rules they lower to
+        RustCodegen::default()
+            .generate(&file.lower(grammar), &staging)
+            .map_err(|e| BuildError::CodegenError { module: self.module.clone(), message: format!("{e:?}") })?;
+        patch_module(&self.module, &file, &imports, &self.derives, self.serde, &staging)?;
+        match &self.mode {
+            OutputMode::OutDir => {
+                // `#[path]` is resolved relative to the including file, an absolute path keeps `mod.rs` able to find
+                // its siblings
+                let wrapper = out_dir.join(format!("{}.rs", self.module));
+                let text = format!(
+                    "/// Parser generated from the `{module}` grammar\n#[path = {path:?}]\npub mod {module};\n",
+                    module = self.module,
+                    path = staging.join("mod.rs").display().to_string(),
+                );
+                write(&wrapper, text).map_err(BuildError::io(&wrapper))?;
+                Ok(staging)
+            }
+            OutputMode::CheckedIn(directory) => {
+                println!("cargo:rerun-if-env-changed={BLESS_VARIABLE}");
+                if var_os(BLESS_VARIABLE).is_some_and(|v| v == "1") {
+                    bless(&staging, directory)?;
+                    return Ok(directory.clone());
+                }
+                if !directory.exists() {
+                    // nothing to compare with yet, the first build writes the module
+                    bless(&staging, directory)?;
+                    println!("cargo:warning=generated `{}` into {}, commit it", self.module, directory.display());
+                    return Ok(directory.clone());
+                }
+                let files = stale_files(&staging, directory)?;
+                if !files.is_empty() {
+                    return Err(BuildError::StaleFiles { directory: directory.clone(), files });
+                }
+                Ok(directory.clone())
+            }
+        }
+    }
+    /// Apply the fixes `generate` makes to the output of the code generator, already written into `directory`.
+    ///
+    /// Tests of the fixes run this over a fixed generator output instead of the generator itself.
+    pub fn patch_generated<P: AsRef<Path>>(&self, grammar: &str, directory: P) -> Result<()> {
+        let grammar = &grammar.replace("\r\n", "\n");
+        let (file, imports) = self.read_grammar(grammar)?;
+        patch_module(&self.module, &file, &imports, &self.derives, self.serde, directory.as_ref())
+    }
+    /// Parse the grammar and link its imports, left-recursive grammars are rejected
+    fn read_grammar(&self, grammar: &str) -> Result<(GrammarFile, Vec<ImportedModule>)> {
+        let mut file = GrammarFile::parse(grammar).map_err(|e| self.grammar_error(&self.module, grammar, e))?;
+        let imports = self.link(&mut file, grammar, &mut vec![self.module.clone()])?;
+        let cycles = file.left_recursion();
+        if !cycles.is_empty() {
+            let cycles = cycles.iter().map(|cycle| cycle.to_string()).collect();
+            return Err(BuildError::LeftRecursion { module: self.module.clone(), cycles });
+        }
+        Ok((file, imports))
+    }
+    fn grammar_error(&self, grammar: &str, text: &str, e: GrammarError) -> BuildError {
+        let (line, column) = e.line_column(text);
+        let file = match grammar == self.module {
+            true => "grammar".to_string(),
+            false => format!("{grammar}.ygg"),
+        };
+        BuildError::CodegenError { module: self.module.clone(), message: format!("{file}:{line}:{column}: {}", e.message) }
+    }
+    /// Copy the imported rules into `file`, whose text is `grammar`, `chain` holds the modules importing it
+    fn link(&self, file: &mut GrammarFile, grammar: &str, chain: &mut Vec<String>) -> Result<Vec<ImportedModule>> {
+        let mut out = vec![];
+        for import in file.imports.clone() {
+            let module = module_name(Path::new(&import.path))
+                .ok_or_else(|| BuildError::InvalidModuleName { path: PathBuf::from(&import.path) })?;
+            let codegen = |message: String| BuildError::CodegenError { module: self.module.clone(), message };
+            if chain.contains(&module) {
+                return Err(codegen(format!("grammars import each other: {} -> {module}", chain.join(" -> "))));
+            }
+            let Some((_, text)) = self.imports.iter().find(|(name, _)| *name == module)
+            else {
+                return Err(codegen(format!("`{}` is imported but not given to `ParserBuilder::import`", import.path)));
+            };
+            let mut imported = GrammarFile::parse(text).map_err(|e| self.grammar_error(&module, text, e))?;
+            chain.push(module.clone());
+            self.link(&mut imported, text, chain)?;
+            chain.pop();
+            let current = chain.last().cloned().unwrap_or_default();
+            file.link(&import.namespace.text, &imported).map_err(|e| self.grammar_error(&current, grammar, e))?;
+            let prefix = match &imported.name {
+                Some(name) => name.text.clone(),
+                None => upper_camel(&module),
+            };
+            out.push(ImportedModule { namespace: import.namespace.text, module, prefix });
+        }
+        Ok(out)
+    }
+}
+
+/// `json5_value` becomes `Json5Value`
+fn upper_camel(name: &str) -> String {
+    let mut out = String::with_capacity(name.len());
+    for word in name.split('_') {
+        let mut chars = word.chars();
+        out.extend(chars.next().map(|c| c.to_ascii_uppercase()));
+        out.push_str(chars.as_str());
+    }
+    out
+}
+
+/// Files of `generated` that are missing or different in `committed` and files of `committed` that are no longer
+/// generated, line endings are ignored.
+fn stale_files(generated: &Path, committed: &Path) -> Result<Vec<PathBuf>> {
+    let fresh = file_names(generated)?;
+    let mut stale = vec![];
+    for name in &fresh {
+        let path = generated.join(name);
+        let bytes = read(&path).map_err(BuildError::io(&path))?;
+        let current = read(committed.join(name)).ok();
+        if current.map(normalize) != Some(normalize(bytes)) {
+            stale.push(committed.join(name));
+        }
+    }
+    if committed.is_dir() {
+        stale.extend(file_names(committed)?.difference(&fresh).map(|name| committed.join(name)));
+    }
+    stale.sort();
+    Ok(stale)
+}
+
+/// Names of the files directly inside `directory`
+fn file_names(directory: &Path) -> Result<BTreeSet<OsString>> {
+    let mut out = BTreeSet::new();
+    for entry in read_dir(directory).map_err(BuildError::io(directory))? {
+        let path = entry.map_err(BuildError::io(directory))?.path();
+        if let Some(name) = path.file_name().filter(|_| path.is_file()) {
+            out.insert(name.to_os_string());
+        }
+    }
+    Ok(out)
+}
+
+fn normalize(bytes: Vec<u8>) -> Vec<u8> {
+    bytes.into_iter().filter(|b| *b != b'\r').collect()
+}
+
+/// Make `committed` a copy of `generated`, files the generator no longer writes are deleted
+fn bless(generated: &Path, committed: &Path) -> Result<()> {
+    create_dir_all(committed).map_err(BuildError::io(committed))?;
+    let fresh = file_names(generated)?;
+    for name in file_names(committed)?.difference(&fresh) {
+        let orphan = committed.join(name);
+        remove_file(&orphan).map_err(BuildError::io(&orphan))?;
+    }
+    for name in &fresh {
+        let target = committed.join(name);
+        copy(generated.join(name), &target).map_err(BuildError::io(&target))?;
+    }
+    Ok(())
+}
@@ -0,0 +1,198 @@
+use crate::{BuildError, ParserBuilder, Result};
+use grammar_tools::{GrammarError, GrammarFile};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs::{read_dir, read_to_string},
+    path::{Path, PathBuf},
+};
+
+/// Extension of grammar files
+pub const GRAMMAR_EXTENSION: &str = "ygg";
+
+/// Generate one module per `.ygg` file found under a directory.
+///
+/// ```rust,ignore
+/// // build.rs
+/// fn main() -> build_helper::Result<()> {
+///     build_helper::GrammarDirectory::new("grammars").run()
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct GrammarDirectory {
+    directory: PathBuf,
+    export: Option<PathBuf>,
+    build_dir: Option<PathBuf>,
+}
+
+/// A grammar found by [`GrammarDirectory::discover`]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct DiscoveredGrammar {
+    /// Name of the generated module
+    pub module: String,
+    /// Path of the grammar file
+    pub path: PathBuf,
+}
+
+/// Outcome of [`GrammarDirectory::generate`], grammars are processed independently so one bad file does not hide the others
+#[derive(Debug, Default)]
+pub struct GenerateReport {
+    /// Directories holding the generated modules, keyed by module name
+    pub modules: BTreeMap<String, PathBuf>,
+    /// Problems that do not stop the build, such as a missing import
+    pub warnings: Vec<BuildError>,
+    /// Grammars that could not be generated, or the missing grammar directory
+    pub errors: Vec<BuildError>,
+}
+
+impl GrammarDirectory {
+    /// Discover grammars under `directory`, modules are generated into `OUT_DIR` by default
+    pub fn new<P: Into<PathBuf>>(directory: P) -> Self {
+        Self { directory: directory.into(), export: None, build_dir: None }
+    }
+    /// Keep every module committed in `export/<module>`, see [`ParserBuilder::checked_in`]
+    pub fn checked_in<P: Into<PathBuf>>(mut self, export: P) -> Self {
+        self.export = Some(export.into());
+        self
+    }
+    /// Generate into `build_dir` instead of `OUT_DIR`, see [`ParserBuilder::build_dir`]
+    pub fn build_dir<P: Into<PathBuf>>(mut self, build_dir: P) -> Self {
+        self.build_dir = Some(build_dir.into());
+        self
+    }
+    /// The directory searched for grammars
+    pub fn directory(&self) -> &Path {
+        &self.directory
+    }
+    /// Every `.ygg` file under the directory, recursively and sorted by path.
+    pub fn discover(&self) -> Result<Vec<DiscoveredGrammar>> {
+        if !self.directory.is_dir() {
+            return Err(BuildError::MissingGrammar { path: self.directory.clone(), imported_by: None });
+        }
+        let mut paths = vec![];
+        collect_grammars(&self.directory, &mut paths)?;
+        paths.sort();
+        let mut seen = BTreeMap::<String, PathBuf>::new();
+        let mut out = Vec::with_capacity(paths.len());
+        for path in paths {
+            let module = module_name(&path).ok_or_else(|| BuildError::InvalidModuleName { path: path.clone() })?;
+            if let Some(first) = seen.insert(module.clone(), path.clone()) {
+                return Err(BuildError::DuplicateModule { module, first, second: path });
+            }
+            out.push(DiscoveredGrammar { module, path });
+        }
+        Ok(out)
+    }
+    /// Generate every grammar, printing `rerun-if-changed` for each grammar and each file it imports.
+    pub fn generate(&self) -> GenerateReport {
+        let mut report = GenerateReport::default();
+        // watching the directory itself picks up added and removed grammars
+        println!("cargo:rerun-if-changed={}", self.directory.display());
+        let grammars = match self.discover() {
+            Ok(o) => o,
+            // a mistyped directory would otherwise build a crate without its parsers
+            Err(e) => {
+                report.errors.push(e);
+                return report;
+            }
+        };
+        for grammar in grammars {
+            let text = match read_to_string(&grammar.path) {
+                Ok(o) => o,
+                Err(e) => {
+                    report.errors.push(BuildError::io(&grammar.path)(e));
+                    continue;
+                }
+            };
+            println!("cargo:rerun-if-changed={}", grammar.path.display());
+            let mut builder = match &self.export {
+                Some(export) => ParserBuilder::new(&grammar.module).checked_in(export.join(&grammar.module)),
+                None => ParserBuilder::new(&grammar.module),
+            };
+            if let Some(build_dir) = &self.build_dir {
+                builder = builder.build_dir(build_dir);
+            }
+            for import in import_closure(&grammar.path, &text, &mut report.warnings) {
+                println!("cargo:rerun-if-changed={}", import.display());
+                if let (Some(module), Ok(text)) = (module_name(&import), read_to_string(&import)) {
+                    builder = builder.import(module, &text);
+                }
+            }
+            match builder.generate(&text) {
+                Ok(o) => {
+                    report.modules.insert(grammar.module, o);
+                }
+                Err(BuildError::CodegenError { module, message }) => report
+                    .errors
+                    .push(BuildError::CodegenError { module, message: format!("{}: {message}", grammar.path.display()) }),
+                Err(e) => report.errors.push(e),
+            }
+        }
+        report
+    }
+    /// Generate every grammar and report problems to cargo, returns the error of the invalid grammar or
+    /// [`BuildError::GrammarErrors`] if there are several.
+    pub fn run(&self) -> Result<()> {
+        let mut report = self.generate();
+        for warning in &report.warnings {
+            println!("cargo:warning={warning}");
+        }
+        for error in &report.errors {
+            println!("cargo:warning=error: {error}");
+        }
+        match report.errors.len() {
+            0 => Ok(()),
+            1 => Err(report.errors.remove(0)),
+            _ => Err(BuildError::GrammarErrors { errors: report.errors }),
+        }
+    }
+}
+
+fn collect_grammars(directory: &Path, out: &mut Vec<PathBuf>) -> Result<()> {
+    for entry in read_dir(directory).map_err(BuildError::io(directory))? {
+        let path = entry.map_err(BuildError::io(directory))?.path();
+        if path.is_dir() {
+            collect_grammars(&path, out)?;
+        }
+        else if path.extension().is_some_and(|e| e == GRAMMAR_EXTENSION) {
+            out.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// `grammars/json5.ygg` generates the `json5` module, `None` if the file name is not a valid module name.
+pub fn module_name(grammar: &Path) -> Option<String> {
+    let stem = grammar.file_stem()?.to_str()?.to_ascii_lowercase().replace('-', "_");
+    let mut chars = stem.chars();
+    let valid = matches!(chars.next(), Some(c) if c == '_' || c.is_ascii_alphabetic())
+        && chars.all(|c| c == '_' || c.is_ascii_alphanumeric());
+    valid.then_some(stem)
+}
+
+/// Paths named by the `import "<path>"` statements of a grammar, in source order.
+pub fn grammar_imports(text: &str) -> std::result::Result<Vec<String>, GrammarError> {
+    Ok(GrammarFile::parse(text)?.imports.into_iter().map(|import| import.path).collect())
+}
+
+/// Every file reachable through imports, resolved against the importing file, missing files become warnings.
+///
+/// A grammar that does not parse contributes no imports, its error is reported when it is generated.
+fn import_closure(grammar: &Path, text: &str, warnings: &mut Vec<BuildError>) -> BTreeSet<PathBuf> {
+    let mut seen = BTreeSet::new();
+    let mut pending = vec![(grammar.to_path_buf(), grammar_imports(text).unwrap_or_default())];
+    while let Some((importer, imports)) = pending.pop() {
+        let base = importer.parent().unwrap_or(Path::new(""));
+        for import in imports {
+            let path = base.join(import);
+            if path == grammar || !seen.insert(path.clone()) {
+                continue;
+            }
+            match read_to_string(&path) {
+                Ok(o) => pending.push((path, grammar_imports(&o).unwrap_or_default())),
+                Err(_) => warnings.push(BuildError::MissingGrammar { path, imported_by: Some(importer.clone()) }),
+            }
+        }
+    }
+    seen.retain(|path| path.is_file());
+    seen
+}
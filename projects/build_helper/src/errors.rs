@@ -0,0 +1,127 @@
+use std::{
+    fmt::{Display, Formatter},
+    path::PathBuf,
+};
+
+/// Errors raised while generating parsers from a build script
+#[derive(Debug)]
+pub enum BuildError {
+    /// `OUT_DIR` is not set, the helper was called outside of a build script
+    MissingOutDir,
+    /// A file could not be read or written
+    IoError {
+        /// The file or directory involved
+        path: PathBuf,
+        /// The underlying error
+        error: std::io::Error,
+    },
+    /// A grammar directory or an imported grammar does not exist
+    MissingGrammar {
+        /// The missing path
+        path: PathBuf,
+        /// The grammar whose `import` names the path, `None` for the grammar directory
+        imported_by: Option<PathBuf>,
+    },
+    /// The file name of a grammar is not a valid module name
+    InvalidModuleName {
+        /// Path of the grammar
+        path: PathBuf,
+    },
+    /// Two grammars generate the same module
+    DuplicateModule {
+        /// Name of the module
+        module: String,
+        /// The grammar generating the module first
+        first: PathBuf,
+        /// The grammar generating it again
+        second: PathBuf,
+    },
+    /// The code generator rejected the grammar
+    CodegenError {
+        /// Name of the generated module
+        module: String,
+        /// Message of the code generator
+        message: String,
+    },
+    /// Rules of the grammar call each other without consuming input
+    LeftRecursion {
+        /// Name of the module
+        module: String,
+        /// Each cycle as a chain of rule names, such as `Sum -> Sum`
+        cycles: Vec<String>,
+    },
+    /// Several grammars of a directory could not be generated
+    GrammarErrors {
+        /// The error of each grammar
+        errors: Vec<BuildError>,
+    },
+    /// Committed generated files no longer match the grammar
+    StaleFiles {
+        /// Directory of the committed module
+        directory: PathBuf,
+        /// Files that differ from a fresh generation or are no longer generated
+        files: Vec<PathBuf>,
+    },
+}
+
+/// Result type of the build helpers
+pub type Result<T> = std::result::Result<T, BuildError>;
+
+impl BuildError {
+    pub(crate) fn io(path: impl Into<PathBuf>) -> impl FnOnce(std::io::Error) -> Self {
+        let path = path.into();
+        move |error| Self::IoError { path, error }
+    }
+}
+
+impl Display for BuildError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MissingOutDir => f.write_str("`OUT_DIR` is not set, parsers must be generated from a build script"),
+            Self::IoError { path, error } => write!(f, "{}: {error}", path.display()),
+            Self::MissingGrammar { path, imported_by: None } => {
+                write!(f, "grammar directory {} does not exist", path.display())
+            }
+            Self::MissingGrammar { path, imported_by: Some(importer) } => {
+                write!(f, "{}: imported grammar {} does not exist", importer.display(), path.display())
+            }
+            Self::InvalidModuleName { path } => {
+                write!(f, "{}: file name is not a valid module name", path.display())
+            }
+            Self::DuplicateModule { module, first, second } => {
+                write!(f, "{} and {} both generate the module `{module}`", first.display(), second.display())
+            }
+            Self::CodegenError { module, message } => write!(f, "failed to generate `{module}`: {message}"),
+            Self::LeftRecursion { module, cycles } => {
+                write!(
+                    f,
+                    "grammar of `{module}` is left recursive, write it as `operators` or a repetition: {}",
+                    cycles.join(", ")
+                )
+            }
+            Self::GrammarErrors { errors } => {
+                write!(f, "{} grammars could not be generated", errors.len())?;
+                for error in errors {
+                    write!(f, "\n{error}")?;
+                }
+                Ok(())
+            }
+            Self::StaleFiles { directory, files } => {
+                write!(f, "generated files in {} are out of date:", directory.display())?;
+                for file in files {
+                    write!(f, " {}", file.display())?;
+                }
+                write!(f, ", rebuild with `{}=1` to update them", crate::BLESS_VARIABLE)
+            }
+        }
+    }
+}
+
+impl std::error::Error for BuildError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::IoError { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
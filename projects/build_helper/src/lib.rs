@@ -0,0 +1,33 @@
+#![deny(missing_debug_implementations)]
+#![warn(missing_docs, rustdoc::missing_crate_level_docs)]
+#![doc = include_str!("../readme.md")]
+#![doc(html_logo_url = "https://raw.githubusercontent.com/oovm/shape-rs/dev/projects/images/Trapezohedron.svg")]
+#![doc(html_favicon_url = "https://raw.githubusercontent.com/oovm/shape-rs/dev/projects/images/Trapezohedron.svg")]
+
+pub use crate::{
+    builder::{BLESS_VARIABLE, OutputMode, ParserBuilder},
+    discover::{DiscoveredGrammar, GRAMMAR_EXTENSION, GenerateReport, GrammarDirectory, grammar_imports, module_name},
+    errors::{BuildError, Result},
+};
+
+mod builder;
+mod discover;
+mod errors;
+mod patch;
+
+/// Former name of [`DiscoveredGrammar`]
+#[deprecated(note = "renamed to `DiscoveredGrammar`, `GrammarFile` is the parsed grammar of `grammar_tools`")]
+pub type GrammarFile = DiscoveredGrammar;
+
+/// Include a parser module generated into `OUT_DIR` by [`ParserBuilder`], the crate needs `build_helper` as a regular
+/// dependency besides the build dependency.
+///
+/// ```ignore
+/// build_helper::include_parser!("json5");
+/// ```
+#[macro_export]
+macro_rules! include_parser {
+    ($module:literal) => {
+        include!(concat!(env!("OUT_DIR"), "/", $module, ".rs"));
+    };
+}
@@ -0,0 +1,1340 @@
+use crate::{BuildError, Result};
+use grammar_tools::{
+    Expression, ExpressionKind, Fixity, GrammarFile, Indentation, Operator, OperatorLevel, OperatorTable, RuleKind,
+    external_marker, imported_name, indentation_marker, lookahead_marker, snake_case,
+};
+use std::{
+    collections::BTreeSet,
+    fmt::Write,
+    fs::{read_dir, read_to_string, write},
+    ops::Range,
+    path::Path,
+};
+
+/// Fix up a freshly generated module before it is used or compared against a checked in copy.
+///
+/// - `parse_cst` arms of helper rules such as `IgnoreText` end in `unreachable!()`, `parse_cst` now returns an
+///   `invalid_node` error naming the rule instead of aborting the process
+/// - `rules.rs` lists the rules `parse_cst` accepts as `parsable_rules()` and `is_parsable()`, and describes every rule
+///   in a static `RULE_INFO` table read from the grammar text
+/// - `external` rules call their scanner in place of the text they are lowered to
+/// - every rule call is a step of the budget in `budget.rs`, whose `parse_cst_budgeted` fails the parse as soon as
+///   it makes too many calls, builds too many nodes or passes a deadline
+/// - every running call of a rule that can reach itself, such as a `union` choosing between an array and an object,
+///   counts as one nesting level, `nesting.rs` adds `parse_cst_nested` which fails the parse where the levels go over a
+///   limit instead of overflowing the stack
+/// - the text matched in place of each `&e` and `!e`, see [`GrammarFile::lower`], becomes a `state.lookahead` check of
+///   `e` that consumes nothing
+/// - `INDENT`, `SAMEDENT` and `DEDENT` become matchers over the span stack of the state, which starts with the
+///   unindented top level and is restored when a rule opening or closing a block fails
+/// - `operators` rules get an `operators.rs` with an enum of unary and binary operations folded by binding power, in
+///   place of the flat struct generated for the lowered rule
+/// - named captures of a single terminal, such as `c:ANY`, become fields of the type `Captured` from `captures.rs`,
+///   an `Option` when the capture may not match and a `Vec` when it may match more than once
+/// - a rule redirected with `class Name -> Target` is built as the node of its target, which it replaces when both
+///   have the same fields, or as a new variant `Name` when the target is a `union`
+/// - every node derives `derives` besides the derives of the generator, the node of a rule also derives the paths of
+///   its `@derive(...)` annotations and is marked with the attributes of its `@attr(...)` annotations
+/// - the `serde` derives behind the `serde` feature of the crate are removed when `serde` is off
+/// - rules copied in from an imported grammar keep their nodes in its module, the fields holding them are parsed again
+///   from their text by the imported parser
+pub(crate) fn patch_module(
+    module: &str,
+    file: &GrammarFile,
+    imports: &[ImportedModule],
+    derives: &[String],
+    serde: bool,
+    directory: &Path,
+) -> Result<()> {
+    let parse_cst = directory.join("parse_cst.rs");
+    let text = read_to_string(&parse_cst).map_err(BuildError::io(&parse_cst))?;
+    let layout = |message: &str| BuildError::CodegenError { module: module.to_string(), message: message.to_string() };
+    let rule = text
+        .split_once("rule: ")
+        .and_then(|(_, rest)| rest.split_once(')'))
+        .map(|(name, _)| name.trim())
+        .ok_or_else(|| layout("`parse_cst` has no `rule` parameter"))?;
+    let mut patched = String::with_capacity(text.len());
+    let mut entries = vec![];
+    for line in text.split_inclusive('\n') {
+        let arm =
+            line.trim().strip_prefix(rule).and_then(|rest| rest.strip_prefix("::")).and_then(|rest| rest.split_once(" => "));
+        match arm {
+            Some((_, "unreachable!(),")) => patched.push_str(&line.replacen("unreachable!()", "Err(state)", 1)),
+            Some((variant, _)) => {
+                entries.push(variant);
+                patched.push_str(line);
+            }
+            None => patched.push_str(line),
+        }
+        if line.contains("fn parse_cst(") {
+            // starting from a helper rule is a mistake of the caller, not a syntax error at the start of the input
+            patched.push_str("    if !rule.is_parsable() {\n");
+            patched.push_str(
+                "        let span = TextSpan::new(input, 0, 0).expect(\"the start of the input is a valid span\");\n",
+            );
+            patched.push_str("        return Err(YggdrasilError::invalid_node(rule, span));\n    }\n");
+        }
+    }
+    if entries.is_empty() {
+        return Err(layout("`parse_cst` has no rule arms"));
+    }
+    let variants = rule_variants(&text, rule);
+    let tags = rule_tags(&text, rule);
+    let patched = patch_externals(module, file, patched)?;
+    let patched = patch_lookaheads(module, file, patched)?;
+    let patched = patch_indentation(module, file, patched)?;
+    let (patched, nested) = patch_nesting(module, file, patched)?;
+    let patched = patch_budget(patched);
+    let patched = patch_offset(module, rule, patched)?;
+    write(&parse_cst, patched).map_err(BuildError::io(&parse_cst))?;
+    let rules = directory.join("rules.rs");
+    write(&rules, rules_file(rule, &entries, &variants, &tags, file)).map_err(BuildError::io(&rules))?;
+    let mod_rs = directory.join("mod.rs");
+    let mut text = read_to_string(&mod_rs).map_err(BuildError::io(&mod_rs))?;
+    if !text.contains("mod rules;") {
+        text = text.replacen("mod parse_ast;\n", "mod parse_ast;\nmod rules;\n", 1);
+    }
+    if !text.contains("pub use self::rules::") {
+        text = text.replacen("mod rules;\n", "mod rules;\n\npub use self::rules::{RuleInfo, RuleKind};\n", 1);
+    }
+    let budget = directory.join("budget.rs");
+    write(&budget, budget_file(rule)).map_err(BuildError::io(&budget))?;
+    text = text.replacen("mod parse_ast;\n", "mod parse_ast;\nmod budget;\n", 1);
+    text = text.replacen(
+        "pub use self::rules::",
+        "pub use self::budget::{BudgetExceeded, ParseBudget};\npub use self::rules::",
+        1,
+    );
+    if !nested.is_empty() {
+        let nesting = directory.join("nesting.rs");
+        write(&nesting, nesting_file(rule, &nested)).map_err(BuildError::io(&nesting))?;
+        text = text.replacen("mod rules;\n", "mod nesting;\nmod rules;\n", 1);
+        text = text.replacen("pub use self::rules::", "pub use self::nesting::NestingExceeded;\npub use self::rules::", 1);
+    }
+    write(&mod_rs, text).map_err(BuildError::io(&mod_rs))?;
+    patch_operators(module, rule, file, directory)?;
+    patch_captures(module, rule, file, directory)?;
+    patch_redirects(module, rule, file, directory)?;
+    patch_imports(module, rule, file, imports, directory)?;
+    patch_derives(module, file, derives, directory)?;
+    if !serde {
+        remove_serde(directory)?;
+    }
+    Ok(())
+}
+
+/// A namespace of the grammar and the module generated from the imported file, a sibling of the importing module
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub(crate) struct ImportedModule {
+    /// `json5` in `json5::Value`
+    pub namespace: String,
+    /// Name of the module
+    pub module: String,
+    /// Start of the type names of the module, `Json5` in `Json5Parser`
+    pub prefix: String,
+}
+
+/// Names the generated module uses for its own files
+const MODULE_FILES: &[&str] = &["parse_cst", "parse_ast", "rules", "budget", "nesting", "operators", "captures", "imports"];
+
+/// Replace the nodes of imported rules by the ones of the imported module, wrapped in `Imported` while they are built
+fn patch_imports(module: &str, rule: &str, file: &GrammarFile, imports: &[ImportedModule], directory: &Path) -> Result<()> {
+    let imported: Vec<_> = file.rules.iter().filter(|r| r.is_imported()).collect();
+    if imported.is_empty() {
+        return Ok(());
+    }
+    let layout = |message: String| BuildError::CodegenError { module: module.to_string(), message };
+    let mod_rs = directory.join("mod.rs");
+    let parse_ast = directory.join("parse_ast.rs");
+    let mut declarations = read_to_string(&mod_rs).map_err(BuildError::io(&mod_rs))?;
+    let mut implementations = read_to_string(&parse_ast).map_err(BuildError::io(&parse_ast))?;
+    let mut out = String::from("use super::*;\n\n");
+    out.push_str("/// A node of an imported grammar, built by parsing the text of the child again with the imported parser\n");
+    out.push_str("/// from where the child starts, so the spans are the ones of the whole input\n");
+    out.push_str("pub(super) struct Imported<N>(pub(super) N);\n");
+    for declared in &imported {
+        let node = format!("{}Node", imported_name(&declared.name.text));
+        declarations = remove_item(&declarations, &format!("pub struct {node} {{"))
+            .or_else(|| remove_item(&declarations, &format!("pub enum {node} {{")))
+            .ok_or_else(|| layout(format!("`mod.rs` does not declare `{node}`")))?;
+        implementations = remove_item(&implementations, &format!("impl YggdrasilNode for {node} {{"))
+            .ok_or_else(|| layout(format!("`parse_ast.rs` does not implement `{node}`")))?;
+    }
+    let mut uses = String::new();
+    for declared in &imported {
+        let node = format!("{}Node", imported_name(&declared.name.text));
+        if !has_name(&declarations, &node) && !has_name(&implementations, &node) {
+            continue;
+        }
+        // only rules referenced by this grammar are left, `json5::Value` and not `json5::Object` below it
+        let Some((namespace, local)) = declared.name.text.split_once("::")
+        else {
+            continue;
+        };
+        let Some(import) = imports.iter().find(|import| import.namespace == namespace)
+        else {
+            return Err(layout(format!("the module imported as `{namespace}` is unknown")));
+        };
+        if MODULE_FILES.contains(&namespace) {
+            return Err(layout(format!(
+                "the namespace `{namespace}` is the name of a generated file, import it `as` another name"
+            )));
+        }
+        let path = match import.module == namespace {
+            true => format!("use super::{namespace};\n"),
+            false => format!("use super::{} as {namespace};\n", import.module),
+        };
+        if !uses.contains(&path) {
+            uses.push_str(&path);
+        }
+        // the imported module builds a redirected rule as the node of its target
+        let local = declared.redirect.as_ref().map_or(local, |r| r.text.rsplit("::").next().unwrap_or(&r.text));
+        let target = format!("{namespace}::{local}Node");
+        declarations = replace_name(&declarations, &node, &target);
+        for method in ["take_tagged_one", "take_tagged_items"] {
+            implementations = import_calls(&implementations, method, &node, &target);
+        }
+        if has_name(&implementations, &node) {
+            return Err(layout(format!("`parse_ast.rs` builds `{node}` in an unknown way")));
+        }
+        let prefix = &import.prefix;
+        writeln!(out, "\nimpl YggdrasilNode for Imported<{target}> {{").unwrap();
+        writeln!(out, "    type Rule = {rule};\n").unwrap();
+        out.push_str("    fn get_range(&self) -> Option<Range<usize>> {\n        None\n    }\n");
+        out.push_str("    fn from_pair(pair: TokenPair<Self::Rule>) -> Result<Self, YggdrasilError<Self::Rule>> {\n");
+        out.push_str("        let span = pair.get_span();\n");
+        out.push_str("        // the pair does not lend the whole input, spaces stand for the text in front of the child\n");
+        out.push_str("        let input = format!(\"{:start$}{}\", \"\", span.as_str(), start = span.start());\n");
+        writeln!(out, "        let rule = {namespace}::{prefix}Rule::{local};").unwrap();
+        writeln!(out, "        let cst = {namespace}::{prefix}Parser::parse_cst_at(&input, span.start(), rule);").unwrap();
+        writeln!(out, "        match cst.and_then({target}::from_cst) {{").unwrap();
+        out.push_str("            Ok(node) => Ok(Self(node)),\n");
+        writeln!(
+            out,
+            "            Err(_) => Err(YggdrasilError::invalid_node({rule}::{}, span)),",
+            imported_name(&declared.name.text)
+        )
+        .unwrap();
+        out.push_str("        }\n    }\n}\n");
+    }
+    if !declarations.contains("mod imports;") {
+        declarations = declarations.replacen("mod parse_ast;\n", "mod imports;\nmod parse_ast;\n", 1);
+        declarations = declarations.replacen("\nuse std::", &format!("\n{uses}use self::imports::Imported;\nuse std::"), 1);
+    }
+    write(&mod_rs, declarations).map_err(BuildError::io(&mod_rs))?;
+    write(&parse_ast, implementations).map_err(BuildError::io(&parse_ast))?;
+    let imports = directory.join("imports.rs");
+    write(&imports, out).map_err(BuildError::io(&imports))
+}
+
+/// Whether `name` appears in `text` as a whole identifier
+fn has_name(text: &str, name: &str) -> bool {
+    replace_name(text, name, "") != text
+}
+
+/// `text` with every whole identifier `name` replaced by `target`
+fn replace_name(text: &str, name: &str, target: &str) -> String {
+    let is_identifier = |c: char| c == '_' || c.is_alphanumeric();
+    let mut out = String::with_capacity(text.len());
+    let mut copied = 0;
+    for (index, _) in text.match_indices(name) {
+        let before = text[..index].chars().next_back().is_some_and(|c| is_identifier(c) || c == ':');
+        let after = text[index + name.len()..].chars().next().is_some_and(is_identifier);
+        if before || after {
+            continue;
+        }
+        out.push_str(&text[copied..index]);
+        out.push_str(target);
+        copied = index + name.len();
+    }
+    out.push_str(&text[copied..]);
+    out
+}
+
+/// `pair.take_tagged_one::<Node>(tag)` becomes `pair.take_tagged_one::<Imported<target>>(tag).map(|node| node.0)`, and
+/// the same for the items of a repetition
+fn import_calls(text: &str, method: &str, node: &str, target: &str) -> String {
+    let call = format!("{method}::<{node}>(");
+    let unwrap = match method {
+        "take_tagged_items" => ".map(|items| items.into_iter().map(|node| node.0).collect())",
+        _ => ".map(|node| node.0)",
+    };
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find(&call) {
+        let Some(end) = rest[start..].find("\"))").map(|end| start + end + 3)
+        else {
+            break;
+        };
+        out.push_str(&rest[..start]);
+        write!(out, "{method}::<Imported<{target}>>({}{unwrap}", &rest[start + call.len()..end]).unwrap();
+        rest = &rest[end..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Replace the node of every `operators` rule by the typed tree of `operators.rs`
+fn patch_operators(module: &str, rule: &str, file: &GrammarFile, directory: &Path) -> Result<()> {
+    let tables: Vec<_> = file
+        .rules
+        .iter()
+        .filter(|r| !r.is_imported())
+        .filter_map(|r| Some((r.name.text.as_str(), r.operators.as_ref()?)))
+        .collect();
+    if tables.is_empty() {
+        return Ok(());
+    }
+    let layout = |message: String| BuildError::CodegenError { module: module.to_string(), message };
+    let mod_rs = directory.join("mod.rs");
+    let parse_ast = directory.join("parse_ast.rs");
+    let mut declarations = read_to_string(&mod_rs).map_err(BuildError::io(&mod_rs))?;
+    let mut implementations = read_to_string(&parse_ast).map_err(BuildError::io(&parse_ast))?;
+    let mut out = String::from("use super::*;\nuse std::{iter::Peekable, vec::IntoIter};\n");
+    for (name, table) in tables {
+        let node = format!("{name}Node");
+        declarations = remove_item(&declarations, &format!("pub struct {node} {{"))
+            .ok_or_else(|| layout(format!("`mod.rs` does not declare `{node}`")))?;
+        implementations = remove_item(&implementations, &format!("impl YggdrasilNode for {node} {{"))
+            .ok_or_else(|| layout(format!("`parse_ast.rs` does not implement `{node}`")))?;
+        operators_file(&mut out, rule, name, table);
+    }
+    if !declarations.contains("mod operators;") {
+        declarations = declarations.replacen("mod rules;\n", "mod operators;\nmod rules;\n", 1);
+        declarations = declarations.replacen("pub use self::rules::", "pub use self::operators::*;\npub use self::rules::", 1);
+    }
+    write(&mod_rs, declarations).map_err(BuildError::io(&mod_rs))?;
+    write(&parse_ast, implementations).map_err(BuildError::io(&parse_ast))?;
+    let operators = directory.join("operators.rs");
+    write(&operators, out).map_err(BuildError::io(&operators))
+}
+
+/// How often a capture can match in one node
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Count {
+    One,
+    Optional,
+    Many,
+}
+
+/// Add a field to the nodes of `class` rules for each of their captures of a terminal, which the generated nodes drop
+fn patch_captures(module: &str, rule: &str, file: &GrammarFile, directory: &Path) -> Result<()> {
+    let layout = |message: String| BuildError::CodegenError { module: module.to_string(), message };
+    let mod_rs = directory.join("mod.rs");
+    let parse_ast = directory.join("parse_ast.rs");
+    let mut declarations = read_to_string(&mod_rs).map_err(BuildError::io(&mod_rs))?;
+    let mut implementations = read_to_string(&parse_ast).map_err(BuildError::io(&parse_ast))?;
+    let mut patched = false;
+    for declared in file.rules.iter().filter(|r| r.kind == RuleKind::Class && !r.is_imported()) {
+        let mut fields = vec![];
+        terminal_captures(&declared.body, Count::One, &mut fields);
+        if fields.is_empty() {
+            continue;
+        }
+        let node = format!("{}Node", declared.name.text);
+        let header = format!("pub struct {node} {{");
+        let declaration =
+            item_lines(&declarations, &header).ok_or_else(|| layout(format!("`mod.rs` does not declare `{node}`")))?;
+        // the generator already has a field for a capture of a reference with the same name
+        fields.retain(|(name, _)| !declarations[declaration.clone()].contains(&format!("    pub {name}: ")));
+        if fields.is_empty() {
+            continue;
+        }
+        let mut types = String::new();
+        let mut values = String::new();
+        for (name, count) in &fields {
+            let (kind, call) = match count {
+                Count::One => ("Captured", format!("take_tagged_one::<Captured>(Cow::Borrowed({name:?}))?")),
+                Count::Optional => ("Option<Captured>", format!("take_tagged_one::<Captured>(Cow::Borrowed({name:?})).ok()")),
+                Count::Many => ("Vec<Captured>", format!("take_tagged_items::<Captured>(Cow::Borrowed({name:?}))?")),
+            };
+            writeln!(types, "    pub {name}: {kind},").unwrap();
+            writeln!(values, "            {name}: pair.{call},").unwrap();
+        }
+        let span = "    pub span: Range<u32>,\n";
+        let at =
+            declarations[declaration.clone()].find(span).ok_or_else(|| layout(format!("`{node}` in `mod.rs` has no span")))?;
+        declarations.insert_str(declaration.start + at, &types);
+        let header = format!("impl YggdrasilNode for {node} {{");
+        let implementation = item_lines(&implementations, &header)
+            .ok_or_else(|| layout(format!("`parse_ast.rs` does not implement `{node}`")))?;
+        let span = "span: Range { start: _span.start() as u32, end: _span.end() as u32 }";
+        let single = format!("        Ok(Self {{ {span} }})\n");
+        let text = &implementations[implementation.clone()];
+        let rebuilt = match text.find(&single) {
+            Some(at) => format!(
+                "{}        Ok(Self {{\n{values}            {span},\n        }})\n{}",
+                &text[..at],
+                &text[at + single.len()..]
+            ),
+            None => {
+                let field = format!("            {span},\n");
+                let at =
+                    text.find(&field).ok_or_else(|| layout(format!("`parse_ast.rs` builds `{node}` in an unknown way")))?;
+                format!("{}{values}{}", &text[..at], &text[at..])
+            }
+        };
+        implementations.replace_range(implementation, &rebuilt);
+        patched = true;
+    }
+    if !patched {
+        return Ok(());
+    }
+    if !declarations.contains("mod captures;") {
+        declarations = declarations.replacen("mod parse_cst;\n", "mod captures;\nmod parse_cst;\n", 1);
+        declarations = declarations.replacen("pub use self::", "pub use self::captures::Captured;\npub use self::", 1);
+    }
+    write(&mod_rs, declarations).map_err(BuildError::io(&mod_rs))?;
+    write(&parse_ast, implementations).map_err(BuildError::io(&parse_ast))?;
+    let captures = directory.join("captures.rs");
+    write(&captures, captures_file(rule)).map_err(BuildError::io(&captures))
+}
+
+/// Captures of a single terminal in `expression`, a name written twice in a sequence matches many times and in two
+/// branches of a choice at most once, captures of a reference are fields of the generated node already
+fn terminal_captures<'g>(expression: &'g Expression, count: Count, out: &mut Vec<(&'g str, Count)>) {
+    let is_terminal = |e: &Expression| {
+        matches!(
+            e.kind,
+            ExpressionKind::Text(_) | ExpressionKind::Regex(_) | ExpressionKind::CharacterClass(_) | ExpressionKind::Any
+        )
+    };
+    match &expression.kind {
+        ExpressionKind::Capture { name, body } => {
+            let single = match &body.kind {
+                ExpressionKind::Choice(items) => items.iter().all(is_terminal),
+                _ => is_terminal(body),
+            };
+            if single {
+                out.push((&name.text, count))
+            }
+        }
+        ExpressionKind::Sequence(items) => {
+            for item in items {
+                let mut found = vec![];
+                terminal_captures(item, count, &mut found);
+                for (name, count) in found {
+                    match out.iter_mut().find(|(field, _)| *field == name) {
+                        Some(field) => field.1 = Count::Many,
+                        None => out.push((name, count)),
+                    }
+                }
+            }
+        }
+        ExpressionKind::Choice(items) => {
+            for item in items {
+                let mut found = vec![];
+                terminal_captures(item, count.max(Count::Optional), &mut found);
+                for (name, count) in found {
+                    match out.iter_mut().find(|(field, _)| *field == name) {
+                        Some(field) => field.1 = field.1.max(count),
+                        None => out.push((name, count)),
+                    }
+                }
+            }
+        }
+        ExpressionKind::Tagged { body, .. } => terminal_captures(body, count, out),
+        ExpressionKind::Repeat { body, max: Some(1), .. } => terminal_captures(body, count.max(Count::Optional), out),
+        ExpressionKind::Repeat { body, .. } => terminal_captures(body, Count::Many, out),
+        _ => {}
+    }
+}
+
+/// Byte range of the item opening with the line `header`, up to the closing brace in the first column
+fn item_lines(text: &str, header: &str) -> Option<Range<usize>> {
+    let start = text.find(&format!("\n{header}\n"))? + 1;
+    let end = start + text[start..].find("\n}\n")? + 3;
+    Some(start..end)
+}
+
+fn captures_file(rule: &str) -> String {
+    let mut out = String::from("use super::*;\n\n");
+    out.push_str("/// Text matched by a named terminal such as `c:ANY`, as a range of the input\n");
+    out.push_str("#[derive(Clone, Debug, PartialEq, Eq, Hash)]\n");
+    out.push_str("#[cfg_attr(feature = \"serde\", derive(serde::Serialize, serde::Deserialize))]\n");
+    out.push_str("pub struct Captured {\n    pub span: Range<u32>,\n}\n\n");
+    out.push_str("impl Captured {\n");
+    out.push_str("    /// The captured text in the parsed `input`\n");
+    out.push_str("    pub fn text<'i>(&self, input: &'i str) -> &'i str {\n");
+    out.push_str("        &input[self.span.start as usize..self.span.end as usize]\n    }\n}\n\n");
+    out.push_str("#[automatically_derived]\n");
+    out.push_str("impl YggdrasilNode for Captured {\n");
+    writeln!(out, "    type Rule = {rule};\n").unwrap();
+    out.push_str("    fn get_range(&self) -> Option<Range<usize>> {\n");
+    out.push_str("        Some(Range { start: self.span.start as usize, end: self.span.end as usize })\n    }\n");
+    out.push_str("    fn from_pair(pair: TokenPair<Self::Rule>) -> Result<Self, YggdrasilError<Self::Rule>> {\n");
+    out.push_str("        let _span = pair.get_span();\n");
+    out.push_str("        Ok(Self { span: Range { start: _span.start() as u32, end: _span.end() as u32 } })\n    }\n}\n");
+    out
+}
+
+/// Build the node of every redirected rule as the one of its target, the fields holding it then hold the target
+fn patch_redirects(module: &str, rule: &str, file: &GrammarFile, directory: &Path) -> Result<()> {
+    let redirects: Vec<_> =
+        file.rules.iter().filter(|r| !r.is_imported()).filter_map(|r| Some((r, r.redirect.as_ref()?))).collect();
+    if redirects.is_empty() {
+        return Ok(());
+    }
+    let layout = |message: String| BuildError::CodegenError { module: module.to_string(), message };
+    let mod_rs = directory.join("mod.rs");
+    let parse_ast = directory.join("parse_ast.rs");
+    let mut declarations = read_to_string(&mod_rs).map_err(BuildError::io(&mod_rs))?;
+    let mut implementations = read_to_string(&parse_ast).map_err(BuildError::io(&parse_ast))?;
+    for (declared, redirect) in redirects {
+        let name = &declared.name.text;
+        let target = file
+            .rule(&redirect.text)
+            .ok_or_else(|| layout(format!("`{name}` redirects to `{}`, which is not defined", redirect.text)))?;
+        if target.redirect.is_some() {
+            return Err(layout(format!("`{name}` redirects to `{}`, which redirects again", redirect.text)));
+        }
+        if [declared, target].iter().any(|r| !matches!(r.kind, RuleKind::Class | RuleKind::Union)) {
+            return Err(layout(format!("`{name}` redirects to `{}`, only `class` and `union` rules redirect", redirect.text)));
+        }
+        let node = format!("{name}Node");
+        let into = format!("{}Node", redirect.text);
+        let declaration = |text: &str, node: &str| {
+            item_lines(text, &format!("pub struct {node} {{")).or_else(|| item_lines(text, &format!("pub enum {node} {{")))
+        };
+        let implementation = |text: &str, node: &str| item_lines(text, &format!("impl YggdrasilNode for {node} {{"));
+        let missing = |file: &str, node: &str| layout(format!("`{file}` does not declare `{node}`"));
+        let own = declaration(&declarations, &node).ok_or_else(|| missing("mod.rs", &node))?;
+        let other = declaration(&declarations, &into).ok_or_else(|| missing("mod.rs", &into))?;
+        let built = implementation(&implementations, &node).ok_or_else(|| missing("parse_ast.rs", &node))?;
+        let builder = implementation(&implementations, &into).ok_or_else(|| missing("parse_ast.rs", &into))?;
+        if target.kind == RuleKind::Class {
+            // the target builds the redirected node from the same tags
+            if replace_name(&declarations[own.clone()], &node, &into) != declarations[other] {
+                return Err(layout(format!("`{name}` redirects to `{}`, whose node has other fields", redirect.text)));
+            }
+            let header = |text: &str, lines: Range<usize>| text[lines].lines().next().unwrap_or_default().to_string();
+            let own = header(&declarations, own);
+            let built = header(&implementations, built);
+            declarations = remove_item(&declarations, &own).ok_or_else(|| missing("mod.rs", &node))?;
+            implementations = remove_item(&implementations, &built).ok_or_else(|| missing("parse_ast.rs", &node))?;
+            declarations = replace_name(&declarations, &node, &into);
+            implementations = replace_name(&implementations, &node, &into);
+            continue;
+        }
+        declarations = replace_outside(&declarations, &[own, other], &node, &into);
+        implementations = replace_outside(&implementations, &[built, builder], &node, &into);
+        let other = declaration(&declarations, &into).ok_or_else(|| missing("mod.rs", &into))?;
+        let variant = format!("    {name}({node}),\n");
+        if !declarations[other.clone()].contains(&variant) {
+            let at = other.start + declarations[other].find('\n').unwrap_or_default() + 1;
+            declarations.insert_str(at, &variant);
+        }
+        let builder = implementation(&implementations, &into).ok_or_else(|| missing("parse_ast.rs", &into))?;
+        let span = "        let _span = pair.get_span();\n";
+        let at = implementations[builder.clone()]
+            .find(span)
+            .ok_or_else(|| layout(format!("`parse_ast.rs` builds `{into}` in an unknown way")))?;
+        let mut check = format!("        if matches!(pair.get_rule(), {rule}::{name}) {{\n");
+        writeln!(check, "            return {node}::from_pair(pair).map(Self::{name});\n        }}").unwrap();
+        implementations.insert_str(builder.start + at + span.len(), &check);
+    }
+    write(&mod_rs, declarations).map_err(BuildError::io(&mod_rs))?;
+    write(&parse_ast, implementations).map_err(BuildError::io(&parse_ast))
+}
+
+/// Add `derives` to the derives of every node, and the `@derive` and `@attr` annotations of a rule to its node, in every
+/// Drop the `#[cfg_attr(feature = "serde", ...)]` lines of every file of the module
+fn remove_serde(directory: &Path) -> Result<()> {
+    for entry in read_dir(directory).map_err(BuildError::io(directory))? {
+        let path = entry.map_err(BuildError::io(directory))?.path();
+        if path.extension().is_none_or(|e| e != "rs") {
+            continue;
+        }
+        let text = read_to_string(&path).map_err(BuildError::io(&path))?;
+        let kept: String =
+            text.split_inclusive('\n').filter(|line| !line.trim_start().starts_with("#[cfg_attr(feature = \"serde\"")).collect();
+        if kept.len() != text.len() {
+            write(&path, kept).map_err(BuildError::io(&path))?;
+        }
+    }
+    Ok(())
+}
+
+/// file of the module, fails when a node is not found
+fn patch_derives(module: &str, file: &GrammarFile, derives: &[String], directory: &Path) -> Result<()> {
+    let annotation = |message: String| BuildError::CodegenError { module: module.to_string(), message };
+    let mut annotated = vec![];
+    // imported rules have their nodes in the imported module
+    for declared in file.rules.iter().filter(|r| !r.is_imported()) {
+        let (paths, attributes) = (declared.annotation_arguments("derive"), declared.annotation_arguments("attr"));
+        if let Some(path) = paths.iter().find(|path| !is_path(path)) {
+            return Err(annotation(format!(
+                "`@derive` of `{}` takes paths such as `PartialEq`, found `{path}`",
+                declared.name.text
+            )));
+        }
+        if paths.is_empty() && attributes.is_empty() {
+            continue;
+        }
+        annotated.push((format!("{}Node", declared.name.text), paths, attributes));
+    }
+    if derives.is_empty() && annotated.is_empty() {
+        return Ok(());
+    }
+    if let Some(path) = derives.iter().find(|path| !is_path(path)) {
+        return Err(annotation(format!("derives are paths such as `PartialEq`, found `{path}`")));
+    }
+    let mut files = vec![];
+    for entry in read_dir(directory).map_err(BuildError::io(directory))? {
+        let path = entry.map_err(BuildError::io(directory))?.path();
+        if path.extension().is_some_and(|e| e == "rs") {
+            files.push(path);
+        }
+    }
+    files.sort();
+    let (mut nodes, mut patched) = (BTreeSet::new(), BTreeSet::new());
+    for path in files {
+        let text = read_to_string(&path).map_err(BuildError::io(&path))?;
+        let lines: Vec<&str> = text.split_inclusive('\n').collect();
+        let mut out = String::with_capacity(text.len());
+        for (index, line) in lines.iter().enumerate() {
+            nodes.extend(node_name(line).map(str::to_string));
+            let derived = line.trim_end().strip_prefix("#[derive(").and_then(|rest| rest.strip_suffix(")]"));
+            // the item follows its other attributes
+            let node = lines[index + 1..].iter().find(|line| !line.starts_with("#[")).and_then(|line| node_name(line));
+            let (Some(derived), Some(node)) = (derived, node)
+            else {
+                out.push_str(line);
+                continue;
+            };
+            patched.insert(node.to_string());
+            let own = annotated.iter().find(|(name, ..)| name == node);
+            let mut list: Vec<&str> = derived.split(", ").collect();
+            for path in derives.iter().map(String::as_str).chain(own.iter().flat_map(|(_, paths, _)| paths.iter().copied())) {
+                if !list.contains(&path) {
+                    list.push(path)
+                }
+            }
+            writeln!(out, "#[derive({})]", list.join(", ")).unwrap();
+            for attribute in own.iter().flat_map(|(.., attributes)| attributes) {
+                writeln!(out, "#[{attribute}]").unwrap();
+            }
+        }
+        write(&path, out).map_err(BuildError::io(&path))?;
+    }
+    // a node the derives cannot reach would silently miss them
+    if let Some((name, ..)) = annotated.iter().find(|(name, ..)| !patched.contains(name)) {
+        return Err(annotation(format!(
+            "the annotations of `{}` name no generated node",
+            name.strip_suffix("Node").unwrap_or(name)
+        )));
+    }
+    if let Some(node) = nodes.difference(&patched).next().filter(|_| !derives.is_empty()) {
+        return Err(annotation(format!("`{node}` has no `#[derive]` to add the project derives to")));
+    }
+    Ok(())
+}
+
+/// `XNode` in the line `pub struct XNode {` or `pub enum XNode {`, and `Captured`
+fn node_name(line: &str) -> Option<&str> {
+    let rest = line.strip_prefix("pub struct ").or_else(|| line.strip_prefix("pub enum "))?;
+    let name = rest.split([' ', '{', '(', '<']).next()?;
+    (name.ends_with("Node") || name == "Captured").then_some(name)
+}
+
+/// A path such as `PartialEq` or `serde::Serialize`
+fn is_path(text: &str) -> bool {
+    text.split("::").all(|part| {
+        let mut chars = part.chars();
+        matches!(chars.next(), Some(c) if c == '_' || c.is_alphabetic()) && chars.all(|c| c == '_' || c.is_alphanumeric())
+    })
+}
+
+/// `text` with every whole identifier `name` replaced by `target`, except in the byte ranges `kept`
+fn replace_outside(text: &str, kept: &[Range<usize>], name: &str, target: &str) -> String {
+    let mut kept = kept.to_vec();
+    kept.sort_by_key(|range| range.start);
+    let mut out = String::with_capacity(text.len());
+    let mut copied = 0;
+    for range in kept {
+        out.push_str(&replace_name(&text[copied..range.start], name, target));
+        out.push_str(&text[range.clone()]);
+        copied = range.end;
+    }
+    out.push_str(&replace_name(&text[copied..], name, target));
+    out
+}
+
+/// Remove the item opening with the line `header`, with its attributes and up to the closing brace in the first column
+fn remove_item(text: &str, header: &str) -> Option<String> {
+    let lines: Vec<&str> = text.split_inclusive('\n').collect();
+    let open = lines.iter().position(|line| line.trim_end() == header)?;
+    let close = open + lines[open..].iter().position(|line| line.trim_end() == "}")?;
+    let mut start = open;
+    while start > 0 && lines[start - 1].starts_with("#[") {
+        start -= 1;
+    }
+    let mut end = close + 1;
+    if start > 0 && lines[start - 1].trim().is_empty() && lines.get(end).is_some_and(|line| line.trim().is_empty()) {
+        end += 1;
+    }
+    Some(lines[..start].concat() + &lines[end..].concat())
+}
+
+fn operators_file(out: &mut String, rule: &str, name: &str, table: &OperatorTable) {
+    let node = format!("{name}Node");
+    let operator = format!("{name}Operator");
+    let token = format!("{name}Token");
+    let operand = &table.operand.text;
+    let operators: Vec<_> = table.levels.iter().flat_map(|level| level.operators.iter().map(move |o| (level, o))).collect();
+    writeln!(out, "\n/// Operators of [`{node}`], tighter binding operators come later").unwrap();
+    out.push_str("#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]\n");
+    out.push_str("#[cfg_attr(feature = \"serde\", derive(serde::Serialize, serde::Deserialize))]\n");
+    writeln!(out, "pub enum {operator} {{").unwrap();
+    for (_, o) in &operators {
+        writeln!(out, "    /// `{}`\n    {},", o.text, o.tag.text).unwrap();
+    }
+    out.push_str("}\n\n");
+    writeln!(out, "impl {operator} {{").unwrap();
+    out.push_str("    /// The operator tagged `tag` in the CST\n");
+    out.push_str("    pub fn from_tag(tag: &str) -> Option<Self> {\n        match tag {\n");
+    for (_, o) in &operators {
+        writeln!(out, "            {:?} => Some(Self::{}),", snake_case(&o.tag.text), o.tag.text).unwrap();
+    }
+    out.push_str("            _ => None,\n        }\n    }\n");
+    out.push_str("    /// Left and right binding power, the higher binds tighter\n");
+    out.push_str("    pub fn binding_power(&self) -> (u32, u32) {\n        match self {\n");
+    for (level, o) in &operators {
+        writeln!(out, "            Self::{} => {:?},", o.tag.text, level.binding_power()).unwrap();
+    }
+    out.push_str("        }\n    }\n");
+    out.push_str("    /// Written before its operand\n");
+    fixity_method(out, "is_prefix", &operators, Fixity::Prefix);
+    out.push_str("    /// Written after its operand\n");
+    fixity_method(out, "is_postfix", &operators, Fixity::Postfix);
+    out.push_str("}\n\n");
+    out.push_str("#[derive(Clone, Debug, Hash)]\n");
+    out.push_str("#[cfg_attr(feature = \"serde\", derive(serde::Serialize, serde::Deserialize))]\n");
+    writeln!(out, "pub enum {node} {{").unwrap();
+    // boxed, the operand usually holds the expression again through parentheses
+    writeln!(out, "    {operand}(Box<{operand}Node>),").unwrap();
+    writeln!(out, "    Unary {{ operator: {operator}, operand: Box<{node}>, span: Range<u32> }},").unwrap();
+    writeln!(out, "    Binary {{ operator: {operator}, lhs: Box<{node}>, rhs: Box<{node}>, span: Range<u32> }},").unwrap();
+    out.push_str("}\n\n");
+    writeln!(out, "enum {token} {{\n    Operand({node}, Range<u32>),\n    Operator({operator}, Range<u32>),\n}}\n").unwrap();
+    out.push_str("#[automatically_derived]\n");
+    writeln!(out, "impl YggdrasilNode for {node} {{").unwrap();
+    writeln!(out, "    type Rule = {rule};\n").unwrap();
+    out.push_str("    fn get_range(&self) -> Option<Range<usize>> {\n        match self {\n");
+    writeln!(out, "            Self::{operand}(node) => node.get_range(),").unwrap();
+    out.push_str("            Self::Unary { span, .. } | Self::Binary { span, .. } => {\n");
+    out.push_str("                Some(Range { start: span.start as usize, end: span.end as usize })\n");
+    out.push_str("            }\n        }\n    }\n");
+    out.push_str("    fn from_pair(pair: TokenPair<Self::Rule>) -> Result<Self, YggdrasilError<Self::Rule>> {\n");
+    out.push_str("        let _span = pair.get_span();\n");
+    out.push_str("        let mut tokens = vec![];\n");
+    out.push_str("        for child in pair.into_inner() {\n");
+    out.push_str("            let span = child.get_span();\n");
+    out.push_str("            let span = Range { start: span.start() as u32, end: span.end() as u32 };\n");
+    out.push_str("            match child.get_tag().map(|tag| tag.to_string()).as_deref() {\n");
+    writeln!(out, "                Some({:?}) => {{", snake_case(operand)).unwrap();
+    writeln!(out, "                    let operand = Box::new({operand}Node::from_pair(child)?);").unwrap();
+    writeln!(out, "                    tokens.push({token}::Operand(Self::{operand}(operand), span))").unwrap();
+    out.push_str("                }\n");
+    writeln!(out, "                Some(tag) => {{\n                    if let Some(operator) = {operator}::from_tag(tag) {{")
+        .unwrap();
+    writeln!(out, "                        tokens.push({token}::Operator(operator, span))").unwrap();
+    out.push_str("                    }\n                }\n                None => {}\n            }\n        }\n");
+    out.push_str("        let mut tokens = tokens.into_iter().peekable();\n");
+    writeln!(out, "        match Self::fold(&mut tokens, 0) {{").unwrap();
+    out.push_str("            Some((node, _)) if tokens.peek().is_none() => Ok(node),\n");
+    writeln!(out, "            _ => Err(YggdrasilError::invalid_node({rule}::{name}, _span)),").unwrap();
+    out.push_str("        }\n    }\n}\n\n");
+    writeln!(out, "impl {node} {{").unwrap();
+    out.push_str("    /// Pratt parsing over the flat CST, operators binding looser than `min` are left to the caller\n");
+    writeln!(out, "    fn fold(tokens: &mut Peekable<IntoIter<{token}>>, min: u32) -> Option<(Self, Range<u32>)> {{").unwrap();
+    out.push_str("        let (mut lhs, mut span) = match tokens.next()? {\n");
+    writeln!(out, "            {token}::Operand(node, span) => (node, span),").unwrap();
+    writeln!(out, "            {token}::Operator(operator, start) => {{").unwrap();
+    out.push_str("                let (operand, end) = Self::fold(tokens, operator.binding_power().1)?;\n");
+    out.push_str("                let span = start.start..end.end;\n");
+    out.push_str("                (Self::Unary { operator, operand: Box::new(operand), span: span.clone() }, span)\n");
+    out.push_str("            }\n        };\n");
+    writeln!(out, "        while let Some({token}::Operator(operator, _)) = tokens.peek() {{").unwrap();
+    out.push_str("            let (left, right) = operator.binding_power();\n");
+    out.push_str("            if left < min {\n                break;\n            }\n");
+    writeln!(out, "            let Some({token}::Operator(operator, end)) = tokens.next() else {{").unwrap();
+    out.push_str("                break;\n            };\n");
+    out.push_str("            if operator.is_postfix() {\n");
+    out.push_str("                span = span.start..end.end;\n");
+    out.push_str("                lhs = Self::Unary { operator, operand: Box::new(lhs), span: span.clone() };\n");
+    out.push_str("                continue;\n            }\n");
+    out.push_str("            let (rhs, end) = Self::fold(tokens, right)?;\n");
+    out.push_str("            span = span.start..end.end;\n");
+    out.push_str("            lhs = Self::Binary { operator, lhs: Box::new(lhs), rhs: Box::new(rhs), span: span.clone() };\n");
+    out.push_str("        }\n        Some((lhs, span))\n    }\n}\n");
+}
+
+fn fixity_method(out: &mut String, method: &str, operators: &[(&OperatorLevel, &Operator)], fixity: Fixity) {
+    let matching: Vec<_> =
+        operators.iter().filter(|(level, _)| level.fixity == fixity).map(|(_, o)| format!("Self::{}", o.tag.text)).collect();
+    writeln!(out, "    pub fn {method}(&self) -> bool {{").unwrap();
+    match matching.is_empty() {
+        true => out.push_str("        false\n"),
+        false => writeln!(out, "        matches!(self, {})", matching.join(" | ")).unwrap(),
+    }
+    out.push_str("    }\n");
+}
+
+fn patch_externals(module: &str, file: &GrammarFile, mut text: String) -> Result<String> {
+    for rule in file.rules.iter().filter(|rule| rule.kind == RuleKind::External) {
+        let marker = format!("{:?}", external_marker(&rule.name.text));
+        let calls = [format!("s.match_string({marker}, false)"), format!("builtin_text(s, {marker}, false)")];
+        if !calls.iter().any(|call| text.contains(call.as_str())) {
+            let message = format!("`parse_cst` does not match the external rule `{}`", rule.name.text);
+            return Err(BuildError::CodegenError { module: module.to_string(), message });
+        }
+        for call in calls {
+            text = text.replace(&call, &format!("{}(s)", rule.body));
+        }
+    }
+    Ok(text)
+}
+
+fn patch_lookaheads(module: &str, file: &GrammarFile, mut text: String) -> Result<String> {
+    for (index, (rule, lookahead)) in file.lookaheads().into_iter().enumerate() {
+        let marker = format!("{:?}", lookahead_marker(index));
+        let check = lookahead_check(lookahead, !rule.atomic);
+        let calls = [format!("builtin_text(s, {marker}, false)"), format!("s.match_string({marker}, false)")];
+        if !calls.iter().any(|call| text.contains(call.as_str())) {
+            let message = format!("`parse_cst` does not match the lookahead `{lookahead}` of `{}`", rule.name.text);
+            return Err(BuildError::CodegenError { module: module.to_string(), message });
+        }
+        for call in calls {
+            text = text.replace(&call, &check);
+        }
+    }
+    Ok(text)
+}
+
+fn patch_indentation(module: &str, file: &GrammarFile, mut text: String) -> Result<String> {
+    if !file.uses_indentation() {
+        return Ok(text);
+    }
+    let layout = |message: String| BuildError::CodegenError { module: module.to_string(), message };
+    for indentation in [Indentation::Indent, Indentation::Samedent, Indentation::Dedent] {
+        let marker = format!("{:?}", indentation_marker(indentation));
+        for call in [format!("builtin_text(s, {marker}, false)"), format!("s.match_string({marker}, false)")] {
+            text = text.replace(&call, &indentation_call(indentation));
+        }
+    }
+    // the bottom of the stack is the top level, so every block has one around it to compare with
+    let open = "    state(input, |state| match rule {\n";
+    let start = text.find(open).ok_or_else(|| layout("`parse_cst` does not match on `rule`".to_string()))?;
+    let end = text[start..]
+        .find("\n    })\n")
+        .map(|end| start + end + 1)
+        .ok_or_else(|| layout("`parse_cst` is not closed".to_string()))?;
+    let mut wrapped =
+        String::from("    state(input, |state| {\n        state.stack_push(|s| Ok(s)).and_then(|state| match rule {\n");
+    for line in text[start + open.len()..end].lines() {
+        writeln!(wrapped, "    {line}").unwrap();
+    }
+    wrapped.push_str("        })\n    })\n");
+    text.replace_range(start..end + "    })\n".len(), &wrapped);
+    // a rule that fails after `INDENT` or `DEDENT` must not leave the block opened or closed
+    for rule in &file.rules {
+        let mut changes_blocks = false;
+        rule.body.visit(&mut |e| {
+            changes_blocks |= matches!(e.kind, ExpressionKind::Indentation(Indentation::Indent | Indentation::Dedent))
+        });
+        if !changes_blocks {
+            continue;
+        }
+        let header = format!("fn parse_{}(state: Input) -> Output {{\n", snake_case(&imported_name(&rule.name.text)));
+        let missing = || layout(format!("`parse_cst` has no function for `{}`", rule.name.text));
+        let body = text.find(&header).map(|start| start + header.len()).ok_or_else(missing)?;
+        if !text[body..].starts_with("    state.rule(") {
+            return Err(missing());
+        }
+        let close = text[body..].find("\n}\n").map(|close| body + close).ok_or_else(missing)?;
+        text.insert(close, ')');
+        text.replace_range(body..body + "    state.rule(".len(), "    state.restore_on_err(|state| state.rule(");
+    }
+    for (call, helper) in INDENTATION_HELPERS {
+        if text.contains(call) {
+            text.push_str(helper);
+        }
+    }
+    Ok(text)
+}
+
+/// Wrap the functions of the recursive rules in `nesting::builtin_nested`, returns the names of the rules.
+///
+/// Every rule on a cycle is counted, whatever it opens with, any of them may be the one the stack grows through.
+fn patch_nesting<'g>(module: &str, file: &'g GrammarFile, mut text: String) -> Result<(String, Vec<&'g str>)> {
+    let recursive = recursive_rules(file);
+    let mut nested = vec![];
+    for rule in file.rules.iter().filter(|r| recursive.contains(r.name.text.as_str())) {
+        let header = format!("fn parse_{}(state: Input) -> Output {{\n", snake_case(&imported_name(&rule.name.text)));
+        let missing = || BuildError::CodegenError {
+            module: module.to_string(),
+            message: format!("`parse_cst` has no function for `{}`", rule.name.text),
+        };
+        let body = text.find(&header).map(|start| start + header.len()).ok_or_else(missing)?;
+        if !text[body..].starts_with("    state.") {
+            return Err(missing());
+        }
+        let close = text[body..].find("\n}\n").map(|close| body + close).ok_or_else(missing)?;
+        text.insert(close, ')');
+        text.insert_str(body + "    ".len(), "nesting::builtin_nested(state, |state| ");
+        nested.push(rule.name.text.as_str());
+    }
+    Ok((text, nested))
+}
+
+/// Rules that can reach themselves through references
+fn recursive_rules(file: &GrammarFile) -> BTreeSet<&str> {
+    let mut out = BTreeSet::new();
+    for rule in &file.rules {
+        let mut seen = BTreeSet::new();
+        let mut pending = rule.references();
+        while let Some(name) = pending.pop() {
+            if name == rule.name.text {
+                out.insert(rule.name.text.as_str());
+                break;
+            }
+            if seen.insert(name) {
+                pending.extend(file.rule(name).map(|r| r.references()).unwrap_or_default());
+            }
+        }
+    }
+    out
+}
+
+/// Wrap every rule function in `budget::builtin_counted`, around the nesting guard of recursive rules
+fn patch_budget(text: String) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text.as_str();
+    const OPEN: &str = "(state: Input) -> Output {\n    ";
+    while let Some(start) = rest.find("\nfn parse_") {
+        let Some(body) = rest[start..].find(OPEN).map(|open| start + open + OPEN.len())
+        else {
+            break;
+        };
+        let Some(close) = rest[body..].find("\n}\n").map(|close| body + close)
+        else {
+            break;
+        };
+        out.push_str(&rest[..body]);
+        out.push_str("budget::builtin_counted(state, |state| ");
+        out.push_str(&rest[body..close]);
+        out.push(')');
+        rest = &rest[close..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Let `parse_cst` start at a byte offset of the input, the grammars importing this one build their nodes of it that way
+fn patch_offset(module: &str, rule: &str, mut text: String) -> Result<String> {
+    let layout = |message: &str| BuildError::CodegenError { module: module.to_string(), message: message.to_string() };
+    let header = format!("pub(super) fn parse_cst(input: &str, rule: {rule}) -> OutputResult<{rule}> {{\n");
+    let start = text.find(&header).ok_or_else(|| layout("`parse_cst` is missing"))?;
+    let open = "    state(input, |state| ";
+    let call = text[start..].find(open).map(|call| start + call).ok_or_else(|| layout("`parse_cst` does not call `state`"))?;
+    let close = text[call..]
+        .find("\n    })\n")
+        .map(|close| call + close + "\n    }".len())
+        .ok_or_else(|| layout("`parse_cst` is not closed"))?;
+    text.insert(close, ')');
+    text.replace_range(
+        call..call + open.len(),
+        "    let skipped = input.get(..offset).map_or(usize::MAX, |before| before.chars().count());\n    \
+         state(input, |state| state.skip(skipped).and_then(|state| ",
+    );
+    let mut wrapper = header.clone();
+    wrapper.push_str("    parse_cst_at(input, 0, rule)\n}\n\n");
+    wrapper.push_str("/// [`parse_cst`] from the byte `offset` of `input` on\n");
+    writeln!(wrapper, "pub(super) fn parse_cst_at(input: &str, offset: usize, rule: {rule}) -> OutputResult<{rule}> {{")
+        .unwrap();
+    text.replace_range(start..start + header.len(), &wrapper);
+    let parser = format!("{}Parser", rule.strip_suffix("Rule").unwrap_or(rule));
+    writeln!(text, "\nimpl {parser} {{").unwrap();
+    text.push_str("    /// [`YggdrasilParser::parse_cst`] from the byte `offset` of `input` on, the spans are the ones\n");
+    text.push_str("    /// of the whole input and the parse fails if `offset` is not at a character of it\n");
+    writeln!(text, "    pub fn parse_cst_at(input: &str, offset: usize, rule: {rule}) -> OutputResult<'_, {rule}> {{").unwrap();
+    text.push_str("        parse_cst_at(input, offset, rule)\n    }\n}\n");
+    Ok(text)
+}
+
+fn budget_file(rule: &str) -> String {
+    let parser = format!("{}Parser", rule.strip_suffix("Rule").unwrap_or(rule));
+    let mut out = String::new();
+    out.push_str("use super::*;\nuse std::{cell::Cell, time::Instant};\n\n");
+    writeln!(out, "/// Caps of a parse run by [`{parser}::parse_cst_budgeted`], checked while the parser runs").unwrap();
+    out.push_str("#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]\n");
+    out.push_str("pub struct ParseBudget {\n");
+    out.push_str("    /// Most rule calls, the failed ones included\n    pub max_steps: usize,\n");
+    out.push_str("    /// Most nodes of the rules that returned, not counting the nodes of a rule failing around them\n");
+    out.push_str("    pub max_nodes: usize,\n");
+    out.push_str("    /// The parse fails once this instant is passed, `None` never reads the clock, which is unavailable\n");
+    out.push_str("    /// on some wasm targets\n    pub deadline: Option<Instant>,\n");
+    out.push_str("}\n\n");
+    out.push_str("/// The cap of a [`ParseBudget`] a parse went over\n");
+    out.push_str("#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]\n");
+    out.push_str("pub enum BudgetExceeded {\n");
+    out.push_str("    /// More rule calls than `max_steps`, the rejected one starts at `offset`\n");
+    out.push_str("    Steps {\n        /// Byte offset of the rejected rule call\n        offset: usize,\n    },\n");
+    out.push_str("    /// More nodes than `max_nodes`, the rejected one ends at `offset`\n");
+    out.push_str("    Nodes {\n        /// Byte offset where the rejected node ends\n        offset: usize,\n    },\n");
+    out.push_str("    /// The deadline passed before the rule call at `offset`\n");
+    out.push_str("    Deadline {\n        /// Byte offset of the rejected rule call\n        offset: usize,\n    },\n");
+    out.push_str("}\n\n");
+    out.push_str("impl ParseBudget {\n");
+    out.push_str("    /// No cap at all\n");
+    out.push_str("    pub const fn unlimited() -> Self {\n");
+    out.push_str("        Self { max_steps: usize::MAX, max_nodes: usize::MAX, deadline: None }\n    }\n}\n\n");
+    out.push_str("/// Steps between two looks at the clock\nconst CLOCK_INTERVAL: usize = 1024;\n\n");
+    out.push_str("thread_local! {\n");
+    out.push_str("    /// Steps, nodes, the budget and the cap first hit, of the parse running on this thread\n");
+    out.push_str("    static BUDGET: Cell<(usize, usize, Option<ParseBudget>, Option<BudgetExceeded>)> =\n");
+    out.push_str("        const { Cell::new((0, 0, None, None)) };\n");
+    out.push_str("}\n\n");
+    writeln!(out, "impl {parser} {{").unwrap();
+    out.push_str("    /// [`YggdrasilParser::parse_cst`] under `budget`, the parse stops where a cap is hit instead of\n");
+    out.push_str("    /// running to the end\n");
+    out.push_str("    pub fn parse_cst_budgeted(\n        input: &str,\n");
+    writeln!(out, "        rule: {rule},\n        budget: ParseBudget,").unwrap();
+    writeln!(out, "    ) -> Result<OutputResult<'_, {rule}>, BudgetExceeded> {{").unwrap();
+    out.push_str("        Self::with_budget(budget, || Self::parse_cst(input, rule))\n    }\n");
+    out.push_str("    /// Run `parse` with the rules it calls on this thread counted against `budget`, such as a parse with\n");
+    out.push_str("    /// another guard around it\n");
+    out.push_str("    pub fn with_budget<T>(budget: ParseBudget, parse: impl FnOnce() -> T) -> Result<T, BudgetExceeded> {\n");
+    out.push_str("        let outer = BUDGET.with(|b| b.replace((0, 0, Some(budget), None)));\n");
+    out.push_str("        let result = parse();\n");
+    out.push_str("        match BUDGET.with(|b| b.replace(outer)) {\n");
+    out.push_str("            (_, _, _, Some(exceeded)) => Err(exceeded),\n");
+    out.push_str("            _ => Ok(result),\n");
+    out.push_str("        }\n    }\n}\n\n");
+    out.push_str("/// Run the rule `parse` as one step, fails without running it once the budget is spent\n");
+    out.push_str("pub(super) fn builtin_counted(state: Input, parse: impl FnOnce(Input) -> Output) -> Output {\n");
+    out.push_str("    let (steps, nodes, budget, exceeded) = BUDGET.with(Cell::get);\n");
+    out.push_str("    let Some(budget) = budget else {\n        return parse(state);\n    };\n");
+    out.push_str("    // the parse is lost once a cap is hit, trying the remaining branches only wastes time\n");
+    out.push_str("    if exceeded.is_some() {\n        return Err(state);\n    }\n");
+    out.push_str("    let offset = state.position().pos();\n");
+    out.push_str("    let over = if steps >= budget.max_steps {\n");
+    out.push_str("        Some(BudgetExceeded::Steps { offset })\n");
+    out.push_str("    }\n    else if steps.is_multiple_of(CLOCK_INTERVAL) && budget.deadline.is_some_and(|deadline| Instant::now() > deadline) {\n");
+    out.push_str("        Some(BudgetExceeded::Deadline { offset })\n");
+    out.push_str("    }\n    else {\n        None\n    };\n");
+    out.push_str("    if over.is_some() {\n");
+    out.push_str("        BUDGET.with(|b| b.set((steps, nodes, Some(budget), over)));\n");
+    out.push_str("        return Err(state);\n    }\n");
+    out.push_str("    BUDGET.with(|b| b.set((steps + 1, nodes, Some(budget), None)));\n");
+    out.push_str("    let out = parse(state);\n");
+    out.push_str("    let (steps, built, _, exceeded) = BUDGET.with(Cell::get);\n");
+    out.push_str("    // the nodes of a failed rule are thrown away with it\n");
+    out.push_str("    let nodes = if out.is_ok() && exceeded.is_none() { built + 1 } else { nodes };\n");
+    out.push_str("    if nodes > budget.max_nodes {\n");
+    out.push_str("        let out = out.and_then(|state| {\n");
+    out.push_str("            let offset = state.position().pos();\n");
+    out.push_str("            BUDGET.with(|b| b.set((steps, nodes, Some(budget), Some(BudgetExceeded::Nodes { offset }))));\n");
+    out.push_str("            Err(state)\n        });\n");
+    out.push_str("        return out;\n    }\n");
+    out.push_str("    BUDGET.with(|b| b.set((steps, nodes, Some(budget), exceeded)));\n");
+    out.push_str("    out\n}\n");
+    out
+}
+
+fn nesting_file(rule: &str, nested: &[&str]) -> String {
+    let parser = format!("{}Parser", rule.strip_suffix("Rule").unwrap_or(rule));
+    let levels = nested.iter().map(|name| format!("`{name}`")).collect::<Vec<_>>().join(" or ");
+    let mut out = String::new();
+    out.push_str("use super::*;\nuse std::cell::Cell;\n\n");
+    writeln!(out, "/// Where a parse run by [`{parser}::parse_cst_nested`] opened more nested levels than allowed").unwrap();
+    out.push_str("#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]\n");
+    out.push_str("pub struct NestingExceeded {\n");
+    out.push_str("    /// Levels open with the rejected one, the limit plus one\n    pub depth: usize,\n");
+    out.push_str("    /// Byte offset where the rejected level starts\n    pub offset: usize,\n");
+    out.push_str("}\n\n");
+    out.push_str("thread_local! {\n");
+    out.push_str("    /// Open levels, the limit and where it was first hit, of the parse running on this thread\n");
+    out.push_str("    static NESTING: Cell<(usize, usize, Option<usize>)> = const { Cell::new((0, usize::MAX, None)) };\n");
+    out.push_str("}\n\n");
+    writeln!(out, "impl {parser} {{").unwrap();
+    writeln!(out, "    /// [`YggdrasilParser::parse_cst`] with at most `max_depth` levels open, one per running {levels},")
+        .unwrap();
+    out.push_str("    /// deeper input is rejected where the limit is hit instead of overflowing the stack\n");
+    out.push_str("    pub fn parse_cst_nested(\n        input: &str,\n");
+    writeln!(out, "        rule: {rule},\n        max_depth: usize,").unwrap();
+    writeln!(out, "    ) -> Result<OutputResult<'_, {rule}>, NestingExceeded> {{").unwrap();
+    out.push_str("        let outer = NESTING.with(|n| n.replace((0, max_depth, None)));\n");
+    out.push_str("        let result = Self::parse_cst(input, rule);\n");
+    out.push_str("        match NESTING.with(|n| n.replace(outer)) {\n");
+    out.push_str("            (_, _, Some(offset)) => Err(NestingExceeded { depth: max_depth + 1, offset }),\n");
+    out.push_str("            _ => Ok(result),\n");
+    out.push_str("        }\n    }\n}\n\n");
+    out.push_str("/// Run `parse` one level deeper, fails without running it when that goes over the limit\n");
+    out.push_str("pub(super) fn builtin_nested(state: Input, parse: impl FnOnce(Input) -> Output) -> Output {\n");
+    out.push_str("    let (depth, limit, exceeded) = NESTING.with(Cell::get);\n");
+    out.push_str("    // the parse is lost once the limit is hit, trying the remaining branches only wastes time\n");
+    out.push_str("    if exceeded.is_some() {\n        return Err(state);\n    }\n");
+    out.push_str("    if depth >= limit {\n");
+    out.push_str("        let offset = state.position().pos();\n");
+    out.push_str("        NESTING.with(|n| n.set((depth, limit, Some(offset))));\n");
+    out.push_str("        return Err(state);\n    }\n");
+    out.push_str("    NESTING.with(|n| n.set((depth + 1, limit, exceeded)));\n");
+    out.push_str("    let out = parse(state);\n");
+    out.push_str("    NESTING.with(|n| n.set((depth, limit, n.get().2)));\n");
+    out.push_str("    out\n}\n");
+    out
+}
+
+fn indentation_call(indentation: Indentation) -> String {
+    format!("builtin_{}(s)", indentation.to_string().to_lowercase())
+}
+
+/// Matchers appended to `parse_cst` when the grammar uses the call, the stack holds the indentation each open block adds
+/// to the one around it
+const INDENTATION_HELPERS: [(&str, &str); 6] = [
+    (
+        "builtin_indent(s)",
+        r#"
+/// Line breaks then an indentation deeper than the innermost block, which opens a block
+fn builtin_indent(state: Input) -> Output {
+    state.sequence(|s| {
+        builtin_line_breaks(s)
+            .and_then(|s| builtin_indented(s))
+            .and_then(|s| s.stack_push(|s| s.repeat(1..u32::MAX, |s| builtin_indentation(s))))
+    })
+}
+"#,
+    ),
+    (
+        "builtin_samedent(s)",
+        r#"
+/// Line breaks then the indentation of the innermost block
+fn builtin_samedent(state: Input) -> Output {
+    state.sequence(|s| {
+        builtin_line_breaks(s)
+            .and_then(|s| builtin_indented(s))
+            .and_then(|s| s.lookahead(false, |s| builtin_indentation(s)))
+    })
+}
+"#,
+    ),
+    (
+        "builtin_dedent(s)",
+        r#"
+/// The end of the input or a line indented less than the innermost block, which closes it
+fn builtin_dedent(state: Input) -> Output {
+    state
+        .lookahead(false, |s| s.sequence(|s| builtin_line_breaks(s).and_then(|s| builtin_indented(s))))
+        .and_then(|s| s.stack_drop())
+}
+"#,
+    ),
+    (
+        "builtin_indented(s)",
+        r#"
+/// The indentation of every open block, outermost first
+fn builtin_indented(state: Input) -> Output {
+    state.stack_match_peek_slice(0, None, MatchDir::BottomToTop)
+}
+"#,
+    ),
+    (
+        "builtin_line_breaks(s)",
+        r#"
+/// Line ends, with the blank lines after them
+fn builtin_line_breaks(state: Input) -> Output {
+    state.repeat(1..u32::MAX, |s| {
+        s.sequence(|s| {
+            s.repeat(0..u32::MAX, |s| builtin_indentation(s))
+                .and_then(|s| s.optional(|s| s.match_string("\r", false)))
+                .and_then(|s| s.match_string("\n", false))
+        })
+    })
+}
+"#,
+    ),
+    (
+        "builtin_indentation(s)",
+        r#"
+fn builtin_indentation(state: Input) -> Output {
+    state.match_char_if(|c| c == ' ' || c == '\t')
+}
+"#,
+    ),
+];
+
+/// The matcher of `expression` in the shape of the generated code, ignored rules are skipped between items if `skip`
+fn lookahead_check(expression: &Expression, skip: bool) -> String {
+    match &expression.kind {
+        ExpressionKind::Text(text) => format!("builtin_text(s, {text:?}, false)"),
+        ExpressionKind::Regex(pattern) | ExpressionKind::CharacterClass(pattern) => format!(
+            "builtin_regex(s, {{ static REGEX: OnceLock<Regex> = OnceLock::new(); REGEX.get_or_init(|| Regex::new({:?}).unwrap()) }})",
+            format!("^({pattern})")
+        ),
+        ExpressionKind::Any => "builtin_any(s)".to_string(),
+        ExpressionKind::Indentation(indentation) => indentation_call(*indentation),
+        ExpressionKind::Reference(name) => format!("parse_{}(s)", snake_case(&imported_name(name))),
+        ExpressionKind::External(path) => format!("{path}(s)"),
+        ExpressionKind::Sequence(items) => {
+            let mut out = String::from("s.sequence(|s| Ok(s)");
+            for (index, item) in items.iter().enumerate() {
+                if skip && index > 0 {
+                    out.push_str(".and_then(|s| builtin_ignore(s))");
+                }
+                write!(out, ".and_then(|s| {})", lookahead_check(item, skip)).unwrap();
+            }
+            out.push(')');
+            out
+        }
+        ExpressionKind::Choice(items) => {
+            let mut out = String::from("Err(s)");
+            for item in items {
+                write!(out, ".or_else(|s| {})", lookahead_check(item, skip)).unwrap();
+            }
+            out
+        }
+        ExpressionKind::Tagged { body, .. } | ExpressionKind::Capture { body, .. } => lookahead_check(body, skip),
+        ExpressionKind::Repeat { body, min: 0, max: Some(1) } => format!("s.optional(|s| {})", lookahead_check(body, skip)),
+        ExpressionKind::Repeat { body, min, max } => {
+            let end = max.map_or(u32::MAX, |max| max.saturating_add(1));
+            format!("s.repeat({min}..{end}, |s| {})", lookahead_check(body, skip))
+        }
+        ExpressionKind::Lookahead { positive, body } => {
+            format!("s.lookahead({positive}, |s| {})", lookahead_check(body, skip))
+        }
+    }
+}
+
+/// Every variant named in `parse_cst`, entry rules first and helper rules after them
+fn rule_variants<'a>(text: &'a str, rule: &str) -> Vec<&'a str> {
+    let mut out: Vec<&str> = vec![];
+    for line in text.lines() {
+        let Some(variant) = line
+            .trim()
+            .strip_prefix(rule)
+            .and_then(|rest| rest.strip_prefix("::"))
+            .and_then(|rest| rest.split_once(" => "))
+            .map(|(v, _)| v)
+        else {
+            continue;
+        };
+        if !out.contains(&variant) {
+            out.push(variant)
+        }
+    }
+    out
+}
+
+/// Tags attached with `tag_node("...")` inside the parse function of each rule, in order of appearance
+fn rule_tags<'a>(text: &'a str, rule: &str) -> Vec<(&'a str, Vec<&'a str>)> {
+    let opening = format!("state.rule({rule}::");
+    let mut out: Vec<(&str, Vec<&str>)> = vec![];
+    let mut in_parse = false;
+    for line in text.lines() {
+        if line.starts_with("fn ") {
+            in_parse = line.starts_with("fn parse_");
+            continue;
+        }
+        if !in_parse {
+            continue;
+        }
+        if let Some(variant) = line.trim().strip_prefix(opening.as_str()).and_then(|rest| rest.split_once(',')).map(|(v, _)| v)
+        {
+            out.push((variant, vec![]));
+        }
+        let Some((_, tags)) = out.last_mut()
+        else {
+            continue;
+        };
+        for (_, rest) in line.match_indices("tag_node(\"").map(|(index, _)| line.split_at(index + 10)) {
+            if let Some((tag, _)) = rest.split_once('"') {
+                if !tags.contains(&tag) {
+                    tags.push(tag)
+                }
+            }
+        }
+    }
+    out
+}
+
+fn rules_file(rule: &str, entries: &[&str], variants: &[&str], tags: &[(&str, Vec<&str>)], file: &GrammarFile) -> String {
+    let mut out = String::new();
+    out.push_str("use super::*;\n\n");
+    out.push_str("/// How a rule was declared in the grammar\n");
+    out.push_str("#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]\n");
+    out.push_str("pub enum RuleKind {\n");
+    out.push_str("    /// `class Name { ... }`\n    Class,\n");
+    out.push_str("    /// `union Name { ... }`\n    Union,\n");
+    out.push_str("    /// `operators Name { ... }`\n    Operators,\n");
+    out.push_str("    /// `external Name { path::to::scanner }`\n    External,\n");
+    out.push_str("    /// Added by the generator, not written in the grammar\n    Builtin,\n");
+    out.push_str("}\n\n");
+    writeln!(out, "/// Static description of a rule, see [`{rule}::info`]").unwrap();
+    out.push_str("#[derive(Clone, Debug, PartialEq, Eq)]\n");
+    out.push_str("pub struct RuleInfo {\n");
+    writeln!(out, "    /// The rule\n    pub rule: {rule},").unwrap();
+    out.push_str("    /// Name in the grammar\n    pub name: &'static str,\n");
+    out.push_str("    /// `class`, `union`, `operators`, `external` or generated\n    pub kind: RuleKind,\n");
+    out.push_str("    /// Marked `atomic`, whitespace is not skipped inside\n    pub atomic: bool,\n");
+    out.push_str("    /// Marked `entry` in the grammar, unrelated to the rules `parse_cst` accepts, see `is_parsable`\n");
+    out.push_str("    pub entry: bool,\n");
+    out.push_str("    /// Marked `ignore`, matched between the tokens of other rules\n    pub ignored: bool,\n");
+    out.push_str("    /// Tags the rule gives to its children in the CST, in order of appearance\n");
+    out.push_str("    pub tags: &'static [&'static str],\n");
+    out.push_str("    /// The `///` comments before the declaration\n    pub document: &'static str,\n");
+    out.push_str("    /// Byte range of the declaration in the grammar file, empty for builtin rules\n");
+    out.push_str("    pub span: Range<usize>,\n");
+    out.push_str("}\n\n");
+    writeln!(out, "impl {rule} {{").unwrap();
+    out.push_str("    /// Rules accepted by [`YggdrasilParser::parse_cst`], in declaration order\n");
+    out.push_str("    pub const PARSABLE_RULES: &'static [Self] = &[\n");
+    for entry in entries {
+        writeln!(out, "        Self::{entry},").unwrap();
+    }
+    out.push_str("    ];\n");
+    out.push_str("    /// Description of every rule, in declaration order\n");
+    out.push_str("    pub const RULE_INFO: &'static [RuleInfo] = &[\n");
+    for variant in variants {
+        let declared = file.rules.iter().find(|r| imported_name(&r.name.text) == *variant);
+        let kind = match declared.map(|r| r.kind) {
+            Some(RuleKind::Class) => "Class",
+            Some(RuleKind::Union) => "Union",
+            Some(RuleKind::Operators) => "Operators",
+            Some(RuleKind::External) => "External",
+            None => "Builtin",
+        };
+        let tags = tags.iter().find(|(v, _)| v == variant).map(|(_, t)| t.as_slice()).unwrap_or_default();
+        out.push_str("        RuleInfo {\n");
+        writeln!(out, "            rule: Self::{variant},").unwrap();
+        writeln!(out, "            name: {:?},", declared.map_or(*variant, |r| r.name.text.as_str())).unwrap();
+        writeln!(out, "            kind: RuleKind::{kind},").unwrap();
+        writeln!(out, "            atomic: {},", declared.is_some_and(|r| r.atomic)).unwrap();
+        writeln!(out, "            entry: {},", declared.is_some_and(|r| r.entry)).unwrap();
+        writeln!(out, "            ignored: {},", declared.is_some_and(|r| r.ignored)).unwrap();
+        writeln!(out, "            tags: &{tags:?},").unwrap();
+        writeln!(out, "            document: {:?},", declared.map(|r| r.document.as_str()).unwrap_or_default()).unwrap();
+        writeln!(out, "            span: {:?},", declared.map(|r| r.span.clone()).unwrap_or_default()).unwrap();
+        out.push_str("        },\n");
+    }
+    out.push_str("    ];\n");
+    out.push_str("    /// Rules a parse can start from, helper rules such as `IgnoreText` are left out\n");
+    out.push_str("    pub fn parsable_rules() -> &'static [Self] {\n        Self::PARSABLE_RULES\n    }\n");
+    out.push_str("    /// Whether [`YggdrasilParser::parse_cst`] can start from this rule\n");
+    out.push_str("    pub fn is_parsable(&self) -> bool {\n        Self::PARSABLE_RULES.contains(self)\n    }\n");
+    out.push_str("    /// Static description of this rule\n");
+    out.push_str("    pub fn info(&self) -> &'static RuleInfo {\n        match self {\n");
+    for (index, variant) in variants.iter().enumerate() {
+        writeln!(out, "            Self::{variant} => &Self::RULE_INFO[{index}],").unwrap();
+    }
+    out.push_str("        }\n    }\n");
+    out.push_str("    /// Name of the rule in the grammar\n");
+    out.push_str("    pub fn name(&self) -> &'static str {\n        self.info().name\n    }\n");
+    out.push_str("    /// Find a rule by its name in the grammar\n");
+    out.push_str("    pub fn from_name(name: &str) -> Option<Self> {\n");
+    out.push_str("        Self::RULE_INFO.iter().find(|info| info.name == name).map(|info| info.rule)\n    }\n");
+    out.push_str("}\n");
+    out
+}
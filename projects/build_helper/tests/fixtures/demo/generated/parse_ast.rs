@@ -0,0 +1,109 @@
+use super::*;
+
+#[automatically_derived]
+impl YggdrasilNode for ExprNode {
+    type Rule = DemoRule;
+
+    fn get_range(&self) -> Option<Range<usize>> {
+        Some(Range { start: self.span.start as usize, end: self.span.end as usize })
+    }
+    fn from_pair(pair: TokenPair<Self::Rule>) -> Result<Self, YggdrasilError<Self::Rule>> {
+        let _span = pair.get_span();
+        Ok(Self {
+            atom: pair.take_tagged_items::<AtomNode>(Cow::Borrowed("atom"))?,
+            span: Range { start: _span.start() as u32, end: _span.end() as u32 },
+        })
+    }
+}
+
+#[automatically_derived]
+impl YggdrasilNode for AtomNode {
+    type Rule = DemoRule;
+
+    fn get_range(&self) -> Option<Range<usize>> {
+        match self {
+            _ => unimplemented!(),
+        }
+    }
+    fn from_pair(pair: TokenPair<Self::Rule>) -> Result<Self, YggdrasilError<Self::Rule>> {
+        let _span = pair.get_span();
+        if let Ok(s) = pair.take_tagged_one::<ExprNode>(Cow::Borrowed("atom_3")) {
+            return Ok(Self::Atom3(s));
+        }
+        if let Ok(s) = pair.take_tagged_one::<EscapeNode>(Cow::Borrowed("escape")) {
+            return Ok(Self::Escape(s));
+        }
+        if let Ok(s) = pair.take_tagged_one::<NumberNode>(Cow::Borrowed("number")) {
+            return Ok(Self::Number(s));
+        }
+        if let Ok(s) = pair.take_tagged_one::<QuoteNode>(Cow::Borrowed("quote")) {
+            return Ok(Self::Quote(s));
+        }
+        Err(YggdrasilError::invalid_node(DemoRule::Atom, _span))
+    }
+}
+
+#[automatically_derived]
+impl YggdrasilNode for NumberNode {
+    type Rule = DemoRule;
+
+    fn get_range(&self) -> Option<Range<usize>> {
+        Some(Range { start: self.span.start as usize, end: self.span.end as usize })
+    }
+    fn from_pair(pair: TokenPair<Self::Rule>) -> Result<Self, YggdrasilError<Self::Rule>> {
+        let _span = pair.get_span();
+        Ok(Self { span: Range { start: _span.start() as u32, end: _span.end() as u32 } })
+    }
+}
+
+#[automatically_derived]
+impl YggdrasilNode for EscapeNode {
+    type Rule = DemoRule;
+
+    fn get_range(&self) -> Option<Range<usize>> {
+        Some(Range { start: self.span.start as usize, end: self.span.end as usize })
+    }
+    fn from_pair(pair: TokenPair<Self::Rule>) -> Result<Self, YggdrasilError<Self::Rule>> {
+        let _span = pair.get_span();
+        Ok(Self { span: Range { start: _span.start() as u32, end: _span.end() as u32 } })
+    }
+}
+
+#[automatically_derived]
+impl YggdrasilNode for QuoteNode {
+    type Rule = DemoRule;
+
+    fn get_range(&self) -> Option<Range<usize>> {
+        Some(Range { start: self.span.start as usize, end: self.span.end as usize })
+    }
+    fn from_pair(pair: TokenPair<Self::Rule>) -> Result<Self, YggdrasilError<Self::Rule>> {
+        let _span = pair.get_span();
+        Ok(Self { span: Range { start: _span.start() as u32, end: _span.end() as u32 } })
+    }
+}
+
+#[automatically_derived]
+impl YggdrasilNode for CommentNode {
+    type Rule = DemoRule;
+
+    fn get_range(&self) -> Option<Range<usize>> {
+        Some(Range { start: self.span.start as usize, end: self.span.end as usize })
+    }
+    fn from_pair(pair: TokenPair<Self::Rule>) -> Result<Self, YggdrasilError<Self::Rule>> {
+        let _span = pair.get_span();
+        Ok(Self { span: Range { start: _span.start() as u32, end: _span.end() as u32 } })
+    }
+}
+
+#[automatically_derived]
+impl YggdrasilNode for SpaceNode {
+    type Rule = DemoRule;
+
+    fn get_range(&self) -> Option<Range<usize>> {
+        Some(Range { start: self.span.start as usize, end: self.span.end as usize })
+    }
+    fn from_pair(pair: TokenPair<Self::Rule>) -> Result<Self, YggdrasilError<Self::Rule>> {
+        let _span = pair.get_span();
+        Ok(Self { span: Range { start: _span.start() as u32, end: _span.end() as u32 } })
+    }
+}
@@ -0,0 +1,123 @@
+use super::*;
+
+pub(super) fn parse_cst(input: &str, rule: DemoRule) -> OutputResult<DemoRule> {
+    state(input, |state| match rule {
+        DemoRule::Expr => parse_expr(state),
+        DemoRule::Atom => parse_atom(state),
+        DemoRule::Number => parse_number(state),
+        DemoRule::Escape => parse_escape(state),
+        DemoRule::Quote => parse_quote(state),
+        DemoRule::Comment => parse_comment(state),
+        DemoRule::Space => parse_space(state),
+        DemoRule::IgnoreText => unreachable!(),
+        DemoRule::IgnoreRegex => unreachable!(),
+    })
+}
+#[inline]
+fn parse_expr(state: Input) -> Output {
+    state.rule(DemoRule::Expr, |s| {
+        s.sequence(|s| {
+            Ok(s)
+                .and_then(|s| s.repeat(0..4294967295, |s| builtin_text(s, "-", false).and_then(|s| s.tag_node("negative"))))
+                .and_then(|s| builtin_ignore(s))
+                .and_then(|s| parse_atom(s).and_then(|s| s.tag_node("atom")))
+                .and_then(|s| builtin_ignore(s))
+                .and_then(|s| {
+                    s.repeat(0..4294967295, |s| {
+                        s.sequence(|s| {
+                            Ok(s)
+                                .and_then(|s| {
+                                    Err(s)
+                                        .or_else(|s| builtin_text(s, "+", false).and_then(|s| s.tag_node("add")))
+                                        .or_else(|s| builtin_text(s, "-", false).and_then(|s| s.tag_node("sub")))
+                                })
+                                .and_then(|s| builtin_ignore(s))
+                                .and_then(|s| {
+                                    s.repeat(0..4294967295, |s| {
+                                        builtin_text(s, "-", false).and_then(|s| s.tag_node("negative"))
+                                    })
+                                })
+                                .and_then(|s| builtin_ignore(s))
+                                .and_then(|s| parse_atom(s).and_then(|s| s.tag_node("atom")))
+                        })
+                    })
+                })
+        })
+    })
+}
+#[inline]
+fn parse_atom(state: Input) -> Output {
+    state.rule(DemoRule::Atom, |s| {
+        Err(s)
+            .or_else(|s| parse_number(s).and_then(|s| s.tag_node("number")))
+            .or_else(|s| parse_escape(s).and_then(|s| s.tag_node("escape")))
+            .or_else(|s| parse_quote(s).and_then(|s| s.tag_node("quote")))
+            .or_else(|s| {
+                s.sequence(|s| {
+                    Ok(s)
+                        .and_then(|s| builtin_text(s, "(", false))
+                        .and_then(|s| builtin_ignore(s))
+                        .and_then(|s| parse_expr(s).and_then(|s| s.tag_node("expr")))
+                        .and_then(|s| builtin_ignore(s))
+                        .and_then(|s| builtin_text(s, ")", false))
+                })
+                .and_then(|s| s.tag_node("atom_3"))
+            })
+    })
+}
+#[inline]
+fn parse_number(state: Input) -> Output {
+    state.rule(DemoRule::Number, |s| {
+        s.match_regex({
+            static REGEX: OnceLock<Regex> = OnceLock::new();
+            REGEX.get_or_init(|| Regex::new("^([0-9]+)").unwrap())
+        })
+    })
+}
+#[inline]
+fn parse_escape(state: Input) -> Output {
+    state.rule(DemoRule::Escape, |s| {
+        s.sequence(|s| {
+            Ok(s)
+                .and_then(|s| builtin_text(s, "\\", false))
+                .and_then(|s| builtin_ignore(s))
+                .and_then(|s| builtin_any(s).and_then(|s| s.tag_node("c")))
+        })
+    })
+}
+#[inline]
+fn parse_quote(state: Input) -> Output {
+    state.rule(DemoRule::Quote, |s| {
+        s.sequence(|s| {
+            Ok(s)
+                .and_then(|s| builtin_text(s, "\"", false))
+                .and_then(|s| builtin_ignore(s))
+                .and_then(|s| builtin_any(s).and_then(|s| s.tag_node("c")))
+        })
+    })
+}
+#[inline]
+fn parse_comment(state: Input) -> Output {
+    state.rule(DemoRule::Comment, |s| s.match_string("__ygg_external_Comment__", false))
+}
+#[inline]
+fn parse_space(state: Input) -> Output {
+    state.rule(DemoRule::Space, |s| s.match_string(" ", false))
+}
+
+/// All rules ignored in ast mode, inline is not recommended
+fn builtin_ignore(state: Input) -> Output {
+    state.repeat(0..u32::MAX, |s| parse_comment(s).or_else(|s| parse_space(s)))
+}
+
+fn builtin_any(state: Input) -> Output {
+    state.rule(DemoRule::IgnoreText, |s| s.match_char_if(|_| true))
+}
+
+fn builtin_text<'i>(state: Input<'i>, text: &'static str, case: bool) -> Output<'i> {
+    state.rule(DemoRule::IgnoreText, |s| s.match_string(text, case))
+}
+
+fn builtin_regex<'i, 'r>(state: Input<'i>, regex: &'r Regex) -> Output<'i> {
+    state.rule(DemoRule::IgnoreRegex, |s| s.match_regex(regex))
+}
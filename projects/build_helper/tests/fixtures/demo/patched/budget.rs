@@ -0,0 +1,113 @@
+use super::*;
+use std::{cell::Cell, time::Instant};
+
+/// Caps of a parse run by [`DemoParser::parse_cst_budgeted`], checked while the parser runs
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ParseBudget {
+    /// Most rule calls, the failed ones included
+    pub max_steps: usize,
+    /// Most nodes of the rules that returned, not counting the nodes of a rule failing around them
+    pub max_nodes: usize,
+    /// The parse fails once this instant is passed, `None` never reads the clock, which is unavailable
+    /// on some wasm targets
+    pub deadline: Option<Instant>,
+}
+
+/// The cap of a [`ParseBudget`] a parse went over
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum BudgetExceeded {
+    /// More rule calls than `max_steps`, the rejected one starts at `offset`
+    Steps {
+        /// Byte offset of the rejected rule call
+        offset: usize,
+    },
+    /// More nodes than `max_nodes`, the rejected one ends at `offset`
+    Nodes {
+        /// Byte offset where the rejected node ends
+        offset: usize,
+    },
+    /// The deadline passed before the rule call at `offset`
+    Deadline {
+        /// Byte offset of the rejected rule call
+        offset: usize,
+    },
+}
+
+impl ParseBudget {
+    /// No cap at all
+    pub const fn unlimited() -> Self {
+        Self { max_steps: usize::MAX, max_nodes: usize::MAX, deadline: None }
+    }
+}
+
+/// Steps between two looks at the clock
+const CLOCK_INTERVAL: usize = 1024;
+
+thread_local! {
+    /// Steps, nodes, the budget and the cap first hit, of the parse running on this thread
+    static BUDGET: Cell<(usize, usize, Option<ParseBudget>, Option<BudgetExceeded>)> =
+        const { Cell::new((0, 0, None, None)) };
+}
+
+impl DemoParser {
+    /// [`YggdrasilParser::parse_cst`] under `budget`, the parse stops where a cap is hit instead of
+    /// running to the end
+    pub fn parse_cst_budgeted(
+        input: &str,
+        rule: DemoRule,
+        budget: ParseBudget,
+    ) -> Result<OutputResult<'_, DemoRule>, BudgetExceeded> {
+        Self::with_budget(budget, || Self::parse_cst(input, rule))
+    }
+    /// Run `parse` with the rules it calls on this thread counted against `budget`, such as a parse with
+    /// another guard around it
+    pub fn with_budget<T>(budget: ParseBudget, parse: impl FnOnce() -> T) -> Result<T, BudgetExceeded> {
+        let outer = BUDGET.with(|b| b.replace((0, 0, Some(budget), None)));
+        let result = parse();
+        match BUDGET.with(|b| b.replace(outer)) {
+            (_, _, _, Some(exceeded)) => Err(exceeded),
+            _ => Ok(result),
+        }
+    }
+}
+
+/// Run the rule `parse` as one step, fails without running it once the budget is spent
+pub(super) fn builtin_counted(state: Input, parse: impl FnOnce(Input) -> Output) -> Output {
+    let (steps, nodes, budget, exceeded) = BUDGET.with(Cell::get);
+    let Some(budget) = budget else {
+        return parse(state);
+    };
+    // the parse is lost once a cap is hit, trying the remaining branches only wastes time
+    if exceeded.is_some() {
+        return Err(state);
+    }
+    let offset = state.position().pos();
+    let over = if steps >= budget.max_steps {
+        Some(BudgetExceeded::Steps { offset })
+    }
+    else if steps.is_multiple_of(CLOCK_INTERVAL) && budget.deadline.is_some_and(|deadline| Instant::now() > deadline) {
+        Some(BudgetExceeded::Deadline { offset })
+    }
+    else {
+        None
+    };
+    if over.is_some() {
+        BUDGET.with(|b| b.set((steps, nodes, Some(budget), over)));
+        return Err(state);
+    }
+    BUDGET.with(|b| b.set((steps + 1, nodes, Some(budget), None)));
+    let out = parse(state);
+    let (steps, built, _, exceeded) = BUDGET.with(Cell::get);
+    // the nodes of a failed rule are thrown away with it
+    let nodes = if out.is_ok() && exceeded.is_none() { built + 1 } else { nodes };
+    if nodes > budget.max_nodes {
+        let out = out.and_then(|state| {
+            let offset = state.position().pos();
+            BUDGET.with(|b| b.set((steps, nodes, Some(budget), Some(BudgetExceeded::Nodes { offset }))));
+            Err(state)
+        });
+        return out;
+    }
+    BUDGET.with(|b| b.set((steps, nodes, Some(budget), exceeded)));
+    out
+}
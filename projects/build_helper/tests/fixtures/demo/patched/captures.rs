@@ -0,0 +1,28 @@
+use super::*;
+
+/// Text matched by a named terminal such as `c:ANY`, as a range of the input
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Captured {
+    pub span: Range<u32>,
+}
+
+impl Captured {
+    /// The captured text in the parsed `input`
+    pub fn text<'i>(&self, input: &'i str) -> &'i str {
+        &input[self.span.start as usize..self.span.end as usize]
+    }
+}
+
+#[automatically_derived]
+impl YggdrasilNode for Captured {
+    type Rule = DemoRule;
+
+    fn get_range(&self) -> Option<Range<usize>> {
+        Some(Range { start: self.span.start as usize, end: self.span.end as usize })
+    }
+    fn from_pair(pair: TokenPair<Self::Rule>) -> Result<Self, YggdrasilError<Self::Rule>> {
+        let _span = pair.get_span();
+        Ok(Self { span: Range { start: _span.start() as u32, end: _span.end() as u32 } })
+    }
+}
@@ -0,0 +1,102 @@
+#![allow(dead_code, unused_imports, non_camel_case_types)]
+#![allow(missing_docs, rustdoc::missing_crate_level_docs)]
+#![allow(clippy::unnecessary_cast)]
+#![doc = include_str!("readme.md")]
+
+mod captures;
+mod parse_cst;
+mod parse_ast;
+mod budget;
+mod nesting;
+mod operators;
+mod rules;
+
+pub use self::captures::Captured;
+pub use self::budget::{BudgetExceeded, ParseBudget};
+pub use self::nesting::NestingExceeded;
+pub use self::operators::*;
+pub use self::rules::{RuleInfo, RuleKind};
+
+use std::{borrow::Cow, ops::Range, sync::OnceLock};
+use yggdrasil_rt::*;
+
+type Input<'i> = Box<State<'i, DemoRule>>;
+type Output<'i> = Result<Box<State<'i, DemoRule>>, Box<State<'i, DemoRule>>>;
+
+#[doc = include_str!("railway.min.svg")]
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DemoParser {}
+
+impl YggdrasilParser for DemoParser {
+    type Rule = DemoRule;
+    fn parse_cst(input: &str, rule: Self::Rule) -> OutputResult<DemoRule> {
+        self::parse_cst::parse_cst(input, rule)
+    }
+}
+
+#[repr(u32)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DemoRule {
+    Expr,
+    Atom,
+    Number,
+    Escape,
+    Quote,
+    Comment,
+    Space,
+    /// Label for text literal
+    IgnoreText,
+    /// Label for regex literal
+    IgnoreRegex,
+}
+
+impl YggdrasilRule for DemoRule {
+    fn is_ignore(&self) -> bool {
+        matches!(self, Self::IgnoreText | Self::IgnoreRegex | Self::Comment | Self::Space)
+    }
+
+    fn get_style(&self) -> &'static str {
+        match self {
+            Self::Expr => "",
+            Self::Atom => "",
+            Self::Number => "",
+            Self::Escape => "",
+            Self::Quote => "",
+            Self::Comment => "",
+            Self::Space => "",
+            _ => "",
+        }
+    }
+}
+#[derive(Clone, Debug, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AtomNode {
+    Atom3(ExprNode),
+    Escape(EscapeNode),
+    Number(NumberNode),
+    Quote(EscapeNode),
+}
+#[derive(Clone, Debug, Hash, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NumberNode {
+    pub span: Range<u32>,
+}
+#[derive(Clone, Debug, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EscapeNode {
+    pub c: Captured,
+    pub span: Range<u32>,
+}
+#[derive(Clone, Debug, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CommentNode {
+    pub span: Range<u32>,
+}
+#[derive(Clone, Debug, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SpaceNode {
+    pub span: Range<u32>,
+}
@@ -0,0 +1,51 @@
+use super::*;
+use std::cell::Cell;
+
+/// Where a parse run by [`DemoParser::parse_cst_nested`] opened more nested levels than allowed
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct NestingExceeded {
+    /// Levels open with the rejected one, the limit plus one
+    pub depth: usize,
+    /// Byte offset where the rejected level starts
+    pub offset: usize,
+}
+
+thread_local! {
+    /// Open levels, the limit and where it was first hit, of the parse running on this thread
+    static NESTING: Cell<(usize, usize, Option<usize>)> = const { Cell::new((0, usize::MAX, None)) };
+}
+
+impl DemoParser {
+    /// [`YggdrasilParser::parse_cst`] with at most `max_depth` levels open, one per running `Expr` or `Atom`,
+    /// deeper input is rejected where the limit is hit instead of overflowing the stack
+    pub fn parse_cst_nested(
+        input: &str,
+        rule: DemoRule,
+        max_depth: usize,
+    ) -> Result<OutputResult<'_, DemoRule>, NestingExceeded> {
+        let outer = NESTING.with(|n| n.replace((0, max_depth, None)));
+        let result = Self::parse_cst(input, rule);
+        match NESTING.with(|n| n.replace(outer)) {
+            (_, _, Some(offset)) => Err(NestingExceeded { depth: max_depth + 1, offset }),
+            _ => Ok(result),
+        }
+    }
+}
+
+/// Run `parse` one level deeper, fails without running it when that goes over the limit
+pub(super) fn builtin_nested(state: Input, parse: impl FnOnce(Input) -> Output) -> Output {
+    let (depth, limit, exceeded) = NESTING.with(Cell::get);
+    // the parse is lost once the limit is hit, trying the remaining branches only wastes time
+    if exceeded.is_some() {
+        return Err(state);
+    }
+    if depth >= limit {
+        let offset = state.position().pos();
+        NESTING.with(|n| n.set((depth, limit, Some(offset))));
+        return Err(state);
+    }
+    NESTING.with(|n| n.set((depth + 1, limit, exceeded)));
+    let out = parse(state);
+    NESTING.with(|n| n.set((depth, limit, n.get().2)));
+    out
+}
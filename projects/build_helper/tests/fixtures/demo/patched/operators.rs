@@ -0,0 +1,126 @@
+use super::*;
+use std::{iter::Peekable, vec::IntoIter};
+
+/// Operators of [`ExprNode`], tighter binding operators come later
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ExprOperator {
+    /// `+`
+    Add,
+    /// `-`
+    Sub,
+    /// `-`
+    Negative,
+}
+
+impl ExprOperator {
+    /// The operator tagged `tag` in the CST
+    pub fn from_tag(tag: &str) -> Option<Self> {
+        match tag {
+            "add" => Some(Self::Add),
+            "sub" => Some(Self::Sub),
+            "negative" => Some(Self::Negative),
+            _ => None,
+        }
+    }
+    /// Left and right binding power, the higher binds tighter
+    pub fn binding_power(&self) -> (u32, u32) {
+        match self {
+            Self::Add => (2, 3),
+            Self::Sub => (2, 3),
+            Self::Negative => (4, 4),
+        }
+    }
+    /// Written before its operand
+    pub fn is_prefix(&self) -> bool {
+        matches!(self, Self::Negative)
+    }
+    /// Written after its operand
+    pub fn is_postfix(&self) -> bool {
+        false
+    }
+}
+
+#[derive(Clone, Debug, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ExprNode {
+    Atom(Box<AtomNode>),
+    Unary { operator: ExprOperator, operand: Box<ExprNode>, span: Range<u32> },
+    Binary { operator: ExprOperator, lhs: Box<ExprNode>, rhs: Box<ExprNode>, span: Range<u32> },
+}
+
+enum ExprToken {
+    Operand(ExprNode, Range<u32>),
+    Operator(ExprOperator, Range<u32>),
+}
+
+#[automatically_derived]
+impl YggdrasilNode for ExprNode {
+    type Rule = DemoRule;
+
+    fn get_range(&self) -> Option<Range<usize>> {
+        match self {
+            Self::Atom(node) => node.get_range(),
+            Self::Unary { span, .. } | Self::Binary { span, .. } => {
+                Some(Range { start: span.start as usize, end: span.end as usize })
+            }
+        }
+    }
+    fn from_pair(pair: TokenPair<Self::Rule>) -> Result<Self, YggdrasilError<Self::Rule>> {
+        let _span = pair.get_span();
+        let mut tokens = vec![];
+        for child in pair.into_inner() {
+            let span = child.get_span();
+            let span = Range { start: span.start() as u32, end: span.end() as u32 };
+            match child.get_tag().map(|tag| tag.to_string()).as_deref() {
+                Some("atom") => {
+                    let operand = Box::new(AtomNode::from_pair(child)?);
+                    tokens.push(ExprToken::Operand(Self::Atom(operand), span))
+                }
+                Some(tag) => {
+                    if let Some(operator) = ExprOperator::from_tag(tag) {
+                        tokens.push(ExprToken::Operator(operator, span))
+                    }
+                }
+                None => {}
+            }
+        }
+        let mut tokens = tokens.into_iter().peekable();
+        match Self::fold(&mut tokens, 0) {
+            Some((node, _)) if tokens.peek().is_none() => Ok(node),
+            _ => Err(YggdrasilError::invalid_node(DemoRule::Expr, _span)),
+        }
+    }
+}
+
+impl ExprNode {
+    /// Pratt parsing over the flat CST, operators binding looser than `min` are left to the caller
+    fn fold(tokens: &mut Peekable<IntoIter<ExprToken>>, min: u32) -> Option<(Self, Range<u32>)> {
+        let (mut lhs, mut span) = match tokens.next()? {
+            ExprToken::Operand(node, span) => (node, span),
+            ExprToken::Operator(operator, start) => {
+                let (operand, end) = Self::fold(tokens, operator.binding_power().1)?;
+                let span = start.start..end.end;
+                (Self::Unary { operator, operand: Box::new(operand), span: span.clone() }, span)
+            }
+        };
+        while let Some(ExprToken::Operator(operator, _)) = tokens.peek() {
+            let (left, right) = operator.binding_power();
+            if left < min {
+                break;
+            }
+            let Some(ExprToken::Operator(operator, end)) = tokens.next() else {
+                break;
+            };
+            if operator.is_postfix() {
+                span = span.start..end.end;
+                lhs = Self::Unary { operator, operand: Box::new(lhs), span: span.clone() };
+                continue;
+            }
+            let (rhs, end) = Self::fold(tokens, right)?;
+            span = span.start..end.end;
+            lhs = Self::Binary { operator, lhs: Box::new(lhs), rhs: Box::new(rhs), span: span.clone() };
+        }
+        Some((lhs, span))
+    }
+}
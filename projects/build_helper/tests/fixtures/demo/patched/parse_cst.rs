@@ -0,0 +1,141 @@
+use super::*;
+
+pub(super) fn parse_cst(input: &str, rule: DemoRule) -> OutputResult<DemoRule> {
+    parse_cst_at(input, 0, rule)
+}
+
+/// [`parse_cst`] from the byte `offset` of `input` on
+pub(super) fn parse_cst_at(input: &str, offset: usize, rule: DemoRule) -> OutputResult<DemoRule> {
+    if !rule.is_parsable() {
+        let span = TextSpan::new(input, 0, 0).expect("the start of the input is a valid span");
+        return Err(YggdrasilError::invalid_node(rule, span));
+    }
+    let skipped = input.get(..offset).map_or(usize::MAX, |before| before.chars().count());
+    state(input, |state| state.skip(skipped).and_then(|state| match rule {
+        DemoRule::Expr => parse_expr(state),
+        DemoRule::Atom => parse_atom(state),
+        DemoRule::Number => parse_number(state),
+        DemoRule::Escape => parse_escape(state),
+        DemoRule::Quote => parse_quote(state),
+        DemoRule::Comment => parse_comment(state),
+        DemoRule::Space => parse_space(state),
+        DemoRule::IgnoreText => Err(state),
+        DemoRule::IgnoreRegex => Err(state),
+    }))
+}
+#[inline]
+fn parse_expr(state: Input) -> Output {
+    budget::builtin_counted(state, |state| nesting::builtin_nested(state, |state| state.rule(DemoRule::Expr, |s| {
+        s.sequence(|s| {
+            Ok(s)
+                .and_then(|s| s.repeat(0..4294967295, |s| builtin_text(s, "-", false).and_then(|s| s.tag_node("negative"))))
+                .and_then(|s| builtin_ignore(s))
+                .and_then(|s| parse_atom(s).and_then(|s| s.tag_node("atom")))
+                .and_then(|s| builtin_ignore(s))
+                .and_then(|s| {
+                    s.repeat(0..4294967295, |s| {
+                        s.sequence(|s| {
+                            Ok(s)
+                                .and_then(|s| {
+                                    Err(s)
+                                        .or_else(|s| builtin_text(s, "+", false).and_then(|s| s.tag_node("add")))
+                                        .or_else(|s| builtin_text(s, "-", false).and_then(|s| s.tag_node("sub")))
+                                })
+                                .and_then(|s| builtin_ignore(s))
+                                .and_then(|s| {
+                                    s.repeat(0..4294967295, |s| {
+                                        builtin_text(s, "-", false).and_then(|s| s.tag_node("negative"))
+                                    })
+                                })
+                                .and_then(|s| builtin_ignore(s))
+                                .and_then(|s| parse_atom(s).and_then(|s| s.tag_node("atom")))
+                        })
+                    })
+                })
+        })
+    })))
+}
+#[inline]
+fn parse_atom(state: Input) -> Output {
+    budget::builtin_counted(state, |state| nesting::builtin_nested(state, |state| state.rule(DemoRule::Atom, |s| {
+        Err(s)
+            .or_else(|s| parse_number(s).and_then(|s| s.tag_node("number")))
+            .or_else(|s| parse_escape(s).and_then(|s| s.tag_node("escape")))
+            .or_else(|s| parse_quote(s).and_then(|s| s.tag_node("quote")))
+            .or_else(|s| {
+                s.sequence(|s| {
+                    Ok(s)
+                        .and_then(|s| builtin_text(s, "(", false))
+                        .and_then(|s| builtin_ignore(s))
+                        .and_then(|s| parse_expr(s).and_then(|s| s.tag_node("expr")))
+                        .and_then(|s| builtin_ignore(s))
+                        .and_then(|s| builtin_text(s, ")", false))
+                })
+                .and_then(|s| s.tag_node("atom_3"))
+            })
+    })))
+}
+#[inline]
+fn parse_number(state: Input) -> Output {
+    budget::builtin_counted(state, |state| state.rule(DemoRule::Number, |s| {
+        s.match_regex({
+            static REGEX: OnceLock<Regex> = OnceLock::new();
+            REGEX.get_or_init(|| Regex::new("^([0-9]+)").unwrap())
+        })
+    }))
+}
+#[inline]
+fn parse_escape(state: Input) -> Output {
+    budget::builtin_counted(state, |state| state.rule(DemoRule::Escape, |s| {
+        s.sequence(|s| {
+            Ok(s)
+                .and_then(|s| builtin_text(s, "\\", false))
+                .and_then(|s| builtin_ignore(s))
+                .and_then(|s| builtin_any(s).and_then(|s| s.tag_node("c")))
+        })
+    }))
+}
+#[inline]
+fn parse_quote(state: Input) -> Output {
+    budget::builtin_counted(state, |state| state.rule(DemoRule::Quote, |s| {
+        s.sequence(|s| {
+            Ok(s)
+                .and_then(|s| builtin_text(s, "\"", false))
+                .and_then(|s| builtin_ignore(s))
+                .and_then(|s| builtin_any(s).and_then(|s| s.tag_node("c")))
+        })
+    }))
+}
+#[inline]
+fn parse_comment(state: Input) -> Output {
+    budget::builtin_counted(state, |state| state.rule(DemoRule::Comment, |s| crate::scanners::comment(s)))
+}
+#[inline]
+fn parse_space(state: Input) -> Output {
+    budget::builtin_counted(state, |state| state.rule(DemoRule::Space, |s| s.match_string(" ", false)))
+}
+
+/// All rules ignored in ast mode, inline is not recommended
+fn builtin_ignore(state: Input) -> Output {
+    state.repeat(0..u32::MAX, |s| parse_comment(s).or_else(|s| parse_space(s)))
+}
+
+fn builtin_any(state: Input) -> Output {
+    state.rule(DemoRule::IgnoreText, |s| s.match_char_if(|_| true))
+}
+
+fn builtin_text<'i>(state: Input<'i>, text: &'static str, case: bool) -> Output<'i> {
+    state.rule(DemoRule::IgnoreText, |s| s.match_string(text, case))
+}
+
+fn builtin_regex<'i, 'r>(state: Input<'i>, regex: &'r Regex) -> Output<'i> {
+    state.rule(DemoRule::IgnoreRegex, |s| s.match_regex(regex))
+}
+
+impl DemoParser {
+    /// [`YggdrasilParser::parse_cst`] from the byte `offset` of `input` on, the spans are the ones
+    /// of the whole input and the parse fails if `offset` is not at a character of it
+    pub fn parse_cst_at(input: &str, offset: usize, rule: DemoRule) -> OutputResult<'_, DemoRule> {
+        parse_cst_at(input, offset, rule)
+    }
+}
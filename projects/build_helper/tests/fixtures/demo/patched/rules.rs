@@ -0,0 +1,184 @@
+use super::*;
+
+/// How a rule was declared in the grammar
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum RuleKind {
+    /// `class Name { ... }`
+    Class,
+    /// `union Name { ... }`
+    Union,
+    /// `operators Name { ... }`
+    Operators,
+    /// `external Name { path::to::scanner }`
+    External,
+    /// Added by the generator, not written in the grammar
+    Builtin,
+}
+
+/// Static description of a rule, see [`DemoRule::info`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RuleInfo {
+    /// The rule
+    pub rule: DemoRule,
+    /// Name in the grammar
+    pub name: &'static str,
+    /// `class`, `union`, `operators`, `external` or generated
+    pub kind: RuleKind,
+    /// Marked `atomic`, whitespace is not skipped inside
+    pub atomic: bool,
+    /// Marked `entry` in the grammar, unrelated to the rules `parse_cst` accepts, see `is_parsable`
+    pub entry: bool,
+    /// Marked `ignore`, matched between the tokens of other rules
+    pub ignored: bool,
+    /// Tags the rule gives to its children in the CST, in order of appearance
+    pub tags: &'static [&'static str],
+    /// The `///` comments before the declaration
+    pub document: &'static str,
+    /// Byte range of the declaration in the grammar file, empty for builtin rules
+    pub span: Range<usize>,
+}
+
+impl DemoRule {
+    /// Rules accepted by [`YggdrasilParser::parse_cst`], in declaration order
+    pub const PARSABLE_RULES: &'static [Self] = &[
+        Self::Expr,
+        Self::Atom,
+        Self::Number,
+        Self::Escape,
+        Self::Quote,
+        Self::Comment,
+        Self::Space,
+    ];
+    /// Description of every rule, in declaration order
+    pub const RULE_INFO: &'static [RuleInfo] = &[
+        RuleInfo {
+            rule: Self::Expr,
+            name: "Expr",
+            kind: RuleKind::Operators,
+            atomic: false,
+            entry: true,
+            ignored: false,
+            tags: &["negative", "atom", "add", "sub"],
+            document: "Sums of numbers and escapes",
+            span: 49..154,
+        },
+        RuleInfo {
+            rule: Self::Atom,
+            name: "Atom",
+            kind: RuleKind::Union,
+            atomic: false,
+            entry: false,
+            ignored: false,
+            tags: &["number", "escape", "quote", "expr", "atom_3"],
+            document: "",
+            span: 155..233,
+        },
+        RuleInfo {
+            rule: Self::Number,
+            name: "Number",
+            kind: RuleKind::Class,
+            atomic: true,
+            entry: false,
+            ignored: false,
+            tags: &[],
+            document: "",
+            span: 234..283,
+        },
+        RuleInfo {
+            rule: Self::Escape,
+            name: "Escape",
+            kind: RuleKind::Class,
+            atomic: false,
+            entry: false,
+            ignored: false,
+            tags: &["c"],
+            document: "`\\n`, the escaped character is captured",
+            span: 328..354,
+        },
+        RuleInfo {
+            rule: Self::Quote,
+            name: "Quote",
+            kind: RuleKind::Class,
+            atomic: false,
+            entry: false,
+            ignored: false,
+            tags: &["c"],
+            document: "Built as an `Escape`, it has the same fields",
+            span: 404..439,
+        },
+        RuleInfo {
+            rule: Self::Comment,
+            name: "Comment",
+            kind: RuleKind::External,
+            atomic: false,
+            entry: false,
+            ignored: true,
+            tags: &[],
+            document: "",
+            span: 440..492,
+        },
+        RuleInfo {
+            rule: Self::Space,
+            name: "Space",
+            kind: RuleKind::Class,
+            atomic: false,
+            entry: false,
+            ignored: true,
+            tags: &[],
+            document: "",
+            span: 493..519,
+        },
+        RuleInfo {
+            rule: Self::IgnoreText,
+            name: "IgnoreText",
+            kind: RuleKind::Builtin,
+            atomic: false,
+            entry: false,
+            ignored: false,
+            tags: &[],
+            document: "",
+            span: 0..0,
+        },
+        RuleInfo {
+            rule: Self::IgnoreRegex,
+            name: "IgnoreRegex",
+            kind: RuleKind::Builtin,
+            atomic: false,
+            entry: false,
+            ignored: false,
+            tags: &[],
+            document: "",
+            span: 0..0,
+        },
+    ];
+    /// Rules a parse can start from, helper rules such as `IgnoreText` are left out
+    pub fn parsable_rules() -> &'static [Self] {
+        Self::PARSABLE_RULES
+    }
+    /// Whether [`YggdrasilParser::parse_cst`] can start from this rule
+    pub fn is_parsable(&self) -> bool {
+        Self::PARSABLE_RULES.contains(self)
+    }
+    /// Static description of this rule
+    pub fn info(&self) -> &'static RuleInfo {
+        match self {
+            Self::Expr => &Self::RULE_INFO[0],
+            Self::Atom => &Self::RULE_INFO[1],
+            Self::Number => &Self::RULE_INFO[2],
+            Self::Escape => &Self::RULE_INFO[3],
+            Self::Quote => &Self::RULE_INFO[4],
+            Self::Comment => &Self::RULE_INFO[5],
+            Self::Space => &Self::RULE_INFO[6],
+            Self::IgnoreText => &Self::RULE_INFO[7],
+            Self::IgnoreRegex => &Self::RULE_INFO[8],
+        }
+    }
+    /// Name of the rule in the grammar
+    pub fn name(&self) -> &'static str {
+        self.info().name
+    }
+    /// Find a rule by its name in the grammar
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::RULE_INFO.iter().find(|info| info.name == name).map(|info| info.rule)
+    }
+}
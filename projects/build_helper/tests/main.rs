@@ -0,0 +1,215 @@
+use build_helper::{BLESS_VARIABLE, BuildError, GrammarDirectory, OutputMode, ParserBuilder, grammar_imports, module_name};
+use std::{
+    env::var_os,
+    fs::{copy, create_dir_all, read_dir, read_to_string, remove_dir_all, remove_file, write},
+    path::{Path, PathBuf},
+};
+
+const JSON5: &str = include_str!("../../build_by_dep/grammars/json5.ygg");
+
+/// Imports `json5.ygg`
+const SETTINGS: &str = include_str!("../../build_by_dep/grammars/settings.ygg");
+
+#[test]
+fn ready() {
+    println!("it works!")
+}
+
+#[test]
+fn test_modes() {
+    let builder = ParserBuilder::new("json5");
+    assert_eq!(builder.mode(), &OutputMode::OutDir);
+    let builder = builder.checked_in("src/json5");
+    assert_eq!(builder.mode(), &OutputMode::CheckedIn("src/json5".into()));
+    assert_eq!(builder.out_dir().module(), "json5");
+    let builder = ParserBuilder::new("json5").derive("PartialEq").derive("Eq");
+    assert_eq!(builder.derives(), ["PartialEq", "Eq"]);
+}
+
+#[test]
+fn test_outside_build_script() {
+    // cargo sets `OUT_DIR` for build scripts only, tests that generate pass `build_dir` instead of changing it
+    if std::env::var_os("OUT_DIR").is_none() {
+        let error = ParserBuilder::new("json5").generate("grammar Json5 {}").unwrap_err();
+        assert!(matches!(error, BuildError::MissingOutDir));
+    }
+}
+
+#[test]
+fn test_generate_twice() {
+    let root = std::env::temp_dir().join(format!("build_helper_twice_{}", std::process::id()));
+    let _ = remove_dir_all(&root);
+    let builder = ParserBuilder::new("twice").build_dir(&root);
+    let staging = builder.generate("grammar Twice {}\nentry class List { '[' List? ']' }").unwrap();
+    assert!(staging.join("nesting.rs").is_file());
+    // the second grammar has no recursive rule, nothing of the first one is left behind
+    let staging = builder.generate("grammar Twice {}\nentry class Word { /[a-z]+/ }").unwrap();
+    assert!(!staging.join("nesting.rs").exists());
+    assert!(!read_to_string(staging.join("mod.rs")).unwrap().contains("mod nesting;"));
+
+    if var_os(BLESS_VARIABLE).is_some() {
+        // the committed module would be overwritten instead of checked
+        return remove_dir_all(&root).unwrap();
+    }
+    let committed = root.join("committed");
+    let builder = builder.checked_in(&committed);
+    builder.generate("grammar Twice {}\nentry class List { '[' List? ']' }").unwrap();
+    assert!(committed.join("nesting.rs").is_file());
+    let error = builder.generate("grammar Twice {}\nentry class Word { /[a-z]+/ }").unwrap_err();
+    assert!(matches!(error, BuildError::StaleFiles { files, .. } if files.contains(&committed.join("nesting.rs"))));
+    remove_dir_all(&root).unwrap();
+}
+
+/// Code generator output for `fixtures/demo.ygg`, the patched files are compared with `fixtures/demo/patched`
+#[test]
+fn test_patch_fixture() {
+    let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+    let grammar = read_to_string(fixtures.join("demo.ygg")).unwrap();
+    let work = std::env::temp_dir().join(format!("build_helper_patch_{}", std::process::id()));
+    let _ = remove_dir_all(&work);
+    create_dir_all(&work).unwrap();
+    for file in files(&fixtures.join("demo/generated")) {
+        copy(&file, work.join(file.file_name().unwrap())).unwrap();
+    }
+    ParserBuilder::new("demo").derive("PartialEq").patch_generated(&grammar, &work).unwrap();
+    let read = |name: &str| read_to_string(work.join(name)).unwrap();
+    // captures, `c:ANY` is a field
+    assert!(read("mod.rs").contains("pub struct EscapeNode {\n    pub c: Captured,"));
+    // redirects, `Quote -> Escape` is built as an `EscapeNode`
+    assert!(!read("mod.rs").contains("pub struct QuoteNode"));
+    assert!(read("mod.rs").contains("Quote(EscapeNode)"));
+    // derives, `@derive(Default)` and `derive("PartialEq")`
+    assert!(read("mod.rs").contains("#[derive(Clone, Debug, Hash, PartialEq, Default)]\n#[cfg_attr(feature = \"serde\", derive(serde::Serialize, serde::Deserialize))]\npub struct NumberNode"));
+    // operators, `ExprNode` is folded from the flat tree
+    assert!(read("operators.rs").contains("Negative"));
+    // externals, the scanner replaces the placeholder text
+    assert!(read("parse_cst.rs").contains("crate::scanners::comment("));
+    assert!(!read("parse_cst.rs").contains("__ygg_external_"));
+    // nesting, the union `Atom` reaches itself through `Expr` without opening with a literal, and budget, every rule
+    // call is counted
+    let counted = "budget::builtin_counted(state, |state| ";
+    let nested = |rule: &str| format!("fn parse_{rule}(state: Input) -> Output {{\n    {counted}nesting::builtin_nested(");
+    assert!(read("parse_cst.rs").contains(&nested("atom")));
+    assert!(read("parse_cst.rs").contains(&nested("expr")));
+    assert!(read("parse_cst.rs").contains(&format!("fn parse_escape(state: Input) -> Output {{\n    {counted}state.rule(")));
+    assert!(read("mod.rs").contains("pub use self::budget::{BudgetExceeded, ParseBudget};"));
+    assert!(read("nesting.rs").contains("one per running `Expr` or `Atom`"));
+
+    // serde, the derives behind the `serde` feature are left out
+    let plain = std::env::temp_dir().join(format!("build_helper_plain_{}", std::process::id()));
+    let _ = remove_dir_all(&plain);
+    create_dir_all(&plain).unwrap();
+    for file in files(&fixtures.join("demo/generated")) {
+        copy(&file, plain.join(file.file_name().unwrap())).unwrap();
+    }
+    ParserBuilder::new("demo").serde(false).patch_generated(&grammar, &plain).unwrap();
+    assert!(files(&plain).iter().all(|file| !read_to_string(file).unwrap().contains("serde")));
+    remove_dir_all(&plain).unwrap();
+
+    let expected = fixtures.join("demo/patched");
+    if var_os(BLESS_VARIABLE).is_some_and(|v| v == "1") {
+        let _ = remove_dir_all(&expected);
+        create_dir_all(&expected).unwrap();
+        for file in files(&work) {
+            copy(&file, expected.join(file.file_name().unwrap())).unwrap();
+        }
+    }
+    let names = |directory: &Path| files(directory).iter().map(|f| f.file_name().unwrap().to_owned()).collect::<Vec<_>>();
+    assert_eq!(names(&work), names(&expected), "run with {BLESS_VARIABLE}=1 to update the snapshot");
+    for file in files(&work) {
+        let snapshot = read_to_string(expected.join(file.file_name().unwrap())).unwrap().replace('\r', "");
+        assert_eq!(read_to_string(&file).unwrap(), snapshot, "{} differs, run with {BLESS_VARIABLE}=1", file.display());
+    }
+    remove_dir_all(&work).unwrap();
+}
+
+/// Files directly inside `directory`, sorted
+fn files(directory: &Path) -> Vec<PathBuf> {
+    let mut out: Vec<_> = read_dir(directory).unwrap().map(|e| e.unwrap().path()).filter(|p| p.is_file()).collect();
+    out.sort();
+    out
+}
+
+#[test]
+fn test_left_recursion() {
+    let grammar = "grammar Calc {}\nentry union Sum {\n    | Sum '+' Number #Add\n    | Number\n}\nclass Number { /[0-9]+/ }";
+    let error = ParserBuilder::new("calc").generate(grammar).unwrap_err();
+    assert!(matches!(&error, BuildError::LeftRecursion { module, cycles } if module == "calc" && cycles == &["Sum -> Sum"]));
+    assert_eq!(error.to_string(), "grammar of `calc` is left recursive, write it as `operators` or a repetition: Sum -> Sum");
+}
+
+#[test]
+fn test_module_name() {
+    assert_eq!(module_name(Path::new("grammars/Json5.ygg")).as_deref(), Some("json5"));
+    assert_eq!(module_name(Path::new("grammars/ini-file.ygg")).as_deref(), Some("ini_file"));
+    assert_eq!(module_name(Path::new("grammars/5json.ygg")), None);
+}
+
+#[test]
+fn test_grammar_imports() {
+    let text =
+        "import \"common.ygg\"\n  import 'lexical/strings.ygg' as strings\n// import \"commented.ygg\"\nclass A { 'import' }";
+    assert_eq!(grammar_imports(text).unwrap(), vec!["common.ygg", "lexical/strings.ygg"]);
+    assert!(grammar_imports("import 'a.ygg'\nclass {").is_err());
+}
+
+#[test]
+fn test_linked_imports() {
+    let error = ParserBuilder::new("settings").generate(SETTINGS).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "failed to generate `settings`: `json5.ygg` is imported but not given to `ParserBuilder::import`"
+    );
+    let error = ParserBuilder::new("settings").import("json5", "class Other { 'a' }").generate(SETTINGS).unwrap_err();
+    assert_eq!(error.to_string(), "failed to generate `settings`: grammar:13:19: rule `Value` is not defined in `json5.ygg`");
+    let error =
+        ParserBuilder::new("a").import("b", "import 'a.ygg'\nclass B { a::A }").generate("import 'b.ygg'\nclass A { b::B }");
+    assert_eq!(error.unwrap_err().to_string(), "failed to generate `a`: grammars import each other: a -> b -> a");
+    let root = std::env::temp_dir().join(format!("build_helper_imports_{}", std::process::id()));
+    let _ = remove_dir_all(&root);
+    ParserBuilder::new("json5").build_dir(&root).generate(JSON5).unwrap();
+    let settings = ParserBuilder::new("settings").build_dir(&root).import("json5", JSON5).generate(SETTINGS).unwrap();
+    assert!(read_to_string(settings.join("mod.rs")).unwrap().contains("use super::json5;"));
+    remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn test_discover() {
+    let root = std::env::temp_dir().join(format!("build_helper_discover_{}", std::process::id()));
+    let _ = remove_dir_all(&root);
+    create_dir_all(root.join("nested")).unwrap();
+    write(root.join("json5.ygg"), "grammar Json5 {}").unwrap();
+    write(root.join("nested/ini.ygg"), "grammar Ini {}").unwrap();
+    write(root.join("notes.md"), "not a grammar").unwrap();
+    let found = GrammarDirectory::new(&root).discover().unwrap();
+    let modules: Vec<_> = found.iter().map(|g| g.module.as_str()).collect();
+    assert_eq!(modules, vec!["json5", "ini"]);
+
+    write(root.join("nested/json5.ygg"), "grammar Json5 {}").unwrap();
+    let error = GrammarDirectory::new(&root).discover().unwrap_err();
+    assert!(matches!(error, BuildError::DuplicateModule { module, .. } if module == "json5"));
+    remove_dir_all(&root).unwrap();
+
+    create_dir_all(&root).unwrap();
+    write(root.join("a.ygg"), "class {").unwrap();
+    write(root.join("b.ygg"), "class B { 'b' ").unwrap();
+    let directory = GrammarDirectory::new(&root).build_dir(root.join("out"));
+    let error = directory.run().unwrap_err();
+    assert!(matches!(error, BuildError::GrammarErrors { errors } if errors.len() == 2));
+    remove_file(root.join("a.ygg")).unwrap();
+    let error = directory.run().unwrap_err();
+    assert!(matches!(error, BuildError::CodegenError { module, .. } if module == "b"));
+    write(root.join("b.ygg"), "grammar B {}\nentry class B { 'b' }").unwrap();
+    directory.run().unwrap();
+    assert!(root.join("out/b/mod.rs").is_file());
+    remove_dir_all(&root).unwrap();
+
+    let error = GrammarDirectory::new(&root).discover().unwrap_err();
+    assert!(matches!(error, BuildError::MissingGrammar { imported_by: None, .. }));
+    // a missing directory fails the build instead of generating nothing
+    let error = GrammarDirectory::new(&root).build_dir(&root).run().unwrap_err();
+    assert_eq!(error.to_string(), format!("grammar directory {} does not exist", root.display()));
+    // the old name still compiles
+    #[allow(deprecated)]
+    let _: fn(build_helper::DiscoveredGrammar) -> build_helper::GrammarFile = |grammar| grammar;
+}
@@ -0,0 +1,5 @@
+use build_helper::{GrammarDirectory, Result};
+
+fn main() -> Result<()> {
+    GrammarDirectory::new("grammars").run()
+}
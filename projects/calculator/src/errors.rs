@@ -0,0 +1,47 @@
+use crate::calc::CalcRule;
+use std::fmt::{Display, Formatter};
+use yggdrasil_rt::YggdrasilError;
+
+/// Errors raised while evaluating an expression
+#[derive(Debug, Clone)]
+pub enum Error {
+    /// Input left after the longest expression the grammar matched
+    TrailingInput {
+        /// Byte offset where the expression stops
+        offset: usize,
+    },
+    /// A division whose right side is zero
+    DivisionByZero {
+        /// Byte offset where the division starts
+        offset: usize,
+    },
+    /// A factorial of a negative or fractional number
+    InvalidFactorial {
+        /// Byte offset where the factorial starts
+        offset: usize,
+    },
+    /// The input was rejected by the grammar
+    SyntaxError(YggdrasilError<CalcRule>),
+}
+
+/// Result type of [`evaluate`](crate::evaluate)
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl From<YggdrasilError<CalcRule>> for Error {
+    fn from(value: YggdrasilError<CalcRule>) -> Self {
+        Self::SyntaxError(value)
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::TrailingInput { offset } => write!(f, "unexpected input at offset {offset}"),
+            Self::DivisionByZero { offset } => write!(f, "division by zero at offset {offset}"),
+            Self::InvalidFactorial { offset } => write!(f, "factorial of a negative or fractional number at offset {offset}"),
+            Self::SyntaxError(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
@@ -0,0 +1,61 @@
+use crate::{
+    Error, Result,
+    calc::{AtomNode, CalcParser, CalcRule, ExprNode, ExprOperator},
+};
+use yggdrasil_rt::{YggdrasilNode, YggdrasilParser};
+
+/// Parse a whole expression into the tree folded by the operator table of `Expr`.
+pub fn parse_expr(input: &str) -> Result<ExprNode> {
+    let cst = CalcParser::parse_cst(input, CalcRule::Expr)?;
+    let end = cst.clone().map(|pair| pair.get_span().end()).max().unwrap_or(0);
+    // the longest match is taken, what follows it is not an error of the grammar
+    if end < input.len() {
+        return Err(Error::TrailingInput { offset: end });
+    }
+    Ok(ExprNode::from_cst(cst)?)
+}
+
+/// Parse and compute a whole expression.
+pub fn evaluate(input: &str) -> Result<f64> {
+    value(input, &parse_expr(input)?)
+}
+
+fn value(input: &str, node: &ExprNode) -> Result<f64> {
+    match node {
+        ExprNode::Atom(atom) => match atom.as_ref() {
+            AtomNode::Number(number) => {
+                let text = &input[number.span.start as usize..number.span.end as usize];
+                // `[0-9]+` always reads, it may round a long literal
+                Ok(text.parse().unwrap_or(f64::INFINITY))
+            }
+            AtomNode::Group(group) => value(input, &group.expr),
+        },
+        ExprNode::Unary { operator, operand, span } => {
+            let operand = value(input, operand)?;
+            match operator {
+                ExprOperator::Negative => Ok(-operand),
+                ExprOperator::Factorial => factorial(operand).ok_or(Error::InvalidFactorial { offset: span.start as usize }),
+                _ => unreachable!("`{operator:?}` is not a unary operator"),
+            }
+        }
+        ExprNode::Binary { operator, lhs, rhs, span } => {
+            let (lhs, rhs) = (value(input, lhs)?, value(input, rhs)?);
+            match operator {
+                ExprOperator::Add => Ok(lhs + rhs),
+                ExprOperator::Sub => Ok(lhs - rhs),
+                ExprOperator::Mul => Ok(lhs * rhs),
+                ExprOperator::Div if rhs == 0.0 => Err(Error::DivisionByZero { offset: span.start as usize }),
+                ExprOperator::Div => Ok(lhs / rhs),
+                ExprOperator::Power => Ok(lhs.powf(rhs)),
+                _ => unreachable!("`{operator:?}` is not a binary operator"),
+            }
+        }
+    }
+}
+
+fn factorial(n: f64) -> Option<f64> {
+    if n < 0.0 || n.fract() != 0.0 {
+        return None;
+    }
+    Some((1..=n as u64).map(|i| i as f64).product())
+}
@@ -0,0 +1,17 @@
+#![deny(missing_debug_implementations)]
+#![warn(missing_docs, rustdoc::missing_crate_level_docs)]
+#![doc = include_str!("../readme.md")]
+#![doc(html_logo_url = "https://raw.githubusercontent.com/oovm/shape-rs/dev/projects/images/Trapezohedron.svg")]
+#![doc(html_favicon_url = "https://raw.githubusercontent.com/oovm/shape-rs/dev/projects/images/Trapezohedron.svg")]
+
+pub use crate::{
+    errors::{Error, Result},
+    evaluate::{evaluate, parse_expr},
+};
+pub use yggdrasil_rt::{YggdrasilNode, YggdrasilParser};
+
+mod errors;
+mod evaluate;
+mod scanners;
+
+build_helper::include_parser!("calc");
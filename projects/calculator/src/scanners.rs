@@ -0,0 +1,17 @@
+use crate::calc::CalcRule;
+use yggdrasil_rt::State;
+
+/// `/* a /* b */ c */`, matched by the `external` rule `Comment`
+pub fn comment<'i>(s: Box<State<'i, CalcRule>>) -> Result<Box<State<'i, CalcRule>>, Box<State<'i, CalcRule>>> {
+    s.sequence(|s| {
+        s.match_string("/*", false)
+            .and_then(|s| {
+                s.repeat(0..u32::MAX, |s| {
+                    comment(s).or_else(|s| {
+                        s.lookahead(false, |s| s.match_string("*/", false)).and_then(|s| s.match_char_if(|_| true))
+                    })
+                })
+            })
+            .and_then(|s| s.match_string("*/", false))
+    })
+}
@@ -0,0 +1,96 @@
+use calculator::{
+    Error,
+    calc::{AtomNode, ExprNode, ExprOperator},
+    evaluate, parse_expr,
+};
+
+#[test]
+fn ready() {
+    println!("it works!")
+}
+
+/// The folded tree as prefix notation, groups kept as parentheses
+fn prefix(input: &str) -> String {
+    fn write(input: &str, node: &ExprNode) -> String {
+        let symbol = |operator: &ExprOperator| match operator {
+            ExprOperator::Add => "+",
+            ExprOperator::Sub | ExprOperator::Negative => "-",
+            ExprOperator::Mul => "*",
+            ExprOperator::Div => "/",
+            ExprOperator::Power => "^",
+            ExprOperator::Factorial => "!",
+        };
+        match node {
+            ExprNode::Atom(atom) => match atom.as_ref() {
+                AtomNode::Number(number) => input[number.span.start as usize..number.span.end as usize].to_string(),
+                AtomNode::Group(group) => format!("({})", write(input, &group.expr)),
+            },
+            ExprNode::Unary { operator, operand, .. } => format!("({} {})", symbol(operator), write(input, operand)),
+            ExprNode::Binary { operator, lhs, rhs, .. } => {
+                format!("({} {} {})", symbol(operator), write(input, lhs), write(input, rhs))
+            }
+        }
+    }
+    write(input, &parse_expr(input).unwrap())
+}
+
+#[test]
+fn test_precedence() {
+    assert_eq!(prefix("1 + 2 * 3"), "(+ 1 (* 2 3))");
+    assert_eq!(prefix("1 * 2 + 3"), "(+ (* 1 2) 3)");
+    assert_eq!(prefix("1 - 6 / 3"), "(- 1 (/ 6 3))");
+    assert_eq!(prefix("2 * 3 ^ 2"), "(* 2 (^ 3 2))");
+    // the prefix minus binds looser than `^` and `!`, tighter than `*`
+    assert_eq!(prefix("-2 ^ 2"), "(- (^ 2 2))");
+    assert_eq!(prefix("-3!"), "(- (! 3))");
+    assert_eq!(prefix("-2 * 3"), "(* (- 2) 3)");
+    assert_eq!(prefix("2 ^ -1"), "(^ 2 (- 1))");
+    assert_eq!(prefix("2 ^ 3!"), "(^ 2 (! 3))");
+    // parentheses override the table
+    assert_eq!(prefix("(1 + 2) * 3"), "(* ((+ 1 2)) 3)");
+}
+
+#[test]
+fn test_associativity() {
+    assert_eq!(prefix("1 - 2 - 3"), "(- (- 1 2) 3)");
+    assert_eq!(prefix("1 + 2 - 3 + 4"), "(+ (- (+ 1 2) 3) 4)");
+    assert_eq!(prefix("8 / 4 / 2"), "(/ (/ 8 4) 2)");
+    assert_eq!(prefix("8 / 4 * 2"), "(* (/ 8 4) 2)");
+    assert_eq!(prefix("2 ^ 3 ^ 2"), "(^ 2 (^ 3 2))");
+    assert_eq!(prefix("3!!"), "(! (! 3))");
+    assert_eq!(prefix("--1"), "(- (- 1))");
+}
+
+#[test]
+fn test_spans() {
+    let ExprNode::Binary { operator, lhs, rhs, span } = parse_expr("1 - 2 - 3").unwrap() else { panic!("not a binary node") };
+    assert_eq!((operator, span), (ExprOperator::Sub, 0..9));
+    assert!(matches!(*lhs, ExprNode::Binary { span: ref inner, .. } if *inner == (0..5)));
+    assert!(matches!(*rhs, ExprNode::Atom(_)));
+    let ExprNode::Unary { span, .. } = parse_expr("-3!").unwrap() else { panic!("not a unary node") };
+    assert_eq!(span, 0..3);
+}
+
+#[test]
+fn test_evaluate() {
+    assert_eq!(evaluate("1 + 2 * 3").unwrap(), 7.0);
+    assert_eq!(evaluate("10 - 4 - 3").unwrap(), 3.0);
+    assert_eq!(evaluate("8 / 4 / 2").unwrap(), 1.0);
+    assert_eq!(evaluate("2 ^ 3 ^ 2").unwrap(), 512.0);
+    assert_eq!(evaluate("-2 ^ 2").unwrap(), -4.0);
+    assert_eq!(evaluate("(1 + 2)! * 2").unwrap(), 12.0);
+    assert!(matches!(evaluate("1 / (2 - 2)"), Err(Error::DivisionByZero { offset: 0 })));
+    assert!(matches!(evaluate("2 + (0 - 1)!"), Err(Error::InvalidFactorial { offset: 4 })));
+    // the space is skipped before the grammar gives up on `)`
+    assert!(matches!(evaluate("1 + 2 )"), Err(Error::TrailingInput { offset: 6 })));
+    assert!(matches!(evaluate("* 2"), Err(Error::SyntaxError(_))));
+}
+
+#[test]
+fn test_external_comment() {
+    assert_eq!(evaluate("1 /* a /* b */ c */ + /**/ 2").unwrap(), 3.0);
+    assert_eq!(prefix("/* lead */ 1 - /* -2 */ 3"), "(- 1 3)");
+    // the scanner counts the nesting, the first `*/` does not close the comment
+    assert!(matches!(evaluate("1 /* a /* b */ + 2"), Err(Error::TrailingInput { offset: 2 })));
+    assert!(evaluate("1 + /* a */ b */ 2").is_err());
+}
@@ -0,0 +1,422 @@
+use std::{
+    fmt::{Display, Formatter},
+    ops::Range,
+};
+
+/// A parsed `.ygg` file
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GrammarFile {
+    /// Name in the `grammar Name { ... }` header
+    pub name: Option<Identifier>,
+    /// `key: value` pairs of the header
+    pub properties: Vec<(Identifier, String)>,
+    /// `import` statements in declaration order
+    pub imports: Vec<Import>,
+    /// `macro` declarations in declaration order, their calls are expanded in the rule bodies
+    pub macros: Vec<Macro>,
+    /// Rules in declaration order, followed by the rules copied in by [`GrammarFile::link`]
+    pub rules: Vec<Rule>,
+}
+
+/// `import "json5.ygg" as json5`, rules of the imported file are referenced as `json5::Value`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Import {
+    /// Path of the imported file, relative to the importing one
+    pub path: String,
+    /// Prefix of the imported rules, the file stem when `as` is left out
+    pub namespace: Identifier,
+    /// The whole statement
+    pub span: Range<usize>,
+}
+
+/// `macro separated(item, separator: ',', trailing: false) { ... }`, called in rule bodies as `@separated(Value)`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Macro {
+    /// Name after `macro`
+    pub name: Identifier,
+    /// Parameters in declaration order
+    pub parameters: Vec<Parameter>,
+    /// Text of the `///` comments before the declaration, one line per comment
+    pub document: String,
+    /// Byte range of the body between the braces, which is read again for every call
+    pub body: Range<usize>,
+    /// The whole declaration
+    pub span: Range<usize>,
+}
+
+/// `item` or `trailing: false`, a parameter of a [`Macro`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Parameter {
+    /// Name used in the body
+    pub name: Identifier,
+    /// Used when a call leaves the parameter out
+    pub default: Option<Argument>,
+}
+
+/// What a macro parameter stands for in one call
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Argument {
+    /// An expression put in place of the parameter
+    Expression(Expression),
+    /// `true` or `false`, tested with `if name { ... }` in the body
+    Flag(bool),
+}
+
+/// A name and where it was written
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Identifier {
+    /// The name
+    pub text: String,
+    /// Byte range in the grammar file
+    pub span: Range<usize>,
+}
+
+/// Whether a rule is declared with `class`, `union` or `operators`
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum RuleKind {
+    /// A sequence producing a single node
+    Class,
+    /// Tagged alternatives, each branch is a variant of the node
+    Union,
+    /// An operator table over an operand, the node is a tree of unary and binary operations
+    Operators,
+    /// A terminal matched by a hand-written Rust function
+    External,
+}
+
+/// A `class` or `union` declaration
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Rule {
+    /// Name of the rule
+    pub name: Identifier,
+    /// `class`, `union`, `operators` or `external`
+    pub kind: RuleKind,
+    /// Marked `entry`
+    pub entry: bool,
+    /// Marked `atomic`, whitespace is not skipped inside
+    pub atomic: bool,
+    /// Marked `ignore`, matched between the tokens of other rules
+    pub ignored: bool,
+    /// Target of `class Name -> Target`
+    pub redirect: Option<Identifier>,
+    /// `@name(arguments)` lines before the declaration
+    pub annotations: Vec<Annotation>,
+    /// Text of the `///` comments before the declaration, one line per comment
+    pub document: String,
+    /// Right hand side of the rule, for `operators` the flat sequence of operands and operators it matches and for
+    /// `external` the path of the scanner
+    pub body: Expression,
+    /// The `@name(...)` macro calls written in the body, which holds their expansions
+    pub expansions: Vec<Range<usize>>,
+    /// The table of an `operators` rule
+    pub operators: Option<OperatorTable>,
+    /// The whole declaration, from the first annotation or modifier to the closing brace
+    pub span: Range<usize>,
+}
+
+/// `@style(field)`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Annotation {
+    /// Name after the `@`
+    pub name: Identifier,
+    /// Arguments as written, strings without their quotes
+    pub arguments: Vec<String>,
+    /// The whole annotation
+    pub span: Range<usize>,
+}
+
+/// The body of `operators Name { operand Atom ... }`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OperatorTable {
+    /// The rule matched between operators
+    pub operand: Identifier,
+    /// Precedence levels, loosest first
+    pub levels: Vec<OperatorLevel>,
+}
+
+/// `infix left '+' #Add | '-' #Sub`, one line of an operator table
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OperatorLevel {
+    /// 1 for the first line, levels declared later bind tighter
+    pub precedence: u32,
+    /// Where the operators stand and how they group
+    pub fixity: Fixity,
+    /// Operators of the level
+    pub operators: Vec<Operator>,
+    /// The whole line
+    pub span: Range<usize>,
+}
+
+/// A line break compared with the indentation of the enclosing block.
+///
+/// Blocks form a stack that starts with the unindented top level, blank lines are skipped.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Indentation {
+    /// Line breaks then a deeper indentation, which opens a block
+    Indent,
+    /// Line breaks then the indentation of the enclosing block
+    Samedent,
+    /// The end of the input or a line indented less than the enclosing block, which closes it, matches nothing
+    Dedent,
+}
+
+/// Position and grouping of the operators of an [`OperatorLevel`]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Fixity {
+    /// `-a`
+    Prefix,
+    /// `a!`
+    Postfix,
+    /// `a - b - c` is `(a - b) - c`
+    InfixLeft,
+    /// `a ^ b ^ c` is `a ^ (b ^ c)`
+    InfixRight,
+}
+
+/// `'+' #Add`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Operator {
+    /// The operator text
+    pub text: String,
+    /// Name of the operator, the variant of the generated operator enum
+    pub tag: Identifier,
+    /// Byte range in the grammar file
+    pub span: Range<usize>,
+}
+
+/// A node of a rule body
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Expression {
+    /// What the node matches
+    pub kind: ExpressionKind,
+    /// Byte range in the grammar file
+    pub span: Range<usize>,
+}
+
+/// The expression forms of the grammar language
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ExpressionKind {
+    /// `'text'` or `"text"`, quotes have no escapes
+    Text(String),
+    /// `/regex/`, without the slashes
+    Regex(String),
+    /// `[a-z]`, with the brackets
+    CharacterClass(String),
+    /// `ANY`, a single character
+    Any,
+    /// `INDENT`, `SAMEDENT` and `DEDENT`, the blocks of an indentation-sensitive grammar
+    Indentation(Indentation),
+    /// Another rule by name, `namespace::Name` for a rule of an imported file
+    Reference(String),
+    /// `a b c`
+    Sequence(Vec<Expression>),
+    /// `a | b | c`, the first matching branch wins
+    Choice(Vec<Expression>),
+    /// `a #Tag`, names a branch
+    Tagged {
+        /// The tag
+        tag: Identifier,
+        /// The tagged branch
+        body: Box<Expression>,
+    },
+    /// `name:a`, names a captured node
+    Capture {
+        /// The capture name
+        name: Identifier,
+        /// The captured expression
+        body: Box<Expression>,
+    },
+    /// `a?`, `a*` and `a+`
+    Repeat {
+        /// The repeated expression
+        body: Box<Expression>,
+        /// Minimum count
+        min: u32,
+        /// Maximum count, `None` for unbounded
+        max: Option<u32>,
+    },
+    /// `path::to::scanner`, the body of an `external` rule
+    External(String),
+    /// `&a` and `!a`, whether `a` matches here, without consuming input or making nodes
+    Lookahead {
+        /// `&` succeeds when the body matches, `!` when it does not
+        positive: bool,
+        /// The checked expression
+        body: Box<Expression>,
+    },
+}
+
+impl GrammarFile {
+    /// Find a rule by name
+    pub fn rule(&self, name: &str) -> Option<&Rule> {
+        self.rules.iter().find(|rule| rule.name.text == name)
+    }
+    /// Find a macro by name
+    pub fn macro_named(&self, name: &str) -> Option<&Macro> {
+        self.macros.iter().find(|m| m.name.text == name)
+    }
+    /// The `import` statement of a namespace
+    pub fn import(&self, namespace: &str) -> Option<&Import> {
+        self.imports.iter().find(|import| import.namespace.text == namespace)
+    }
+    /// Value of a header property
+    pub fn property(&self, key: &str) -> Option<&str> {
+        self.properties.iter().find(|(k, _)| k.text == key).map(|(_, v)| v.as_str())
+    }
+}
+
+impl Parameter {
+    /// Declared with `true` or `false` as default, the parameter is tested with `if` instead of being matched
+    pub fn is_flag(&self) -> bool {
+        matches!(self.default, Some(Argument::Flag(_)))
+    }
+}
+
+impl Rule {
+    /// Copied from an imported file by [`GrammarFile::link`], the name is `namespace::Name`
+    pub fn is_imported(&self) -> bool {
+        self.name.text.contains("::")
+    }
+    /// Arguments of every `@name(...)` annotation of the rule, in order, `@derive(PartialEq, Eq)` gives both names
+    pub fn annotation_arguments(&self, name: &str) -> Vec<&str> {
+        self.annotations
+            .iter()
+            .filter(|annotation| annotation.name.text == name)
+            .flat_map(|annotation| annotation.arguments.iter().map(String::as_str))
+            .collect()
+    }
+    /// Tags of the branches, in order, for `union` rules and tagged choices
+    pub fn branch_tags(&self) -> Vec<&Identifier> {
+        let mut out = vec![];
+        self.body.visit(&mut |e| {
+            if let ExpressionKind::Tagged { tag, .. } = &e.kind {
+                out.push(tag)
+            }
+        });
+        out
+    }
+    /// Names of the rules referenced by the body, in order of appearance and without duplicates
+    pub fn references(&self) -> Vec<&str> {
+        let mut out: Vec<&str> = vec![];
+        self.body.visit(&mut |e| {
+            if let ExpressionKind::Reference(name) = &e.kind {
+                if !out.contains(&name.as_str()) {
+                    out.push(name)
+                }
+            }
+        });
+        out
+    }
+}
+
+impl OperatorTable {
+    /// The operator tagged `tag`, by the `#Tag` name or its snake case
+    pub fn operator(&self, tag: &str) -> Option<(&OperatorLevel, &Operator)> {
+        self.levels.iter().find_map(|level| {
+            let operator = level.operators.iter().find(|o| o.tag.text == tag || crate::snake_case(&o.tag.text) == tag)?;
+            Some((level, operator))
+        })
+    }
+}
+
+impl OperatorLevel {
+    /// Left and right binding power for Pratt parsing, the operand of a prefix operator is parsed with the right one
+    pub fn binding_power(&self) -> (u32, u32) {
+        let base = self.precedence * 2;
+        match self.fixity {
+            Fixity::InfixRight => (base + 1, base),
+            Fixity::InfixLeft => (base, base + 1),
+            Fixity::Prefix | Fixity::Postfix => (base, base),
+        }
+    }
+}
+
+impl Expression {
+    /// Call `f` on this expression and every expression below it, parents first
+    pub fn visit<'a>(&'a self, f: &mut impl FnMut(&'a Expression)) {
+        f(self);
+        match &self.kind {
+            ExpressionKind::Sequence(items) | ExpressionKind::Choice(items) => items.iter().for_each(|e| e.visit(f)),
+            ExpressionKind::Tagged { body, .. }
+            | ExpressionKind::Capture { body, .. }
+            | ExpressionKind::Repeat { body, .. }
+            | ExpressionKind::Lookahead { body, .. } => body.visit(f),
+            _ => {}
+        }
+    }
+}
+
+/// The expression in grammar syntax, with parentheses only where they are needed
+impl Display for Expression {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match &self.kind {
+            ExpressionKind::Text(text) if text.contains('\'') => write!(f, "\"{text}\""),
+            ExpressionKind::Text(text) => write!(f, "'{text}'"),
+            ExpressionKind::Regex(pattern) => write!(f, "/{pattern}/"),
+            ExpressionKind::CharacterClass(class) => f.write_str(class),
+            ExpressionKind::Any => f.write_str("ANY"),
+            ExpressionKind::Indentation(indentation) => write!(f, "{indentation}"),
+            ExpressionKind::Reference(name) | ExpressionKind::External(name) => f.write_str(name),
+            ExpressionKind::Sequence(items) => {
+                for (index, item) in items.iter().enumerate() {
+                    if index > 0 {
+                        f.write_str(" ")?;
+                    }
+                    match item.kind {
+                        ExpressionKind::Choice(_) | ExpressionKind::Tagged { .. } => write!(f, "({item})")?,
+                        _ => write!(f, "{item}")?,
+                    }
+                }
+                Ok(())
+            }
+            ExpressionKind::Choice(items) => {
+                for (index, item) in items.iter().enumerate() {
+                    if index > 0 {
+                        f.write_str(" | ")?;
+                    }
+                    write!(f, "{item}")?;
+                }
+                Ok(())
+            }
+            ExpressionKind::Tagged { tag, body } => write!(f, "{body} #{}", tag.text),
+            ExpressionKind::Capture { name, body } => write!(f, "{}:{}", name.text, Primary(body)),
+            ExpressionKind::Repeat { body, min, max } => {
+                let suffix = match (min, max) {
+                    (0, Some(1)) => "?",
+                    (0, _) => "*",
+                    _ => "+",
+                };
+                write!(f, "{}{suffix}", Primary(body))
+            }
+            ExpressionKind::Lookahead { positive, body } => {
+                write!(f, "{}{}", if *positive { "&" } else { "!" }, Primary(body))
+            }
+        }
+    }
+}
+
+impl Display for Indentation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Indentation::Indent => "INDENT",
+            Indentation::Samedent => "SAMEDENT",
+            Indentation::Dedent => "DEDENT",
+        })
+    }
+}
+
+/// An expression in parentheses unless it is a single item
+struct Primary<'a>(&'a Expression);
+
+impl Display for Primary<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.0.kind {
+            ExpressionKind::Sequence(_)
+            | ExpressionKind::Choice(_)
+            | ExpressionKind::Tagged { .. }
+            | ExpressionKind::Capture { .. }
+            | ExpressionKind::Lookahead { .. } => write!(f, "({})", self.0),
+            _ => write!(f, "{}", self.0),
+        }
+    }
+}
@@ -0,0 +1,90 @@
+use std::{
+    fmt::{Display, Formatter},
+    ops::Range,
+};
+
+/// A problem in a grammar file, located by byte range
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GrammarError {
+    /// What is wrong
+    pub message: String,
+    /// Byte range in the grammar file
+    pub span: Range<usize>,
+}
+
+/// Result type of the grammar readers
+pub type Result<T> = std::result::Result<T, GrammarError>;
+
+/// Why [`Interpreter::parse`](crate::Interpreter::parse) failed
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ParseError {
+    /// The grammar has no rule of that name
+    UnknownRule {
+        /// The requested rule
+        rule: String,
+    },
+    /// The input does not match the rule
+    Mismatch {
+        /// The requested rule
+        rule: String,
+        /// Furthest byte offset any branch reached
+        offset: usize,
+        /// Literals and regexes tried at that offset
+        expected: Vec<String>,
+    },
+    /// Rules are nested deeper than the interpreter allows
+    TooDeep {
+        /// Where the limit was hit
+        offset: usize,
+        /// The limit
+        depth: usize,
+    },
+}
+
+impl GrammarError {
+    /// Create an error at `span`
+    pub fn new(message: impl Into<String>, span: Range<usize>) -> Self {
+        Self { message: message.into(), span }
+    }
+    /// 1-based line and column, in characters, of the start of the error in `text`
+    pub fn line_column(&self, text: &str) -> (usize, usize) {
+        line_column(text, self.span.start)
+    }
+}
+
+/// 1-based line and column, in characters, of the byte `offset` in `text`
+pub fn line_column(text: &str, offset: usize) -> (usize, usize) {
+    let mut offset = offset.min(text.len());
+    while !text.is_char_boundary(offset) {
+        offset -= 1;
+    }
+    let before = &text[..offset];
+    let line = before.matches('\n').count() + 1;
+    let column = before.rsplit('\n').next().unwrap_or_default().chars().count() + 1;
+    (line, column)
+}
+
+impl Display for GrammarError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} at {:?}", self.message, self.span)
+    }
+}
+
+impl std::error::Error for GrammarError {}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnknownRule { rule } => write!(f, "rule `{rule}` is not defined"),
+            Self::Mismatch { rule, offset, expected } if expected.is_empty() => {
+                write!(f, "input does not match `{rule}` at {offset}")
+            }
+            Self::Mismatch { rule, offset, expected } => {
+                write!(f, "input does not match `{rule}` at {offset}, expected {}", expected.join(" or "))
+            }
+            Self::TooDeep { offset, depth } => write!(f, "rules nested deeper than {depth} at {offset}"),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
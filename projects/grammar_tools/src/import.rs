@@ -0,0 +1,106 @@
+use crate::{Expression, ExpressionKind, GrammarError, GrammarFile, Result, Rule, RuleKind};
+use std::ops::Range;
+
+impl GrammarFile {
+    /// Copy in the rules of `imported` that the `namespace::Name` references of this grammar reach.
+    ///
+    /// The copies are named `namespace::Name`, along with every reference inside them, and lose their `entry` and
+    /// `ignore` marks, imported rules skip the whitespace of this grammar. Their spans are the `import` statement, so
+    /// diagnostics point into this file. `external` rules cannot be imported, their scanners take the state of the other
+    /// parser.
+    pub fn link(&mut self, namespace: &str, imported: &GrammarFile) -> Result<()> {
+        let prefix = format!("{namespace}::");
+        let (path, span) = match self.import(namespace) {
+            Some(import) => (import.path.clone(), import.span.clone()),
+            None => {
+                let span = self.qualified_references(&prefix).first().map_or(0..0, |(_, span)| span.clone());
+                return Err(GrammarError::new(format!("namespace `{namespace}` is not imported"), span));
+            }
+        };
+        let mut pending = self.qualified_references(&prefix);
+        let mut copies: Vec<(usize, Rule)> = vec![];
+        while let Some((name, reference)) = pending.pop() {
+            if self.rule(&name).is_some() || copies.iter().any(|(_, copy)| copy.name.text == name) {
+                continue;
+            }
+            let local = &name[prefix.len()..];
+            let Some(index) = imported.rules.iter().position(|rule| rule.name.text == local)
+            else {
+                return Err(GrammarError::new(format!("rule `{local}` is not defined in `{path}`"), reference));
+            };
+            if imported.rules[index].kind == RuleKind::External {
+                let message = format!("rule `{name}` is an external scanner, which cannot be imported");
+                return Err(GrammarError::new(message, reference));
+            }
+            let copy = copy_rule(&imported.rules[index], &prefix, &span);
+            pending.extend(copy.references().into_iter().map(|name| (name.to_string(), reference.clone())));
+            copies.push((index, copy));
+        }
+        // declaration order of the imported file
+        copies.sort_by_key(|(index, _)| *index);
+        self.rules.extend(copies.into_iter().map(|(_, copy)| copy));
+        Ok(())
+    }
+    /// References starting with `prefix`, with where they are written
+    fn qualified_references(&self, prefix: &str) -> Vec<(String, Range<usize>)> {
+        let mut out = vec![];
+        for rule in &self.rules {
+            rule.body.visit(&mut |e| {
+                if let ExpressionKind::Reference(name) = &e.kind {
+                    if name.starts_with(prefix) {
+                        out.push((name.clone(), e.span.clone()))
+                    }
+                }
+            });
+        }
+        out.reverse();
+        out
+    }
+}
+
+fn copy_rule(rule: &Rule, prefix: &str, span: &Range<usize>) -> Rule {
+    let mut copy = rule.clone();
+    copy.name.text = format!("{prefix}{}", rule.name.text);
+    copy.entry = false;
+    copy.ignored = false;
+    copy.span = span.clone();
+    copy.name.span = span.clone();
+    copy.expansions.iter_mut().for_each(|call| *call = span.clone());
+    for annotation in &mut copy.annotations {
+        annotation.span = span.clone();
+        annotation.name.span = span.clone();
+    }
+    if let Some(redirect) = &mut copy.redirect {
+        redirect.text = format!("{prefix}{}", redirect.text);
+        redirect.span = span.clone();
+    }
+    if let Some(table) = &mut copy.operators {
+        table.operand.text = format!("{prefix}{}", table.operand.text);
+        table.operand.span = span.clone();
+        for level in &mut table.levels {
+            level.span = span.clone();
+            for operator in &mut level.operators {
+                operator.span = span.clone();
+                operator.tag.span = span.clone();
+            }
+        }
+    }
+    copy_expression(&mut copy.body, prefix, span);
+    copy
+}
+
+fn copy_expression(expression: &mut Expression, prefix: &str, span: &Range<usize>) {
+    expression.span = span.clone();
+    match &mut expression.kind {
+        ExpressionKind::Reference(name) => *name = format!("{prefix}{name}"),
+        ExpressionKind::Sequence(items) | ExpressionKind::Choice(items) => {
+            items.iter_mut().for_each(|item| copy_expression(item, prefix, span))
+        }
+        ExpressionKind::Tagged { tag: name, body } | ExpressionKind::Capture { name, body } => {
+            name.span = span.clone();
+            copy_expression(body, prefix, span)
+        }
+        ExpressionKind::Repeat { body, .. } | ExpressionKind::Lookahead { body, .. } => copy_expression(body, prefix, span),
+        _ => {}
+    }
+}
@@ -0,0 +1,531 @@
+use crate::{Expression, ExpressionKind, GrammarError, GrammarFile, Indentation, ParseError, Rule, RuleKind, imported_name};
+use regex::Regex;
+use std::{collections::BTreeMap, ops::Range};
+
+/// Name of the nodes made by literal text and `ANY` inside a larger expression
+pub const IGNORE_TEXT: &str = "IgnoreText";
+/// Name of the nodes made by regexes and character classes inside a larger expression
+pub const IGNORE_REGEX: &str = "IgnoreRegex";
+
+/// A hand-written matcher of an `external` rule, the length of the match at the start of the input
+pub type Scanner = fn(&str) -> Option<usize>;
+
+/// Rule nesting allowed by [`Interpreter::new`], deeper input is an error instead of a stack overflow
+pub const DEFAULT_MAX_DEPTH: usize = 256;
+
+/// Runs a grammar against input without generating code.
+///
+/// The tree has the shape of the generated `parse_cst`:
+///
+/// - every rule call is a node, literals and regexes inside a larger expression are `IgnoreText` and `IgnoreRegex`
+///   nodes, a `class` made of a single literal or regex has no child
+/// - references are tagged with the snake case name of the rule, captures with their name, and branches of a `union`
+///   that are not a single reference with `<rule>_<index>`
+/// - ignored rules are matched between the items of a sequence, except inside `atomic` rules
+/// - `&e` and `!e` make no nodes, whatever `e` matched is dropped
+/// - `external` rules match with the [`Scanner`] given to [`Interpreter::with_scanner`] and have no child, without one
+///   they never match
+/// - `INDENT`, `SAMEDENT` and `DEDENT` make no nodes, the blocks they open and close are undone when matching backtracks
+/// - the input is neither trimmed nor required to be consumed to the end
+///
+/// Left-recursive rules recurse until [`ParseError::TooDeep`] like the generated parser, unless
+/// [`Interpreter::with_left_recursion`] turns on seed growing.
+#[derive(Debug)]
+pub struct Interpreter {
+    rules: Vec<Compiled>,
+    names: BTreeMap<String, usize>,
+    ignored: Vec<usize>,
+    max_depth: usize,
+    /// Leaders of left-recursive cycles, see [`LeftRecursion::leader`](crate::LeftRecursion::leader)
+    leaders: Vec<usize>,
+    left_recursion: bool,
+    scanners: BTreeMap<String, Scanner>,
+}
+
+/// A node of the concrete syntax tree
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CstNode {
+    /// Rule name, or [`IGNORE_TEXT`] and [`IGNORE_REGEX`] for literals
+    pub rule: String,
+    /// Tag given by the parent
+    pub tag: Option<String>,
+    /// Byte range in the input
+    pub span: Range<usize>,
+    /// Child nodes, in input order
+    pub children: Vec<CstNode>,
+}
+
+#[derive(Debug)]
+struct Compiled {
+    name: String,
+    body: Op,
+    /// The body is a single terminal matched without a node of its own
+    bare: bool,
+}
+
+/// The longest match found so far of a rule at a position, `None` while the first attempt runs
+type Seed = Option<(usize, CstNode)>;
+
+#[derive(Debug)]
+enum Op {
+    Text(String),
+    Regex { regex: Regex, source: String },
+    Any,
+    Indentation(Indentation),
+    Scan(String),
+    Call(usize),
+    Sequence { items: Vec<Op>, skip: bool },
+    Choice(Vec<Op>),
+    Repeat { body: Box<Op>, min: u32, max: Option<u32> },
+    Tag { body: Box<Op>, tag: String },
+    Lookahead { body: Box<Op>, positive: bool, source: String },
+}
+
+struct Run<'i> {
+    input: &'i str,
+    depth: usize,
+    furthest: usize,
+    expected: Vec<String>,
+    too_deep: bool,
+    seeds: BTreeMap<(usize, usize), Seed>,
+    /// Every block opened so far with the one around it, a list so that backtracking only restores `block`
+    blocks: Vec<(Option<usize>, &'i str)>,
+    /// The innermost open block, `None` at the top level
+    block: Option<usize>,
+}
+
+impl CstNode {
+    /// The text of the node
+    pub fn text<'i>(&self, input: &'i str) -> &'i str {
+        &input[self.span.clone()]
+    }
+    /// Call `f` on this node and every node below it, parents first
+    pub fn visit<'a>(&'a self, f: &mut impl FnMut(&'a CstNode)) {
+        f(self);
+        self.children.iter().for_each(|child| child.visit(f));
+    }
+}
+
+impl Interpreter {
+    /// Prepare a grammar, fails on references to undefined rules and invalid regexes.
+    pub fn new(grammar: &GrammarFile) -> Result<Self, GrammarError> {
+        let names: BTreeMap<String, usize> =
+            grammar.rules.iter().enumerate().map(|(index, rule)| (rule.name.text.clone(), index)).collect();
+        let mut rules = Vec::with_capacity(grammar.rules.len());
+        for rule in &grammar.rules {
+            let compiler = Compiler { names: &names, skip: !rule.atomic, rule: &rule.name.text };
+            let bare = matches!(rule.kind, RuleKind::Class | RuleKind::External)
+                && matches!(
+                    rule.body.kind,
+                    ExpressionKind::Text(_)
+                        | ExpressionKind::Regex(_)
+                        | ExpressionKind::CharacterClass(_)
+                        | ExpressionKind::Any
+                        | ExpressionKind::External(_)
+                );
+            let body = match rule.kind {
+                RuleKind::Union => compiler.union(&rule.body)?,
+                RuleKind::Class | RuleKind::Operators | RuleKind::External => compiler.expression(&rule.body)?,
+            };
+            rules.push(Compiled { name: rule.name.text.clone(), body, bare });
+        }
+        let ignored = grammar.rules.iter().enumerate().filter(|(_, rule)| rule.ignored).map(|(index, _)| index).collect();
+        let mut leaders: Vec<usize> = grammar.left_recursion().iter().map(|cycle| names[cycle.leader()]).collect();
+        leaders.dedup();
+        Ok(Self {
+            rules,
+            names,
+            ignored,
+            max_depth: DEFAULT_MAX_DEPTH,
+            leaders,
+            left_recursion: false,
+            scanners: BTreeMap::new(),
+        })
+    }
+    /// Allow deeper nesting of rules
+    pub fn with_max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = depth;
+        self
+    }
+    /// Grow left-recursive rules from a seed instead of recursing forever.
+    ///
+    /// The first rule of each cycle is matched once with its recursive call failing, then again with the call returning
+    /// the previous match, for as long as the match gets longer. `Sum { Sum '+' Product | Product }` then parses
+    /// `1 + 2 + 3` as `(1 + 2) + 3`.
+    ///
+    /// Generated parsers do not grow seeds, the build helper rejects left-recursive grammars
+    pub fn with_left_recursion(mut self, enabled: bool) -> Self {
+        self.left_recursion = enabled;
+        self
+    }
+    /// Match the `external` rule `rule` with `scanner`
+    pub fn with_scanner(mut self, rule: &str, scanner: Scanner) -> Self {
+        self.scanners.insert(rule.to_string(), scanner);
+        self
+    }
+    /// Names of the rules, in declaration order
+    pub fn rule_names(&self) -> impl Iterator<Item = &str> {
+        self.rules.iter().map(|rule| rule.name.as_str())
+    }
+    /// Match `rule` at the start of `input`.
+    pub fn parse(&self, input: &str, rule: &str) -> Result<CstNode, ParseError> {
+        let index = *self.names.get(rule).ok_or_else(|| ParseError::UnknownRule { rule: rule.to_string() })?;
+        let mut run = Run::new(input);
+        let mut nodes = vec![];
+        match self.call(&mut run, index, 0, &mut nodes) {
+            Some(_) => Ok(nodes.remove(0)),
+            None if run.too_deep => Err(ParseError::TooDeep { offset: run.furthest, depth: self.max_depth }),
+            None => Err(ParseError::Mismatch { rule: rule.to_string(), offset: run.furthest, expected: run.expected }),
+        }
+    }
+    /// Length of the match of `expression`, written inside `rule`, at the start of `input`
+    pub(crate) fn match_expression(&self, rule: &Rule, expression: &Expression, input: &str) -> Option<usize> {
+        let compiler = Compiler { names: &self.names, skip: !rule.atomic, rule: &rule.name.text };
+        let op = compiler.expression(expression).ok()?;
+        let mut run = Run::new(input);
+        self.run(&mut run, &op, 0, &mut vec![])
+    }
+
+    fn call(&self, run: &mut Run, index: usize, position: usize, out: &mut Vec<CstNode>) -> Option<usize> {
+        match self.left_recursion && self.leaders.contains(&index) {
+            true => self.grow(run, index, position, out),
+            false => self.call_rule(run, index, position, out),
+        }
+    }
+
+    fn grow(&self, run: &mut Run, index: usize, position: usize, out: &mut Vec<CstNode>) -> Option<usize> {
+        let key = (index, position);
+        if let Some(seed) = run.seeds.get(&key) {
+            let (end, node) = seed.clone()?;
+            out.push(node);
+            return Some(end);
+        }
+        run.seeds.insert(key, None);
+        loop {
+            let mut nodes = vec![];
+            let end = self.call_rule(run, index, position, &mut nodes);
+            match (end, &run.seeds[&key]) {
+                (Some(end), Some((last, _))) if end <= *last => break,
+                (Some(end), _) => {
+                    let node = nodes.pop().expect("a matched rule makes a node");
+                    run.seeds.insert(key, Some((end, node)));
+                }
+                (None, _) => break,
+            }
+        }
+        let (end, node) = run.seeds.remove(&key).flatten()?;
+        out.push(node);
+        Some(end)
+    }
+
+    fn call_rule(&self, run: &mut Run, index: usize, position: usize, out: &mut Vec<CstNode>) -> Option<usize> {
+        // once too deep, every other branch would hit the limit again, exponentially often for left recursion
+        if run.too_deep {
+            return None;
+        }
+        if run.depth >= self.max_depth {
+            run.too_deep = true;
+            run.fail(position, "less nesting".to_string());
+            return None;
+        }
+        let rule = &self.rules[index];
+        let mut children = vec![];
+        run.depth += 1;
+        let end = match rule.bare {
+            true => self.terminal(run, &rule.body, position),
+            false => self.run(run, &rule.body, position, &mut children),
+        };
+        run.depth -= 1;
+        let end = end?;
+        out.push(CstNode { rule: rule.name.clone(), tag: None, span: position..end, children });
+        Some(end)
+    }
+
+    fn run(&self, run: &mut Run, op: &Op, position: usize, out: &mut Vec<CstNode>) -> Option<usize> {
+        let mark = out.len();
+        let block = run.block;
+        let result = self.run_inner(run, op, position, out);
+        if result.is_none() {
+            out.truncate(mark);
+            run.block = block;
+        }
+        result
+    }
+
+    fn run_inner(&self, run: &mut Run, op: &Op, position: usize, out: &mut Vec<CstNode>) -> Option<usize> {
+        match op {
+            Op::Text(_) | Op::Any | Op::Scan(_) => {
+                let end = self.terminal(run, op, position)?;
+                out.push(CstNode { rule: IGNORE_TEXT.to_string(), tag: None, span: position..end, children: vec![] });
+                Some(end)
+            }
+            Op::Regex { .. } => {
+                let end = self.terminal(run, op, position)?;
+                out.push(CstNode { rule: IGNORE_REGEX.to_string(), tag: None, span: position..end, children: vec![] });
+                Some(end)
+            }
+            Op::Indentation(indentation) => self.indentation(run, *indentation, position),
+            Op::Call(index) => self.call(run, *index, position, out),
+            Op::Sequence { items, skip } => {
+                let mut position = position;
+                for (index, item) in items.iter().enumerate() {
+                    if *skip && index > 0 {
+                        position = self.skip_ignored(run, position, out);
+                    }
+                    position = self.run(run, item, position, out)?;
+                }
+                Some(position)
+            }
+            Op::Choice(branches) => branches.iter().find_map(|branch| self.run(run, branch, position, out)),
+            Op::Repeat { body, min, max } => {
+                let mut position = position;
+                let mut count = 0;
+                while max.is_none_or(|max| count < max) {
+                    match self.run(run, body, position, out) {
+                        Some(end) if end == position && count >= *min => break,
+                        Some(end) => position = end,
+                        None => break,
+                    }
+                    count += 1;
+                }
+                (count >= *min).then_some(position)
+            }
+            Op::Tag { body, tag } => {
+                let mark = out.len();
+                let end = self.run(run, body, position, out)?;
+                if out.len() > mark {
+                    if let Some(last) = out.last_mut() {
+                        last.tag = Some(tag.clone());
+                    }
+                }
+                Some(end)
+            }
+            Op::Lookahead { body, positive, source } => self.lookahead(run, body, *positive, source, position),
+        }
+    }
+
+    /// Whether `body` matches at `position`, nothing it matched or opened is kept
+    fn lookahead(&self, run: &mut Run, body: &Op, positive: bool, source: &str, position: usize) -> Option<usize> {
+        // what the body expected is only worth reporting when it had to match
+        let saved = (run.furthest, run.expected.clone());
+        let block = run.block;
+        let matched = self.run(run, body, position, &mut vec![]).is_some();
+        run.block = block;
+        if !positive {
+            (run.furthest, run.expected) = saved;
+        }
+        if matched != positive {
+            if !positive {
+                run.fail(position, source.to_string());
+            }
+            return None;
+        }
+        Some(position)
+    }
+
+    /// Match a literal, regex or `ANY` without making a node
+    fn terminal(&self, run: &mut Run, op: &Op, position: usize) -> Option<usize> {
+        let rest = &run.input[position..];
+        let matched = match op {
+            Op::Text(text) => rest.starts_with(text.as_str()).then_some(text.len()),
+            Op::Regex { regex, .. } => regex.find(rest).map(|m| m.end()),
+            Op::Any => rest.chars().next().map(char::len_utf8),
+            // a scanner cannot end inside a character or past the input
+            Op::Scan(rule) => self.scanners.get(rule).and_then(|scan| scan(rest)).filter(|end| rest.is_char_boundary(*end)),
+            _ => unreachable!("only terminals are bare"),
+        };
+        if matched.is_none() {
+            let expected = match op {
+                Op::Text(text) => format!("{text:?}"),
+                Op::Regex { source, .. } => source.clone(),
+                Op::Scan(rule) => format!("`{rule}`"),
+                _ => "ANY".to_string(),
+            };
+            run.fail(position, expected);
+        }
+        matched.map(|length| position + length)
+    }
+
+    /// Match `INDENT`, `SAMEDENT` or `DEDENT` against the innermost block, or the unindented top level
+    fn indentation(&self, run: &mut Run, indentation: Indentation, position: usize) -> Option<usize> {
+        let block = run.block.map_or("", |index| run.blocks[index].1);
+        let input = run.input;
+        let rest = &input[position..];
+        let breaks = line_breaks(rest);
+        let next = breaks.map(|length| {
+            let line = &rest[length..];
+            &line[..line.len() - line.trim_start_matches([' ', '\t']).len()]
+        });
+        let end = match (indentation, breaks, next, run.block) {
+            (Indentation::Indent, Some(length), Some(indent), _) if indent.len() > block.len() && indent.starts_with(block) => {
+                run.blocks.push((run.block, indent));
+                run.block = Some(run.blocks.len() - 1);
+                Some(position + length + indent.len())
+            }
+            (Indentation::Samedent, Some(length), Some(indent), _) if indent == block => Some(position + length + indent.len()),
+            // the top level never closes
+            (Indentation::Dedent, _, next, Some(index)) if !next.is_some_and(|indent| indent.starts_with(block)) => {
+                run.block = run.blocks[index].0;
+                Some(position)
+            }
+            _ => None,
+        };
+        if end.is_none() {
+            run.fail(position, indentation.to_string());
+        }
+        end
+    }
+
+    fn skip_ignored(&self, run: &mut Run, mut position: usize, out: &mut Vec<CstNode>) -> usize {
+        // a missing whitespace is not what the user should be told about
+        let saved = (run.furthest, run.expected.clone());
+        'outer: loop {
+            for index in &self.ignored {
+                match self.call(run, *index, position, out) {
+                    Some(end) if end > position => {
+                        position = end;
+                        continue 'outer;
+                    }
+                    Some(_) => {
+                        out.pop();
+                    }
+                    None => {}
+                }
+            }
+            (run.furthest, run.expected) = saved;
+            return position;
+        }
+    }
+}
+
+impl<'i> Run<'i> {
+    fn new(input: &'i str) -> Self {
+        Self {
+            input,
+            depth: 0,
+            furthest: 0,
+            expected: vec![],
+            too_deep: false,
+            seeds: BTreeMap::new(),
+            blocks: vec![],
+            block: None,
+        }
+    }
+    fn fail(&mut self, position: usize, expected: String) {
+        if position > self.furthest {
+            self.furthest = position;
+            self.expected.clear();
+        }
+        if position == self.furthest && !self.expected.contains(&expected) {
+            self.expected.push(expected);
+        }
+    }
+}
+
+/// Length of the line ends at the start of `text`, with the blank lines after them, `None` when there is none
+fn line_breaks(text: &str) -> Option<usize> {
+    let mut end = None;
+    let mut rest = text;
+    loop {
+        let line = rest.trim_start_matches([' ', '\t']);
+        let Some(after) = line.strip_prefix("\r\n").or_else(|| line.strip_prefix('\n'))
+        else {
+            return end;
+        };
+        end = Some(text.len() - after.len());
+        rest = after;
+    }
+}
+
+struct Compiler<'g> {
+    names: &'g BTreeMap<String, usize>,
+    skip: bool,
+    rule: &'g str,
+}
+
+impl Compiler<'_> {
+    fn union(&self, body: &Expression) -> Result<Op, GrammarError> {
+        let branches = match &body.kind {
+            ExpressionKind::Choice(branches) => branches.as_slice(),
+            _ => std::slice::from_ref(body),
+        };
+        let mut out = Vec::with_capacity(branches.len());
+        for (index, branch) in branches.iter().enumerate() {
+            let inner = match &branch.kind {
+                ExpressionKind::Tagged { body, .. } => body,
+                _ => branch,
+            };
+            let op = self.expression(inner)?;
+            out.push(match inner.kind {
+                ExpressionKind::Reference(_) => op,
+                _ => Op::Tag { body: Box::new(op), tag: format!("{}_{index}", snake_case(&imported_name(self.rule))) },
+            });
+        }
+        Ok(Op::Choice(out))
+    }
+
+    fn expression(&self, expression: &Expression) -> Result<Op, GrammarError> {
+        let op = match &expression.kind {
+            ExpressionKind::Text(text) => Op::Text(text.clone()),
+            ExpressionKind::Regex(source) => self.regex(source, format!("/{source}/"), expression)?,
+            ExpressionKind::CharacterClass(class) => self.regex(class, class.clone(), expression)?,
+            ExpressionKind::Any => Op::Any,
+            ExpressionKind::Indentation(indentation) => Op::Indentation(*indentation),
+            ExpressionKind::External(_) => Op::Scan(self.rule.to_string()),
+            ExpressionKind::Reference(name) => {
+                let tag = snake_case(&imported_name(name));
+                Op::Tag { body: Box::new(Op::Call(self.reference(name, expression)?)), tag }
+            }
+            ExpressionKind::Sequence(items) => {
+                Op::Sequence { items: items.iter().map(|e| self.expression(e)).collect::<Result<_, _>>()?, skip: self.skip }
+            }
+            ExpressionKind::Choice(items) => Op::Choice(items.iter().map(|e| self.expression(e)).collect::<Result<_, _>>()?),
+            ExpressionKind::Tagged { body, .. } => self.expression(body)?,
+            ExpressionKind::Capture { name, body } => {
+                let body = match &body.kind {
+                    ExpressionKind::Reference(reference) => Op::Call(self.reference(reference, body)?),
+                    _ => self.expression(body)?,
+                };
+                Op::Tag { body: Box::new(body), tag: name.text.clone() }
+            }
+            ExpressionKind::Repeat { body, min, max } => {
+                Op::Repeat { body: Box::new(self.expression(body)?), min: *min, max: *max }
+            }
+            ExpressionKind::Lookahead { positive, body } => {
+                Op::Lookahead { body: Box::new(self.expression(body)?), positive: *positive, source: expression.to_string() }
+            }
+        };
+        Ok(op)
+    }
+
+    fn reference(&self, name: &str, expression: &Expression) -> Result<usize, GrammarError> {
+        match self.names.get(name) {
+            Some(index) => Ok(*index),
+            None => Err(GrammarError::new(format!("rule `{name}` is not defined"), expression.span.clone())),
+        }
+    }
+
+    fn regex(&self, pattern: &str, source: String, expression: &Expression) -> Result<Op, GrammarError> {
+        match Regex::new(&format!("^({pattern})")) {
+            Ok(regex) => Ok(Op::Regex { regex, source }),
+            Err(e) => Err(GrammarError::new(format!("invalid regex: {e}"), expression.span.clone())),
+        }
+    }
+}
+
+/// `ObjectPair` to `object_pair`, the tag the generator gives to references
+pub fn snake_case(name: &str) -> String {
+    let chars: Vec<char> = name.chars().collect();
+    let mut out = String::with_capacity(name.len() + 4);
+    for (index, c) in chars.iter().enumerate() {
+        if c.is_uppercase() && index > 0 {
+            let previous = chars[index - 1];
+            let next_lower = chars.get(index + 1).is_some_and(|n| n.is_lowercase());
+            if previous.is_lowercase() || previous.is_ascii_digit() || (previous.is_uppercase() && next_lower) {
+                out.push('_');
+            }
+        }
+        out.extend(c.to_lowercase());
+    }
+    out
+}
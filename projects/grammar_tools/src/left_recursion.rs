@@ -0,0 +1,112 @@
+use crate::{
+    Expression, ExpressionKind, GrammarFile,
+    lint::{is_nullable, nullable_rules},
+};
+use std::{
+    collections::BTreeSet,
+    fmt::{Display, Formatter},
+    iter::once,
+    ops::Range,
+};
+
+/// A cycle of rules that call each other without consuming input, found by [`GrammarFile::left_recursion`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LeftRecursion {
+    /// The rules of the cycle, starting and ending with the rule declared first
+    pub chain: Vec<String>,
+    /// The reference in the first rule that starts the cycle
+    pub span: Range<usize>,
+}
+
+impl Display for LeftRecursion {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.chain.join(" -> "))
+    }
+}
+
+impl LeftRecursion {
+    /// The rule whose calls grow the seed, the first of the chain
+    pub fn leader(&self) -> &str {
+        &self.chain[0]
+    }
+}
+
+impl GrammarFile {
+    /// Every left-recursive cycle, once each and in order of the first rule of the cycle.
+    ///
+    /// A rule calls another on the left when nothing before the call has to consume input, so `Sum { Sum '+' Product }`
+    /// and `A { B? A 'a' }` are both left recursive. A plain recursive-descent parser never returns from such rules.
+    pub fn left_recursion(&self) -> Vec<LeftRecursion> {
+        let nullable = nullable_rules(self);
+        let edges: Vec<Vec<(usize, Range<usize>)>> = self
+            .rules
+            .iter()
+            .map(|rule| {
+                let mut out = vec![];
+                left_calls(&nullable, &rule.body, &mut out);
+                // one edge per called rule, the first call is the one reported
+                let mut calls: Vec<(usize, Range<usize>)> = vec![];
+                for (name, span) in out {
+                    match self.rules.iter().position(|r| r.name.text == name) {
+                        Some(index) if !calls.iter().any(|(i, _)| *i == index) => calls.push((index, span)),
+                        _ => {}
+                    }
+                }
+                calls
+            })
+            .collect();
+        let mut out = vec![];
+        for start in 0..self.rules.len() {
+            self.find_cycles(&edges, start, &mut vec![], &(0..0), &mut out);
+        }
+        out
+    }
+
+    /// Cycles through `start` that only visit rules declared after it, so every cycle is found from its first rule
+    fn find_cycles(
+        &self,
+        edges: &[Vec<(usize, Range<usize>)>],
+        start: usize,
+        path: &mut Vec<usize>,
+        span: &Range<usize>,
+        out: &mut Vec<LeftRecursion>,
+    ) {
+        let rule = path.last().copied().unwrap_or(start);
+        for (next, call) in &edges[rule] {
+            let span = if path.is_empty() { call } else { span };
+            if *next == start {
+                let chain = once(start).chain(path.iter().copied()).chain(once(start));
+                out.push(LeftRecursion {
+                    chain: chain.map(|index| self.rules[index].name.text.clone()).collect(),
+                    span: span.clone(),
+                });
+            }
+            else if *next > start && !path.contains(next) {
+                path.push(*next);
+                self.find_cycles(edges, start, path, span, out);
+                path.pop();
+            }
+        }
+    }
+}
+
+/// References `expression` may call before consuming any input
+fn left_calls<'g>(nullable: &BTreeSet<&str>, expression: &'g Expression, out: &mut Vec<(&'g str, Range<usize>)>) {
+    match &expression.kind {
+        ExpressionKind::Reference(name) => out.push((name, expression.span.clone())),
+        ExpressionKind::Sequence(items) => {
+            for item in items {
+                left_calls(nullable, item, out);
+                if !is_nullable(nullable, item) {
+                    break;
+                }
+            }
+        }
+        ExpressionKind::Choice(items) => items.iter().for_each(|item| left_calls(nullable, item, out)),
+        ExpressionKind::Tagged { body, .. }
+        | ExpressionKind::Capture { body, .. }
+        | ExpressionKind::Repeat { body, .. }
+        | ExpressionKind::Lookahead { body, .. } => left_calls(nullable, body, out),
+        _ => {}
+    }
+}
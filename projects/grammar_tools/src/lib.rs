@@ -0,0 +1,28 @@
+#![deny(missing_debug_implementations)]
+#![warn(missing_docs, rustdoc::missing_crate_level_docs)]
+#![doc = include_str!("../readme.md")]
+#![doc(html_logo_url = "https://raw.githubusercontent.com/oovm/shape-rs/dev/projects/images/Trapezohedron.svg")]
+#![doc(html_favicon_url = "https://raw.githubusercontent.com/oovm/shape-rs/dev/projects/images/Trapezohedron.svg")]
+
+pub use crate::{
+    ast::{
+        Annotation, Argument, Expression, ExpressionKind, Fixity, GrammarFile, Identifier, Import, Indentation, Macro,
+        Operator, OperatorLevel, OperatorTable, Parameter, Rule, RuleKind,
+    },
+    errors::{GrammarError, ParseError, Result, line_column},
+    interpreter::{CstNode, DEFAULT_MAX_DEPTH, IGNORE_REGEX, IGNORE_TEXT, Interpreter, Scanner, snake_case},
+    left_recursion::LeftRecursion,
+    lint::{Lint, LintKind},
+    lower::{external_marker, imported_name, indentation_marker, lookahead_marker},
+    railroad::{RailroadRenderer, RailroadTheme},
+};
+
+mod ast;
+mod errors;
+mod import;
+mod interpreter;
+mod left_recursion;
+mod lint;
+mod lower;
+mod parser;
+mod railroad;
@@ -0,0 +1,490 @@
+use crate::{Expression, ExpressionKind, GrammarFile, Indentation, Interpreter, Rule, RuleKind, snake_case};
+use regex::Regex;
+use regex_syntax::hir::{Class, Hir, HirKind};
+use std::{collections::BTreeSet, ops::Range};
+
+/// Example inputs tried per branch when looking for shadowed branches
+const SAMPLES: usize = 8;
+/// References followed when building example inputs
+const SAMPLE_DEPTH: usize = 6;
+
+/// A likely mistake found by [`GrammarFile::lint`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Lint {
+    /// What kind of mistake
+    pub kind: LintKind,
+    /// What is wrong
+    pub message: String,
+    /// Byte range in the grammar file
+    pub span: Range<usize>,
+    /// The other half of the problem, such as the branch that shadows this one
+    pub related: Option<Range<usize>>,
+}
+
+/// The checks of [`GrammarFile::lint`]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum LintKind {
+    /// An earlier branch of an ordered choice also matches input a later branch is written for
+    ChoiceShadowing,
+    /// A branch of an ordered choice can never be taken
+    UnreachableAlternative,
+    /// `*` or `+` over an expression that can match nothing, the generated parser never leaves the loop
+    EmptyLoop,
+    /// A rule that cannot be reached from an `entry` or `ignore` rule
+    UnusedRule,
+    /// A reference to a rule that does not exist
+    UndefinedReference,
+    /// Rules that call each other without consuming input, the generated parser never returns
+    LeftRecursion,
+    /// A class redirects to a class whose node has other fields, the generator cannot build one as the other
+    RedirectMismatch,
+}
+
+impl LintKind {
+    /// Name of the check, in kebab case
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::ChoiceShadowing => "choice-shadowing",
+            Self::UnreachableAlternative => "unreachable-alternative",
+            Self::EmptyLoop => "empty-loop",
+            Self::UnusedRule => "unused-rule",
+            Self::UndefinedReference => "undefined-reference",
+            Self::LeftRecursion => "left-recursion",
+            Self::RedirectMismatch => "redirect-mismatch",
+        }
+    }
+    /// The generated parser would not build or would hang
+    pub fn is_error(&self) -> bool {
+        matches!(self, Self::EmptyLoop | Self::UndefinedReference | Self::LeftRecursion | Self::RedirectMismatch)
+    }
+}
+
+impl GrammarFile {
+    /// Look for shadowed and unreachable branches, loops over empty matches, left recursion, unused rules, undefined
+    /// references and redirects between classes of different fields.
+    ///
+    /// Shadowing is found by trying the earlier branches on example inputs of the later one, so it can miss cases but
+    /// every report comes with an input that shows it.
+    pub fn lint(&self) -> Vec<Lint> {
+        let interpreter = Interpreter::new(self).ok().map(|interpreter| interpreter.with_left_recursion(true));
+        let mut linter = Linter { grammar: self, nullable: nullable_rules(self), interpreter, out: vec![] };
+        linter.check_references();
+        linter.check_redirects();
+        linter.check_unused();
+        for cycle in self.left_recursion() {
+            let message = format!("rule `{}` is left recursive: {cycle}", cycle.leader());
+            linter.report(LintKind::LeftRecursion, message, cycle.span, None)
+        }
+        // imported rules are linted in their own file
+        for rule in self.rules.iter().filter(|rule| !rule.is_imported()) {
+            rule.body.visit(&mut |expression| linter.check_expression(rule, expression));
+        }
+        linter.out.sort_by_key(|lint| (lint.span.start, lint.span.end));
+        linter.out
+    }
+}
+
+struct Linter<'g> {
+    grammar: &'g GrammarFile,
+    /// Rules that can match the empty string
+    nullable: BTreeSet<&'g str>,
+    /// Missing when the grammar cannot run, shadowing is not checked then
+    interpreter: Option<Interpreter>,
+    out: Vec<Lint>,
+}
+
+impl<'g> Linter<'g> {
+    fn report(&mut self, kind: LintKind, message: String, span: Range<usize>, related: Option<Range<usize>>) {
+        self.out.push(Lint { kind, message, span, related })
+    }
+
+    fn check_references(&mut self) {
+        let grammar = self.grammar;
+        for rule in &grammar.rules {
+            rule.body.visit(&mut |expression| {
+                if let ExpressionKind::Reference(name) = &expression.kind {
+                    if grammar.rule(name).is_none() {
+                        let message = match name.rsplit_once("::").and_then(|(namespace, _)| grammar.import(namespace)) {
+                            Some(import) => format!("rule `{name}` is imported from `{}`, which is not linked", import.path),
+                            None if name.contains("::") => format!("rule `{name}` is not in an imported namespace"),
+                            None => format!("rule `{name}` is not defined"),
+                        };
+                        self.report(LintKind::UndefinedReference, message, expression.span.clone(), None)
+                    }
+                }
+            });
+            if let Some(redirect) = rule.redirect.as_ref().filter(|redirect| grammar.rule(&redirect.text).is_none()) {
+                let message = format!("rule `{}` redirects to `{}`, which is not defined", rule.name.text, redirect.text);
+                self.report(LintKind::UndefinedReference, message, redirect.span.clone(), None)
+            }
+        }
+    }
+
+    fn check_redirects(&mut self) {
+        let grammar = self.grammar;
+        for rule in grammar.rules.iter().filter(|rule| rule.kind == RuleKind::Class) {
+            let Some((redirect, target)) = rule.redirect.as_ref().and_then(|r| Some((r, grammar.rule(&r.text)?)))
+            else {
+                continue;
+            };
+            if target.kind != RuleKind::Class {
+                continue;
+            }
+            let (own, other) = (node_fields(grammar, rule), node_fields(grammar, target));
+            if own != other {
+                let message = format!(
+                    "rule `{}` redirects to `{}`, whose node has other fields: {} against {}",
+                    rule.name.text,
+                    target.name.text,
+                    field_list(&own),
+                    field_list(&other)
+                );
+                self.report(LintKind::RedirectMismatch, message, redirect.span.clone(), Some(target.name.span.clone()))
+            }
+        }
+    }
+
+    fn check_unused(&mut self) {
+        let rules = &self.grammar.rules;
+        let has_entry = rules.iter().any(|rule| rule.entry);
+        // without an `entry` rule the first rule is where parsing starts
+        let mut pending: Vec<&Rule> = rules
+            .iter()
+            .enumerate()
+            .filter(|(index, rule)| rule.entry || rule.ignored || (!has_entry && *index == 0))
+            .map(|(_, rule)| rule)
+            .collect();
+        let mut used: BTreeSet<&str> = pending.iter().map(|rule| rule.name.text.as_str()).collect();
+        while let Some(rule) = pending.pop() {
+            for name in rule.references() {
+                if let Some(next) = self.grammar.rule(name) {
+                    if used.insert(&next.name.text) {
+                        pending.push(next)
+                    }
+                }
+            }
+        }
+        for rule in rules.iter().filter(|rule| !rule.is_imported() && !used.contains(rule.name.text.as_str())) {
+            let message = format!("rule `{}` is never used", rule.name.text);
+            self.report(LintKind::UnusedRule, message, rule.name.span.clone(), None)
+        }
+    }
+
+    fn check_expression(&mut self, rule: &Rule, expression: &Expression) {
+        match &expression.kind {
+            ExpressionKind::Repeat { body, max: None, .. } if is_nullable(&self.nullable, body) => {
+                let message = "the repeated expression can match nothing, so the loop never ends".to_string();
+                self.report(LintKind::EmptyLoop, message, expression.span.clone(), Some(body.span.clone()))
+            }
+            ExpressionKind::Choice(branches) => self.check_choice(rule, branches),
+            _ => {}
+        }
+    }
+
+    fn check_choice(&mut self, rule: &Rule, branches: &[Expression]) {
+        for (later, branch) in branches.iter().enumerate().skip(1) {
+            if let Some((earlier, reason)) = self.unreachable(rule, &branches[..later], branch) {
+                let message = format!("branch {} is never taken, {reason}", later + 1);
+                self.report(
+                    LintKind::UnreachableAlternative,
+                    message,
+                    branch.span.clone(),
+                    Some(branches[earlier].span.clone()),
+                );
+                continue;
+            }
+            let Some(interpreter) = &self.interpreter
+            else {
+                continue;
+            };
+            let samples: Vec<String> = self
+                .samples(branch, 0)
+                .into_iter()
+                .filter(|sample| interpreter.match_expression(rule, branch, sample) == Some(sample.len()))
+                .collect();
+            let shadow = branches[..later].iter().enumerate().find_map(|(earlier, other)| {
+                let sample = samples.iter().find(|sample| interpreter.match_expression(rule, other, sample).is_some())?;
+                Some((earlier, sample.clone()))
+            });
+            if let Some((earlier, sample)) = shadow {
+                let message = format!("branch {} also matches {sample:?}, so branch {} never sees it", earlier + 1, later + 1);
+                self.report(LintKind::ChoiceShadowing, message, branch.span.clone(), Some(branches[earlier].span.clone()));
+            }
+        }
+    }
+
+    /// The earlier branch that always wins over `branch`, and why
+    fn unreachable(&self, rule: &Rule, earlier: &[Expression], branch: &Expression) -> Option<(usize, String)> {
+        let prefix = self.literal_prefix(branch, rule.atomic, 0);
+        earlier.iter().enumerate().find_map(|(index, other)| {
+            // a nullable branch with a lookahead can still fail
+            let reason = if is_nullable(&self.nullable, other) && !has_check(other) {
+                format!("branch {} can match nothing and always succeeds", index + 1)
+            }
+            else if same_expression(other, branch) {
+                format!("it is the same as branch {}", index + 1)
+            }
+            else {
+                match self.exact_text(other, 0) {
+                    Some(text) if prefix.starts_with(&text) => {
+                        format!("branch {} matches its prefix {text:?} first", index + 1)
+                    }
+                    _ => return None,
+                }
+            };
+            Some((index, reason))
+        })
+    }
+
+    /// The only text `expression` can match
+    fn exact_text(&self, expression: &Expression, depth: usize) -> Option<String> {
+        match &expression.kind {
+            ExpressionKind::Text(text) => Some(text.clone()),
+            ExpressionKind::Tagged { body, .. } | ExpressionKind::Capture { body, .. } => self.exact_text(body, depth),
+            ExpressionKind::Reference(name) if depth < SAMPLE_DEPTH => {
+                self.exact_text(&self.grammar.rule(name)?.body, depth + 1)
+            }
+            _ => None,
+        }
+    }
+
+    /// Text every match of `expression` starts with, ignored rules can only appear between items of non-atomic rules
+    fn literal_prefix(&self, expression: &Expression, atomic: bool, depth: usize) -> String {
+        match &expression.kind {
+            ExpressionKind::Text(text) => text.clone(),
+            ExpressionKind::Tagged { body, .. } | ExpressionKind::Capture { body, .. } => {
+                self.literal_prefix(body, atomic, depth)
+            }
+            ExpressionKind::Repeat { body, min, .. } if *min > 0 => self.literal_prefix(body, atomic, depth),
+            ExpressionKind::Reference(name) if depth < SAMPLE_DEPTH => match self.grammar.rule(name) {
+                Some(rule) => self.literal_prefix(&rule.body, rule.atomic, depth + 1),
+                None => String::new(),
+            },
+            ExpressionKind::Sequence(items) if atomic => {
+                let mut out = String::new();
+                for item in items {
+                    match self.exact_text(item, depth) {
+                        Some(text) => out.push_str(&text),
+                        None => {
+                            out.push_str(&self.literal_prefix(item, atomic, depth));
+                            break;
+                        }
+                    }
+                }
+                out
+            }
+            ExpressionKind::Sequence(items) => {
+                items.first().map(|item| self.literal_prefix(item, atomic, depth)).unwrap_or_default()
+            }
+            _ => String::new(),
+        }
+    }
+
+    /// Inputs `expression` is likely to match, built from the first characters of classes and the shortest repetitions
+    fn samples(&self, expression: &Expression, depth: usize) -> Vec<String> {
+        match &expression.kind {
+            ExpressionKind::Text(text) => vec![text.clone()],
+            ExpressionKind::Regex(pattern) | ExpressionKind::CharacterClass(pattern) => {
+                regex_syntax::parse(pattern).map(|hir| hir_samples(&hir)).unwrap_or_default()
+            }
+            ExpressionKind::Any => vec!["a".to_string()],
+            // what a scanner accepts is only known to its Rust code, indentation to the enclosing blocks
+            ExpressionKind::External(_) | ExpressionKind::Indentation(_) => vec![],
+            ExpressionKind::Reference(name) => match self.grammar.rule(name) {
+                Some(rule) if depth < SAMPLE_DEPTH => self.samples(&rule.body, depth + 1),
+                _ => vec![],
+            },
+            ExpressionKind::Sequence(items) => {
+                items.iter().fold(vec![String::new()], |out, item| combine(&out, &self.samples(item, depth)))
+            }
+            ExpressionKind::Choice(items) => {
+                let mut out = vec![];
+                items.iter().for_each(|item| extend(&mut out, self.samples(item, depth)));
+                out
+            }
+            ExpressionKind::Tagged { body, .. } | ExpressionKind::Capture { body, .. } => self.samples(body, depth),
+            ExpressionKind::Repeat { body, min, .. } => repeat(self.samples(body, depth), *min),
+            ExpressionKind::Lookahead { .. } => vec![String::new()],
+        }
+    }
+}
+
+fn hir_samples(hir: &Hir) -> Vec<String> {
+    match hir.kind() {
+        HirKind::Empty | HirKind::Look(_) => vec![String::new()],
+        HirKind::Literal(literal) => String::from_utf8(literal.0.to_vec()).into_iter().collect(),
+        HirKind::Class(Class::Unicode(class)) => class_sample(class.ranges().iter().map(|r| (r.start(), r.end()))),
+        HirKind::Class(Class::Bytes(class)) => class_sample(
+            class.ranges().iter().filter(|r| r.start().is_ascii()).map(|r| (r.start() as char, r.end().min(0x7F) as char)),
+        ),
+        HirKind::Repetition(repetition) => repeat(hir_samples(&repetition.sub), repetition.min),
+        HirKind::Capture(capture) => hir_samples(&capture.sub),
+        HirKind::Concat(items) => items.iter().fold(vec![String::new()], |out, item| combine(&out, &hir_samples(item))),
+        HirKind::Alternation(items) => {
+            let mut out = vec![];
+            items.iter().for_each(|item| extend(&mut out, hir_samples(item)));
+            out
+        }
+    }
+}
+
+/// A readable character of the class: a letter or digit if there is one, then any visible ASCII character
+fn class_sample(ranges: impl Iterator<Item = (char, char)> + Clone) -> Vec<String> {
+    let ascii = |accept: fn(&char) -> bool| ranges.clone().find_map(|(start, end)| (start..=end.min('\x7F')).find(accept));
+    let first = ranges.clone().next().map(|(start, _)| start);
+    ascii(char::is_ascii_alphanumeric)
+        .or_else(|| ascii(char::is_ascii_graphic))
+        .or(first)
+        .map(String::from)
+        .into_iter()
+        .collect()
+}
+
+fn combine(heads: &[String], tails: &[String]) -> Vec<String> {
+    heads.iter().flat_map(|head| tails.iter().map(move |tail| format!("{head}{tail}"))).take(SAMPLES).collect()
+}
+
+fn extend(out: &mut Vec<String>, samples: Vec<String>) {
+    for sample in samples {
+        if out.len() < SAMPLES && !out.contains(&sample) {
+            out.push(sample)
+        }
+    }
+}
+
+/// The body repeated as few times as allowed, and once more when that is zero
+fn repeat(body: Vec<String>, min: u32) -> Vec<String> {
+    let mut out = if min == 0 { vec![String::new()] } else { vec![] };
+    extend(&mut out, body.iter().map(|sample| sample.repeat(min.max(1) as usize)).collect());
+    out
+}
+
+/// Rules that can match the empty string
+/// How many values a node field holds
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Arity {
+    One,
+    Optional,
+    Many,
+}
+
+/// Fields of the node of a class, by name: references by snake case name and captures by capture name, with the rule
+/// they hold or `None` for captured text
+fn node_fields(grammar: &GrammarFile, rule: &Rule) -> Vec<(String, Option<String>, Arity)> {
+    let mut out = vec![];
+    collect_fields(grammar, &rule.body, Arity::One, &mut out);
+    out.sort();
+    out
+}
+
+fn collect_fields(
+    grammar: &GrammarFile,
+    expression: &Expression,
+    arity: Arity,
+    out: &mut Vec<(String, Option<String>, Arity)>,
+) {
+    let mut add = |name: String, rule: Option<String>| match out.iter_mut().find(|(field, ..)| *field == name) {
+        // a field seen twice holds many values
+        Some((_, _, existing)) => *existing = Arity::Many,
+        None => out.push((name, rule, arity)),
+    };
+    match &expression.kind {
+        ExpressionKind::Reference(name) if grammar.rule(name).is_some_and(|r| !r.ignored) => {
+            add(snake_case(name), Some(name.clone()))
+        }
+        ExpressionKind::Capture { name, body } => match &body.kind {
+            ExpressionKind::Reference(rule) => add(name.text.clone(), Some(rule.clone())),
+            _ => add(name.text.clone(), None),
+        },
+        ExpressionKind::Sequence(items) => items.iter().for_each(|item| collect_fields(grammar, item, arity, out)),
+        ExpressionKind::Choice(items) => {
+            let arity = if arity == Arity::Many { Arity::Many } else { Arity::Optional };
+            items.iter().for_each(|item| collect_fields(grammar, item, arity, out))
+        }
+        ExpressionKind::Tagged { body, .. } => collect_fields(grammar, body, arity, out),
+        ExpressionKind::Repeat { body, max, .. } => {
+            let arity = match (arity, max) {
+                (Arity::Many, _) => Arity::Many,
+                (_, Some(1)) => Arity::Optional,
+                _ => Arity::Many,
+            };
+            collect_fields(grammar, body, arity, out)
+        }
+        _ => {}
+    }
+}
+
+/// `` `a`, `b?`, `c*` `` or `no fields`
+fn field_list(fields: &[(String, Option<String>, Arity)]) -> String {
+    let field = |(name, _, arity): &(String, Option<String>, Arity)| match arity {
+        Arity::One => format!("`{name}`"),
+        Arity::Optional => format!("`{name}?`"),
+        Arity::Many => format!("`{name}*`"),
+    };
+    match fields.is_empty() {
+        true => "no fields".to_string(),
+        false => fields.iter().map(field).collect::<Vec<_>>().join(", "),
+    }
+}
+
+pub(crate) fn nullable_rules(grammar: &GrammarFile) -> BTreeSet<&str> {
+    let mut nullable = BTreeSet::new();
+    loop {
+        let found: Vec<&str> = grammar
+            .rules
+            .iter()
+            .filter(|rule| !nullable.contains(rule.name.text.as_str()) && is_nullable(&nullable, &rule.body))
+            .map(|rule| rule.name.text.as_str())
+            .collect();
+        if found.is_empty() {
+            return nullable;
+        }
+        nullable.extend(found);
+    }
+}
+
+/// Whether `expression` can match the empty string, given the rules that can
+pub(crate) fn is_nullable(nullable: &BTreeSet<&str>, expression: &Expression) -> bool {
+    match &expression.kind {
+        ExpressionKind::Text(text) => text.is_empty(),
+        ExpressionKind::Regex(pattern) | ExpressionKind::CharacterClass(pattern) => {
+            Regex::new(&format!("^({pattern})")).is_ok_and(|regex| regex.is_match(""))
+        }
+        // scanners are expected to consume what they match
+        ExpressionKind::Any | ExpressionKind::External(_) => false,
+        ExpressionKind::Indentation(indentation) => *indentation == Indentation::Dedent,
+        ExpressionKind::Reference(name) => nullable.contains(name.as_str()),
+        ExpressionKind::Sequence(items) => items.iter().all(|item| is_nullable(nullable, item)),
+        ExpressionKind::Choice(items) => items.iter().any(|item| is_nullable(nullable, item)),
+        ExpressionKind::Tagged { body, .. } | ExpressionKind::Capture { body, .. } => is_nullable(nullable, body),
+        ExpressionKind::Repeat { body, min, .. } => *min == 0 || is_nullable(nullable, body),
+        ExpressionKind::Lookahead { .. } => true,
+    }
+}
+
+/// Whether `expression` holds a check that can fail without consuming input
+fn has_check(expression: &Expression) -> bool {
+    let mut found = false;
+    expression.visit(&mut |e| {
+        found |= matches!(e.kind, ExpressionKind::Lookahead { .. } | ExpressionKind::Indentation(Indentation::Dedent))
+    });
+    found
+}
+
+/// Equal up to spans and tags
+fn same_expression(a: &Expression, b: &Expression) -> bool {
+    use ExpressionKind::*;
+    match (&a.kind, &b.kind) {
+        (Tagged { body, .. }, _) => same_expression(body, b),
+        (_, Tagged { body, .. }) => same_expression(a, body),
+        (Sequence(x), Sequence(y)) | (Choice(x), Choice(y)) => {
+            x.len() == y.len() && x.iter().zip(y).all(|(x, y)| same_expression(x, y))
+        }
+        (Capture { name: n1, body: b1 }, Capture { name: n2, body: b2 }) => n1.text == n2.text && same_expression(b1, b2),
+        (Repeat { body: b1, min: m1, max: x1 }, Repeat { body: b2, min: m2, max: x2 }) => {
+            m1 == m2 && x1 == x2 && same_expression(b1, b2)
+        }
+        (Lookahead { positive: p1, body: b1 }, Lookahead { positive: p2, body: b2 }) => p1 == p2 && same_expression(b1, b2),
+        (x, y) => x == y,
+    }
+}
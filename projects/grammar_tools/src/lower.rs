@@ -0,0 +1,199 @@
+use crate::{Expression, ExpressionKind, GrammarFile, Indentation, Rule, RuleKind};
+use std::{fmt::Write, ops::Range};
+
+/// The literal [`GrammarFile::lower`] writes in place of the lookahead at `index` in [`GrammarFile::lookaheads`]
+pub fn lookahead_marker(index: usize) -> String {
+    format!("__ygg_lookahead_{index}__")
+}
+
+/// The literal [`GrammarFile::lower`] writes as the body of the `external` rule `rule`
+pub fn external_marker(rule: &str) -> String {
+    format!("__ygg_external_{rule}__")
+}
+
+/// The literal [`GrammarFile::lower`] writes in place of `INDENT`, `SAMEDENT` or `DEDENT`
+pub fn indentation_marker(indentation: Indentation) -> String {
+    format!("__ygg_{}__", indentation.to_string().to_lowercase())
+}
+
+/// The name [`GrammarFile::lower`] gives the imported rule `namespace::Name`, `json5::Value` becomes `Json5Value`
+pub fn imported_name(name: &str) -> String {
+    let mut out = String::with_capacity(name.len());
+    for segment in name.split("::") {
+        for word in segment.split('_') {
+            let mut chars = word.chars();
+            out.extend(chars.next().map(|c| c.to_ascii_uppercase()));
+            out.push_str(chars.as_str());
+        }
+    }
+    match name.contains("::") {
+        true => out,
+        false => name.to_string(),
+    }
+}
+
+impl GrammarFile {
+    /// The grammar `text` rewritten for code generators that only know `class` and `union`.
+    ///
+    /// - every `operators` rule is written as the `class` it matches like
+    /// - every `external` rule is written as a `class` matching the text [`external_marker`], whose generated matcher
+    ///   the caller swaps for a call to the scanner
+    /// - every `&e` and `!e` becomes the text [`lookahead_marker`], whose generated matcher the caller swaps for the
+    ///   real check
+    /// - every `INDENT`, `SAMEDENT` and `DEDENT` outside of them becomes the text [`indentation_marker`], in the same way
+    /// - every `namespace::Name` reference becomes [`imported_name`], and the rules copied in by [`GrammarFile::link`]
+    ///   are written after the others under that name, the `import` statements are removed
+    /// - every rule calling a macro has its body written with the expansions, the `macro` declarations are removed
+    ///
+    /// Annotations, modifiers, comments and the other rules are kept as written, so spans of the other rules only move
+    /// after a rewritten one.
+    pub fn lower(&self, text: &str) -> String {
+        let mut edits: Vec<(Range<usize>, String)> =
+            self.imports.iter().map(|import| (import.span.clone(), String::new())).collect();
+        edits.extend(self.macros.iter().map(|declared| (declared.span.clone(), String::new())));
+        for rule in self.rules.iter().filter(|rule| !rule.is_imported()) {
+            let (keyword, body) = match rule.kind {
+                RuleKind::Operators => ("operators", lowered_expression(&rule.body, &mut 0).to_string()),
+                RuleKind::External => ("external", format!("'{}'", external_marker(&rule.name.text))),
+                RuleKind::Class | RuleKind::Union => continue,
+            };
+            if let Some(offset) = text[rule.span.start..rule.name.span.start].rfind(keyword) {
+                let span = rule.span.start + offset..rule.span.end;
+                edits.push((span, format!("class {} {{\n    {body}\n}}", rule.name.text)));
+            }
+        }
+        let lookaheads = self.lookaheads();
+        let mut next_lookahead = lookaheads.iter().filter(|(rule, _)| !rule.is_imported()).count();
+        for (index, (rule, lookahead)) in lookaheads.iter().enumerate().take(next_lookahead) {
+            if rule.expansions.is_empty() {
+                edits.push((lookahead.span.clone(), format!("'{}'", lookahead_marker(index))));
+            }
+        }
+        for rule in self.rules.iter().filter(|rule| !rule.is_imported()) {
+            if !rule.expansions.is_empty() {
+                // the expansions have no text of their own, the whole body is written again
+                let mut first = lookaheads.iter().position(|(r, _)| r.name == rule.name).unwrap_or_default();
+                edits.push((rule.body.span.clone(), lowered_expression(&rule.body, &mut first).to_string()));
+                continue;
+            }
+            if rule.kind != RuleKind::Operators {
+                let mut found = vec![];
+                outer_references(&rule.body, &mut found);
+                edits.extend(found.into_iter().map(|(span, name)| (span, imported_name(name))));
+            }
+            let mut found = vec![];
+            outer_indentations(&rule.body, &mut found);
+            edits.extend(found.into_iter().map(|(span, indentation)| (span, format!("'{}'", indentation_marker(indentation)))));
+        }
+        edits.sort_by_key(|(span, _)| span.start);
+        let mut out = String::with_capacity(text.len());
+        let mut copied = 0;
+        for (span, replacement) in edits {
+            out.push_str(&text[copied..span.start]);
+            out.push_str(&replacement);
+            copied = span.end;
+        }
+        out.push_str(&text[copied..]);
+        for rule in self.rules.iter().filter(|rule| rule.is_imported()) {
+            let keyword = match rule.kind {
+                RuleKind::Union => "union",
+                _ => "class",
+            };
+            let modifier = if rule.atomic { "atomic " } else { "" };
+            let name = imported_name(&rule.name.text);
+            let redirect = rule.redirect.as_ref().map(|r| format!(" -> {}", imported_name(&r.text))).unwrap_or_default();
+            let body = lowered_expression(&rule.body, &mut next_lookahead);
+            write!(out, "\n{modifier}{keyword} {name}{redirect} {{\n    {body}\n}}\n").unwrap();
+        }
+        out
+    }
+    /// Every `&e` and `!e` that is not inside another one, with its rule, in source order
+    pub fn lookaheads(&self) -> Vec<(&Rule, &Expression)> {
+        let mut out = vec![];
+        for rule in &self.rules {
+            let mut found = vec![];
+            outer_lookaheads(&rule.body, &mut found);
+            out.extend(found.into_iter().map(|expression| (rule, expression)));
+        }
+        out
+    }
+    /// Whether a rule uses `INDENT`, `SAMEDENT` or `DEDENT`
+    pub fn uses_indentation(&self) -> bool {
+        let mut found = false;
+        for rule in &self.rules {
+            rule.body.visit(&mut |e| found |= matches!(e.kind, ExpressionKind::Indentation(_)));
+        }
+        found
+    }
+}
+
+/// A rule body as [`GrammarFile::lower`] writes it when it rewrites the whole rule, `lookaheads` is the index of the
+/// next lookahead and is moved past the ones of `expression`
+fn lowered_expression(expression: &Expression, lookaheads: &mut usize) -> Expression {
+    let kind = match &expression.kind {
+        ExpressionKind::Reference(name) => ExpressionKind::Reference(imported_name(name)),
+        ExpressionKind::Indentation(indentation) => ExpressionKind::Text(indentation_marker(*indentation)),
+        ExpressionKind::Lookahead { .. } => {
+            *lookaheads += 1;
+            ExpressionKind::Text(lookahead_marker(*lookaheads - 1))
+        }
+        ExpressionKind::Sequence(items) => {
+            ExpressionKind::Sequence(items.iter().map(|item| lowered_expression(item, lookaheads)).collect())
+        }
+        ExpressionKind::Choice(items) => {
+            ExpressionKind::Choice(items.iter().map(|item| lowered_expression(item, lookaheads)).collect())
+        }
+        ExpressionKind::Tagged { tag, body } => {
+            ExpressionKind::Tagged { tag: tag.clone(), body: Box::new(lowered_expression(body, lookaheads)) }
+        }
+        ExpressionKind::Capture { name, body } => {
+            ExpressionKind::Capture { name: name.clone(), body: Box::new(lowered_expression(body, lookaheads)) }
+        }
+        ExpressionKind::Repeat { body, min, max } => {
+            ExpressionKind::Repeat { body: Box::new(lowered_expression(body, lookaheads)), min: *min, max: *max }
+        }
+        other => other.clone(),
+    };
+    Expression { kind, span: expression.span.clone() }
+}
+
+/// `namespace::Name` references outside of lookaheads, which are lowered whole
+fn outer_references<'g>(expression: &'g Expression, out: &mut Vec<(Range<usize>, &'g str)>) {
+    match &expression.kind {
+        ExpressionKind::Reference(name) if name.contains("::") => out.push((expression.span.clone(), name)),
+        ExpressionKind::Sequence(items) | ExpressionKind::Choice(items) => {
+            items.iter().for_each(|item| outer_references(item, out))
+        }
+        ExpressionKind::Tagged { body, .. } | ExpressionKind::Capture { body, .. } | ExpressionKind::Repeat { body, .. } => {
+            outer_references(body, out)
+        }
+        _ => {}
+    }
+}
+
+fn outer_lookaheads<'g>(expression: &'g Expression, out: &mut Vec<&'g Expression>) {
+    match &expression.kind {
+        ExpressionKind::Lookahead { .. } => out.push(expression),
+        ExpressionKind::Sequence(items) | ExpressionKind::Choice(items) => {
+            items.iter().for_each(|item| outer_lookaheads(item, out))
+        }
+        ExpressionKind::Tagged { body, .. } | ExpressionKind::Capture { body, .. } | ExpressionKind::Repeat { body, .. } => {
+            outer_lookaheads(body, out)
+        }
+        _ => {}
+    }
+}
+
+/// `INDENT`, `SAMEDENT` and `DEDENT` outside of lookaheads, which are lowered whole
+fn outer_indentations(expression: &Expression, out: &mut Vec<(Range<usize>, Indentation)>) {
+    match &expression.kind {
+        ExpressionKind::Indentation(indentation) => out.push((expression.span.clone(), *indentation)),
+        ExpressionKind::Sequence(items) | ExpressionKind::Choice(items) => {
+            items.iter().for_each(|item| outer_indentations(item, out))
+        }
+        ExpressionKind::Tagged { body, .. } | ExpressionKind::Capture { body, .. } | ExpressionKind::Repeat { body, .. } => {
+            outer_indentations(body, out)
+        }
+        _ => {}
+    }
+}
@@ -0,0 +1,970 @@
+use crate::{
+    Annotation, Argument, Expression, ExpressionKind, Fixity, GrammarError, GrammarFile, Identifier, Import, Indentation,
+    Macro, Operator, OperatorLevel, OperatorTable, Parameter, Result, Rule, RuleKind, snake_case,
+};
+use std::ops::Range;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Token {
+    Identifier(String),
+    Text(String),
+    Regex(String),
+    Class(String),
+    Number(u32),
+    Punct(&'static str),
+    Document(String),
+}
+
+#[derive(Clone, Debug)]
+struct Lexeme {
+    token: Token,
+    span: Range<usize>,
+}
+
+/// Punctuation, longest first
+const PUNCTS: &[&str] = &["->", "{", "}", "(", ")", "|", "?", "*", "+", ":", "#", "@", ",", ";", "&", "!", "=", "."];
+
+/// Rule modifiers that may precede `class`, `union`, `operators` and `external`
+const MODIFIERS: &[&str] = &["entry", "atomic", "ignore"];
+
+impl GrammarFile {
+    /// Read a grammar file.
+    pub fn parse(text: &str) -> Result<Self> {
+        let tokens = lex(text)?;
+        let mut parser = Parser { text, tokens, position: 0, macros: vec![], expanding: vec![], calls: vec![] };
+        parser.declare_macros()?;
+        parser.file()
+    }
+}
+
+fn lex(text: &str) -> Result<Vec<Lexeme>> {
+    let bytes = text.as_bytes();
+    let mut out = vec![];
+    let mut offset = 0;
+    while offset < bytes.len() {
+        let start = offset;
+        let rest = &text[offset..];
+        let c = rest.chars().next().unwrap_or_default();
+        if c.is_whitespace() {
+            offset += c.len_utf8();
+        }
+        else if let Some(line) = rest.strip_prefix("///") {
+            let end = line.find('\n').unwrap_or(line.len());
+            let content = line[..end].strip_prefix(' ').unwrap_or(&line[..end]).trim_end();
+            offset += 3 + end;
+            out.push(Lexeme { token: Token::Document(content.to_string()), span: start..offset });
+        }
+        else if rest.starts_with("//") {
+            offset += rest.find('\n').unwrap_or(rest.len());
+        }
+        else if let Some(comment) = rest.strip_prefix("/*") {
+            let end = comment.find("*/").ok_or_else(|| GrammarError::new("unterminated block comment", start..text.len()))?;
+            offset += end + 4;
+        }
+        else if c == '/' {
+            let length = regex_length(rest).ok_or_else(|| GrammarError::new("unterminated regex", start..text.len()))?;
+            offset += length;
+            out.push(Lexeme { token: Token::Regex(rest[1..length - 1].to_string()), span: start..offset });
+        }
+        else if c == '\'' || c == '"' {
+            let end = rest[1..].find(c).ok_or_else(|| GrammarError::new("unterminated string", start..text.len()))?;
+            offset += end + 2;
+            out.push(Lexeme { token: Token::Text(rest[1..end + 1].to_string()), span: start..offset });
+        }
+        else if c == '[' {
+            let length =
+                class_length(rest).ok_or_else(|| GrammarError::new("unterminated character class", start..text.len()))?;
+            offset += length;
+            out.push(Lexeme { token: Token::Class(rest[..length].to_string()), span: start..offset });
+        }
+        else if c == '_' || c.is_alphabetic() {
+            let length = rest.find(|c: char| !(c == '_' || c.is_alphanumeric())).unwrap_or(rest.len());
+            offset += length;
+            out.push(Lexeme { token: Token::Identifier(rest[..length].to_string()), span: start..offset });
+        }
+        else if c.is_ascii_digit() {
+            let length = rest.find(|c: char| !c.is_ascii_digit()).unwrap_or(rest.len());
+            let number = rest[..length].parse().map_err(|_| GrammarError::new("number too large", start..start + length))?;
+            offset += length;
+            out.push(Lexeme { token: Token::Number(number), span: start..offset });
+        }
+        else if let Some(punct) = PUNCTS.iter().find(|p| rest.starts_with(**p)) {
+            offset += punct.len();
+            out.push(Lexeme { token: Token::Punct(punct), span: start..offset });
+        }
+        else {
+            return Err(GrammarError::new(format!("unexpected character `{c}`"), start..start + c.len_utf8()));
+        }
+    }
+    Ok(out)
+}
+
+/// Length of `/.../` including both slashes, a `/` inside `[...]` or after `\` does not close it.
+fn regex_length(rest: &str) -> Option<usize> {
+    let mut in_class = false;
+    let mut chars = rest.char_indices().skip(1);
+    while let Some((index, c)) = chars.next() {
+        match c {
+            '\\' => {
+                chars.next();
+            }
+            '[' => in_class = true,
+            ']' => in_class = false,
+            '/' if !in_class => return Some(index + 1),
+            '\n' => return None,
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Length of `[...]` including both brackets, `\]` does not close it.
+fn class_length(rest: &str) -> Option<usize> {
+    let mut chars = rest.char_indices().skip(1);
+    while let Some((index, c)) = chars.next() {
+        match c {
+            '\\' => {
+                chars.next();
+            }
+            ']' => return Some(index + 1),
+            '\n' => return None,
+            _ => {}
+        }
+    }
+    None
+}
+
+struct Parser<'i> {
+    text: &'i str,
+    tokens: Vec<Lexeme>,
+    position: usize,
+    /// Declared macros with the tokens of their body
+    macros: Vec<(Macro, Range<usize>)>,
+    /// Macros whose body is being read, innermost last
+    expanding: Vec<Expansion>,
+    /// Macro calls written in the current rule
+    calls: Vec<Range<usize>>,
+}
+
+/// A macro body being read, with what its parameters stand for
+struct Expansion {
+    name: String,
+    bindings: Vec<(String, Argument)>,
+}
+
+impl<'i> Parser<'i> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position).map(|l| &l.token)
+    }
+    fn peek_at(&self, ahead: usize) -> Option<&Token> {
+        self.tokens.get(self.position + ahead).map(|l| &l.token)
+    }
+    fn span(&self) -> Range<usize> {
+        match self.tokens.get(self.position) {
+            Some(l) => l.span.clone(),
+            None => self.text.len()..self.text.len(),
+        }
+    }
+    fn last_end(&self) -> usize {
+        self.position.checked_sub(1).and_then(|i| self.tokens.get(i)).map_or(0, |l| l.span.end)
+    }
+    fn next(&mut self) -> Option<Lexeme> {
+        let lexeme = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        lexeme
+    }
+    /// `::` starting `ahead` tokens from here
+    fn is_path_separator(&self, ahead: usize) -> bool {
+        matches!((self.peek_at(ahead), self.peek_at(ahead + 1)), (Some(Token::Punct(":")), Some(Token::Punct(":"))))
+    }
+    fn is_punct(&self, punct: &str) -> bool {
+        matches!(self.peek(), Some(Token::Punct(p)) if *p == punct)
+    }
+    fn eat(&mut self, punct: &str) -> bool {
+        let found = self.is_punct(punct);
+        if found {
+            self.position += 1;
+        }
+        found
+    }
+    fn expect(&mut self, punct: &str) -> Result<Range<usize>> {
+        let span = self.span();
+        match self.eat(punct) {
+            true => Ok(span),
+            false => Err(self.unexpected(&format!("`{punct}`"))),
+        }
+    }
+    fn unexpected(&self, expected: &str) -> GrammarError {
+        let found = match self.peek() {
+            Some(Token::Identifier(s)) => format!("`{s}`"),
+            Some(Token::Punct(p)) => format!("`{p}`"),
+            Some(Token::Text(_)) => "a string".to_string(),
+            Some(Token::Regex(_)) => "a regex".to_string(),
+            Some(Token::Class(_)) => "a character class".to_string(),
+            Some(Token::Number(n)) => format!("`{n}`"),
+            Some(Token::Document(_)) => "a doc comment".to_string(),
+            None => "the end of file".to_string(),
+        };
+        GrammarError::new(format!("expected {expected}, found {found}"), self.span())
+    }
+    fn identifier(&mut self) -> Result<Identifier> {
+        let span = self.span();
+        match self.peek() {
+            Some(Token::Identifier(text)) => {
+                let text = text.clone();
+                self.position += 1;
+                Ok(Identifier { text, span })
+            }
+            _ => Err(self.unexpected("a name")),
+        }
+    }
+
+    fn file(mut self) -> Result<GrammarFile> {
+        let macros = self.macros.iter().map(|(declared, _)| declared.clone()).collect();
+        let mut file = GrammarFile { name: None, properties: vec![], imports: vec![], macros, rules: vec![] };
+        let mut document = vec![];
+        while let Some(token) = self.peek() {
+            match token {
+                Token::Document(line) => {
+                    document.push(line.clone());
+                    self.position += 1;
+                }
+                Token::Identifier(word) if word == "grammar" && file.name.is_none() && file.rules.is_empty() => {
+                    self.position += 1;
+                    file.name = Some(self.identifier()?);
+                    if self.is_punct("{") {
+                        file.properties = self.properties()?;
+                    }
+                    document.clear();
+                }
+                Token::Identifier(word) if word == "import" && matches!(self.peek_at(1), Some(Token::Text(_))) => {
+                    let import = self.import()?;
+                    if let Some(first) = file.import(&import.namespace.text) {
+                        let message = format!("namespace `{}` is already imported at {:?}", import.namespace.text, first.span);
+                        return Err(GrammarError::new(message, import.namespace.span));
+                    }
+                    file.imports.push(import);
+                    document.clear();
+                }
+                Token::Identifier(word) if word == "macro" && matches!(self.peek_at(1), Some(Token::Identifier(_))) => {
+                    // read by `declare_macros` already
+                    let start = self.span().start;
+                    let body = self.macros.iter().find(|(declared, _)| declared.span.start == start).map(|(_, body)| body);
+                    self.position = body.map_or(self.position + 1, |body| body.end + 1);
+                    document.clear();
+                }
+                _ => {
+                    let rule = self.rule(document.join("\n"))?;
+                    if let Some(first) = file.rules.iter().find(|r| r.name.text == rule.name.text) {
+                        let message = format!("rule `{}` is already defined at {:?}", rule.name.text, first.name.span);
+                        return Err(GrammarError::new(message, rule.name.span));
+                    }
+                    file.rules.push(rule);
+                    document.clear();
+                }
+            }
+        }
+        Ok(file)
+    }
+
+    /// `import "path" as namespace`, the namespace defaults to the file stem
+    fn import(&mut self) -> Result<Import> {
+        let start = self.span().start;
+        self.position += 1;
+        let span = self.span();
+        let Some(Token::Text(path)) = self.next().map(|l| l.token)
+        else {
+            self.position -= 1;
+            return Err(self.unexpected("the path of a grammar"));
+        };
+        let namespace = match self.peek() {
+            Some(Token::Identifier(word)) if word == "as" => {
+                self.position += 1;
+                self.identifier()?
+            }
+            _ => {
+                let stem = path.rsplit(['/', '\\']).next().unwrap_or_default();
+                let stem = stem.split_once('.').map_or(stem, |(stem, _)| stem);
+                Identifier { text: stem.to_ascii_lowercase().replace('-', "_"), span: span.clone() }
+            }
+        };
+        if !namespace.text.starts_with(|c: char| c == '_' || c.is_alphabetic()) {
+            return Err(GrammarError::new(format!("`{}` is not a valid namespace, add `as name`", namespace.text), span));
+        }
+        Ok(Import { path, namespace, span: start..self.last_end() })
+    }
+
+    /// Read every top level `macro` declaration first, so rules may call macros declared after them
+    fn declare_macros(&mut self) -> Result<()> {
+        let mut depth = 0usize;
+        while let Some(token) = self.peek() {
+            match token {
+                Token::Punct("{") => depth += 1,
+                Token::Punct("}") => depth = depth.saturating_sub(1),
+                Token::Identifier(word)
+                    if word == "macro" && depth == 0 && matches!(self.peek_at(1), Some(Token::Identifier(_))) =>
+                {
+                    let mut document: Vec<&str> = self.tokens[..self.position]
+                        .iter()
+                        .rev()
+                        .map_while(|l| match &l.token {
+                            Token::Document(line) => Some(line.as_str()),
+                            _ => None,
+                        })
+                        .collect();
+                    document.reverse();
+                    let document = document.join("\n");
+                    self.macro_declaration(document)?;
+                    continue;
+                }
+                _ => {}
+            }
+            self.position += 1;
+        }
+        for index in 0..self.macros.len() {
+            self.check_macro(index)?;
+        }
+        self.calls.clear();
+        self.position = 0;
+        Ok(())
+    }
+
+    /// `macro name(parameters) { body }`, the body is read again for every call
+    fn macro_declaration(&mut self, document: String) -> Result<()> {
+        let start = self.span().start;
+        self.position += 1;
+        let name = self.identifier()?;
+        if let Some((first, _)) = self.macros.iter().find(|(declared, _)| declared.name.text == name.text) {
+            let message = format!("macro `{}` is already declared at {:?}", name.text, first.name.span);
+            return Err(GrammarError::new(message, name.span));
+        }
+        let mut parameters: Vec<Parameter> = vec![];
+        if self.eat("(") {
+            while !self.eat(")") {
+                let parameter = self.identifier()?;
+                if parameters.iter().any(|p| p.name.text == parameter.text) {
+                    let message = format!("parameter `{}` is declared twice", parameter.text);
+                    return Err(GrammarError::new(message, parameter.span));
+                }
+                let default = match self.eat(":") {
+                    true => Some(self.argument(None)?),
+                    false => None,
+                };
+                parameters.push(Parameter { name: parameter, default });
+                if !self.is_punct(")") {
+                    self.expect(",")?;
+                }
+            }
+        }
+        let open = self.expect("{")?;
+        let first = self.position;
+        let mut depth = 0usize;
+        loop {
+            match self.peek() {
+                None => return Err(GrammarError::new("unclosed macro", start..self.text.len())),
+                Some(Token::Punct("{")) => depth += 1,
+                Some(Token::Punct("}")) if depth == 0 => break,
+                Some(Token::Punct("}")) => depth -= 1,
+                _ => {}
+            }
+            self.position += 1;
+        }
+        let tokens = first..self.position;
+        let close = self.expect("}")?;
+        if tokens.is_empty() {
+            return Err(GrammarError::new(format!("macro `{}` has an empty body", name.text), open.start..close.end));
+        }
+        let declared = Macro { name, parameters, document, body: open.end..close.start, span: start..close.end };
+        self.macros.push((declared, tokens));
+        Ok(())
+    }
+
+    /// Read the body of a macro once, with its parameters standing for themselves and every flag set
+    fn check_macro(&mut self, index: usize) -> Result<()> {
+        let (declared, tokens) = self.macros[index].clone();
+        let bindings = declared
+            .parameters
+            .iter()
+            .map(|parameter| {
+                let argument = match parameter.is_flag() {
+                    true => Argument::Flag(true),
+                    false => Argument::Expression(Expression {
+                        kind: ExpressionKind::Reference(parameter.name.text.clone()),
+                        span: parameter.name.span.clone(),
+                    }),
+                };
+                (parameter.name.text.clone(), argument)
+            })
+            .collect();
+        self.read_body(declared.name.text, bindings, tokens).map(|_| ())
+    }
+
+    /// `true`, `false` or an expression, `flag` tells which one the parameter takes when it is known
+    fn argument(&mut self, flag: Option<bool>) -> Result<Argument> {
+        let value = match self.peek() {
+            Some(Token::Identifier(word)) if flag != Some(false) && (word == "true" || word == "false") => Some(word == "true"),
+            _ => None,
+        };
+        match value {
+            Some(value) => {
+                self.position += 1;
+                Ok(Argument::Flag(value))
+            }
+            None if flag == Some(true) => Err(self.unexpected("`true` or `false`")),
+            None => Ok(Argument::Expression(self.choice()?)),
+        }
+    }
+
+    /// `@name(arguments)`, the body of the macro read with the arguments in place of its parameters.
+    ///
+    /// Expressions of the body take the span of the call and errors in it are reported there, the arguments keep their
+    /// own spans.
+    fn call(&mut self) -> Result<Expression> {
+        let start = self.expect("@")?.start;
+        let name = self.identifier()?;
+        let Some((declared, tokens)) = self.macros.iter().find(|(declared, _)| declared.name.text == name.text).cloned()
+        else {
+            return Err(GrammarError::new(format!("macro `{}` is not declared", name.text), name.span));
+        };
+        let mut arguments: Vec<Option<Argument>> = vec![None; declared.parameters.len()];
+        let mut positional = 0;
+        if self.eat("(") {
+            while !self.eat(")") {
+                let index = match matches!(self.peek_at(1), Some(Token::Punct(":"))) && !self.is_path_separator(1) {
+                    true => {
+                        let parameter = self.identifier()?;
+                        self.position += 1;
+                        let index = declared.parameters.iter().position(|p| p.name.text == parameter.text);
+                        let message = format!("macro `{}` has no parameter `{}`", name.text, parameter.text);
+                        index.ok_or_else(|| GrammarError::new(message, parameter.span))?
+                    }
+                    false if positional == arguments.len() => {
+                        let message = format!("macro `{}` takes {} argument(s)", name.text, arguments.len());
+                        return Err(GrammarError::new(message, self.span()));
+                    }
+                    false => {
+                        positional += 1;
+                        positional - 1
+                    }
+                };
+                let parameter = &declared.parameters[index];
+                if arguments[index].is_some() {
+                    let message = format!("parameter `{}` is given twice", parameter.name.text);
+                    return Err(GrammarError::new(message, self.span()));
+                }
+                arguments[index] = Some(self.argument(Some(parameter.is_flag()))?);
+                if !self.is_punct(")") {
+                    self.expect(",")?;
+                }
+            }
+        }
+        let span = start..self.last_end();
+        let mut bindings = vec![];
+        for (parameter, argument) in declared.parameters.iter().zip(arguments) {
+            let Some(argument) = argument.or_else(|| parameter.default.clone())
+            else {
+                let message = format!("`@{}` needs an argument for `{}`", name.text, parameter.name.text);
+                return Err(GrammarError::new(message, span));
+            };
+            bindings.push((parameter.name.text.clone(), argument));
+        }
+        if let Some(first) = self.expanding.iter().position(|expansion| expansion.name == name.text) {
+            let chain: Vec<&str> =
+                self.expanding[first..].iter().map(|expansion| expansion.name.as_str()).chain([name.text.as_str()]).collect();
+            let message = format!("macro `{}` expands itself: {}", name.text, chain.join(" -> "));
+            return Err(GrammarError::new(message, span));
+        }
+        if self.expanding.is_empty() {
+            self.calls.push(span.clone());
+        }
+        let mut expanded = self.read_body(name.text, bindings, tokens).map_err(|mut error| {
+            if declared.span.contains(&error.span.start) {
+                error.span = span.clone();
+            }
+            error
+        })?;
+        relocate(&mut expanded, &declared.span, &span);
+        Ok(expanded)
+    }
+
+    /// Parse the `tokens` of a macro body with its parameters bound, then come back
+    fn read_body(&mut self, name: String, bindings: Vec<(String, Argument)>, tokens: Range<usize>) -> Result<Expression> {
+        let resume = self.position;
+        self.position = tokens.start;
+        self.expanding.push(Expansion { name, bindings });
+        let body = match self.choice() {
+            Ok(_) if self.position != tokens.end => Err(self.unexpected("`|` or `}`")),
+            other => other,
+        };
+        self.expanding.pop();
+        self.position = resume;
+        body
+    }
+
+    /// What a parameter of the innermost macro being read stands for
+    fn binding(&self, name: &str) -> Option<&Argument> {
+        let expansion = self.expanding.last()?;
+        expansion.bindings.iter().find(|(parameter, _)| parameter == name).map(|(_, argument)| argument)
+    }
+
+    /// `if flag { ... }` in a macro body
+    fn is_condition(&self) -> bool {
+        !self.expanding.is_empty()
+            && matches!(
+                (self.peek(), self.peek_at(1), self.peek_at(2)),
+                (Some(Token::Identifier(word)), Some(Token::Identifier(_)), Some(Token::Punct("{"))) if word == "if"
+            )
+    }
+
+    /// `if flag { expression }`, the expression when the flag is set
+    fn condition(&mut self) -> Result<(Identifier, Option<Expression>)> {
+        self.position += 1;
+        let flag = self.identifier()?;
+        let set = match self.binding(&flag.text) {
+            Some(Argument::Flag(set)) => *set,
+            _ => return Err(GrammarError::new(format!("`{}` is not a flag of the macro", flag.text), flag.span)),
+        };
+        self.expect("{")?;
+        let body = self.choice()?;
+        self.expect("}")?;
+        Ok((flag, set.then_some(body)))
+    }
+
+    fn properties(&mut self) -> Result<Vec<(Identifier, String)>> {
+        self.expect("{")?;
+        let mut out = vec![];
+        while !self.eat("}") {
+            let key = self.identifier()?;
+            self.expect(":")?;
+            let value = match self.next().map(|l| l.token) {
+                Some(Token::Text(s) | Token::Identifier(s)) => s,
+                Some(Token::Number(n)) => n.to_string(),
+                _ => {
+                    self.position -= 1;
+                    return Err(self.unexpected("a string"));
+                }
+            };
+            out.push((key, value));
+            while self.eat(",") || self.eat(";") {}
+        }
+        Ok(out)
+    }
+
+    fn rule(&mut self, document: String) -> Result<Rule> {
+        let start = self.span().start;
+        let mut annotations = vec![];
+        while self.is_punct("@") {
+            annotations.push(self.annotation()?);
+        }
+        let (mut entry, mut atomic, mut ignored) = (false, false, false);
+        let kind = loop {
+            let word = self.identifier()?;
+            match word.text.as_str() {
+                "class" => break RuleKind::Class,
+                "union" => break RuleKind::Union,
+                "operators" => break RuleKind::Operators,
+                "external" => break RuleKind::External,
+                "entry" => entry = true,
+                "atomic" => atomic = true,
+                "ignore" => ignored = true,
+                other => {
+                    let message = format!(
+                        "expected `class`, `union`, `operators`, `external` or one of `{}`, found `{other}`",
+                        MODIFIERS.join("`, `")
+                    );
+                    return Err(GrammarError::new(message, word.span));
+                }
+            }
+        };
+        let name = self.identifier()?;
+        let redirect = match self.eat("->") {
+            true => Some(self.identifier()?),
+            false => None,
+        };
+        let open = self.expect("{")?;
+        if kind == RuleKind::Operators {
+            let (table, body) = self.operator_table(&name, open)?;
+            let close = self.expect("}")?;
+            return Ok(Rule {
+                name,
+                kind,
+                entry,
+                atomic,
+                ignored,
+                redirect,
+                annotations,
+                document,
+                body,
+                expansions: vec![],
+                operators: Some(table),
+                span: start..close.end,
+            });
+        }
+        if kind == RuleKind::External {
+            let body = self.scanner_path()?;
+            let close = self.expect("}")?;
+            return Ok(Rule {
+                name,
+                kind,
+                entry,
+                atomic,
+                ignored,
+                redirect,
+                annotations,
+                document,
+                body,
+                expansions: vec![],
+                operators: None,
+                span: start..close.end,
+            });
+        }
+        // a leading `|` is allowed in a class body too, as in `class String { | "'" "'" | "'" ANY "'" }`
+        self.eat("|");
+        if self.is_punct("}") {
+            return Err(GrammarError::new(format!("rule `{}` has an empty body", name.text), open.start..self.span().end));
+        }
+        let body = self.choice()?;
+        if !self.is_punct("}") {
+            return Err(self.unexpected("`|` or `}`"));
+        }
+        let close = self.expect("}")?;
+        Ok(Rule {
+            name,
+            kind,
+            entry,
+            atomic,
+            ignored,
+            redirect,
+            annotations,
+            document,
+            body,
+            expansions: std::mem::take(&mut self.calls),
+            operators: None,
+            span: start..close.end,
+        })
+    }
+
+    /// `crate::scanners::block_comment`, the Rust function matching an `external` rule
+    fn scanner_path(&mut self) -> Result<Expression> {
+        if !matches!(self.peek(), Some(Token::Identifier(_))) {
+            return Err(self.unexpected("the path of a Rust function"));
+        }
+        let first = self.identifier()?;
+        let mut path = first.text;
+        while self.is_path_separator(0) {
+            self.position += 2;
+            path.push_str("::");
+            path.push_str(&self.identifier()?.text);
+        }
+        Ok(Expression { kind: ExpressionKind::External(path), span: first.span.start..self.last_end() })
+    }
+
+    /// `operand Atom` and one line per precedence level, up to the closing brace
+    fn operator_table(&mut self, name: &Identifier, open: Range<usize>) -> Result<(OperatorTable, Expression)> {
+        let keyword = self.identifier()?;
+        if keyword.text != "operand" {
+            return Err(GrammarError::new(format!("expected `operand`, found `{}`", keyword.text), keyword.span));
+        }
+        let operand = self.identifier()?;
+        let mut levels: Vec<OperatorLevel> = vec![];
+        while !self.is_punct("}") {
+            let word = self.identifier()?;
+            let fixity = match word.text.as_str() {
+                "prefix" => Fixity::Prefix,
+                "postfix" => Fixity::Postfix,
+                "infix" => match self.peek() {
+                    Some(Token::Identifier(side)) if side == "right" => {
+                        self.position += 1;
+                        Fixity::InfixRight
+                    }
+                    Some(Token::Identifier(side)) if side == "left" => {
+                        self.position += 1;
+                        Fixity::InfixLeft
+                    }
+                    _ => Fixity::InfixLeft,
+                },
+                other => {
+                    let message = format!("expected `prefix`, `infix` or `postfix`, found `{other}`");
+                    return Err(GrammarError::new(message, word.span));
+                }
+            };
+            let mut operators = vec![self.operator()?];
+            while self.eat("|") {
+                operators.push(self.operator()?);
+            }
+            let precedence = levels.len() as u32 + 1;
+            levels.push(OperatorLevel { precedence, fixity, operators, span: word.span.start..self.last_end() });
+        }
+        if levels.is_empty() {
+            return Err(GrammarError::new(format!("rule `{}` has no operators", name.text), open.start..self.span().end));
+        }
+        let mut seen: Vec<&Identifier> = vec![];
+        for operator in levels.iter().flat_map(|level| &level.operators) {
+            if let Some(first) = seen.iter().find(|tag| snake_case(&tag.text) == snake_case(&operator.tag.text)) {
+                let message = format!("operator tag `{}` is already used at {:?}", operator.tag.text, first.span);
+                return Err(GrammarError::new(message, operator.tag.span.clone()));
+            }
+            seen.push(&operator.tag);
+        }
+        let body = operator_body(&operand, &levels, open.start..self.span().end);
+        Ok((OperatorTable { operand, levels }, body))
+    }
+
+    fn operator(&mut self) -> Result<Operator> {
+        let span = self.span();
+        let Some(Token::Text(text)) = self.peek()
+        else {
+            return Err(self.unexpected("an operator string"));
+        };
+        let text = text.clone();
+        self.position += 1;
+        self.expect("#")?;
+        let tag = self.identifier()?;
+        Ok(Operator { text, span: span.start..tag.span.end, tag })
+    }
+
+    fn annotation(&mut self) -> Result<Annotation> {
+        let start = self.expect("@")?.start;
+        let name = self.identifier()?;
+        let mut arguments = vec![];
+        if self.eat("(") {
+            let mut depth = 0usize;
+            let mut argument_start = self.span().start;
+            let mut single = None;
+            let mut count = 0;
+            loop {
+                let span = self.span();
+                match self.next().map(|l| l.token) {
+                    None => return Err(GrammarError::new("unclosed annotation", start..self.text.len())),
+                    Some(Token::Punct("(")) => depth += 1,
+                    Some(Token::Punct(")")) if depth > 0 => depth -= 1,
+                    Some(Token::Punct(p @ (")" | ","))) if depth == 0 => {
+                        let raw = self.text[argument_start..span.start].trim();
+                        match single.take() {
+                            Some(text) if count == 1 => arguments.push(text),
+                            _ if !raw.is_empty() => arguments.push(raw.to_string()),
+                            _ => {}
+                        }
+                        count = 0;
+                        argument_start = span.end;
+                        if p == ")" {
+                            break;
+                        }
+                        continue;
+                    }
+                    Some(Token::Text(text)) => single = Some(text),
+                    Some(_) => {}
+                }
+                count += 1;
+            }
+        }
+        Ok(Annotation { name, arguments, span: start..self.last_end() })
+    }
+
+    fn choice(&mut self) -> Result<Expression> {
+        let mut branches = vec![self.branch()?];
+        while self.eat("|") {
+            branches.push(self.branch()?);
+        }
+        if branches.len() == 1 {
+            return Ok(branches.remove(0));
+        }
+        let span = branches[0].span.start..branches[branches.len() - 1].span.end;
+        Ok(Expression { kind: ExpressionKind::Choice(branches), span })
+    }
+
+    fn branch(&mut self) -> Result<Expression> {
+        let body = self.sequence()?;
+        if !self.eat("#") {
+            return Ok(body);
+        }
+        let tag = self.identifier()?;
+        let span = body.span.start..tag.span.end;
+        Ok(Expression { kind: ExpressionKind::Tagged { tag, body: Box::new(body) }, span })
+    }
+
+    fn sequence(&mut self) -> Result<Expression> {
+        let mut items = vec![];
+        let mut skipped = None;
+        while !matches!(self.peek(), None | Some(Token::Punct("|" | "#" | "}" | ")" | ","))) {
+            if !self.is_condition() {
+                items.push(self.capture()?);
+                continue;
+            }
+            match self.condition()? {
+                (_, Some(Expression { kind: ExpressionKind::Sequence(inner), .. })) => items.extend(inner),
+                (_, Some(expression)) => items.push(expression),
+                (flag, None) => skipped = Some(flag),
+            }
+        }
+        match items.len() {
+            0 => match skipped {
+                Some(flag) => {
+                    let message = format!("nothing is left to match when `{}` is false", flag.text);
+                    Err(GrammarError::new(message, flag.span))
+                }
+                None => Err(self.unexpected("an expression")),
+            },
+            1 => Ok(items.remove(0)),
+            _ => {
+                let span = items[0].span.start..items[items.len() - 1].span.end;
+                Ok(Expression { kind: ExpressionKind::Sequence(items), span })
+            }
+        }
+    }
+
+    fn capture(&mut self) -> Result<Expression> {
+        if matches!((self.peek(), self.peek_at(1)), (Some(Token::Identifier(_)), Some(Token::Punct(":"))))
+            && !self.is_path_separator(1)
+        {
+            let name = self.identifier()?;
+            self.position += 1;
+            let body = self.postfix()?;
+            let span = name.span.start..body.span.end;
+            return Ok(Expression { kind: ExpressionKind::Capture { name, body: Box::new(body) }, span });
+        }
+        self.lookahead()
+    }
+
+    /// `&a` and `!a` apply to the repeated expression, `!a*` checks `a*`
+    fn lookahead(&mut self) -> Result<Expression> {
+        let positive = match self.peek() {
+            Some(Token::Punct("&")) => true,
+            Some(Token::Punct("!")) => false,
+            _ => return self.postfix(),
+        };
+        let start = self.span().start;
+        self.position += 1;
+        let body = self.lookahead()?;
+        let span = start..body.span.end;
+        Ok(Expression { kind: ExpressionKind::Lookahead { positive, body: Box::new(body) }, span })
+    }
+
+    fn postfix(&mut self) -> Result<Expression> {
+        let mut expression = self.primary()?;
+        loop {
+            let (min, max) = match self.peek() {
+                Some(Token::Punct("?")) => (0, Some(1)),
+                Some(Token::Punct("*")) => (0, None),
+                Some(Token::Punct("+")) => (1, None),
+                _ => return Ok(expression),
+            };
+            let end = self.next().map_or(expression.span.end, |l| l.span.end);
+            let span = expression.span.start..end;
+            expression = Expression { kind: ExpressionKind::Repeat { body: Box::new(expression), min, max }, span };
+        }
+    }
+
+    fn primary(&mut self) -> Result<Expression> {
+        let span = self.span();
+        if let Some(Token::Identifier(name)) = self.peek() {
+            match self.binding(name) {
+                Some(_) if self.is_path_separator(1) => {}
+                Some(Argument::Expression(argument)) => {
+                    let argument = argument.clone();
+                    self.position += 1;
+                    return Ok(argument);
+                }
+                Some(Argument::Flag(_)) => {
+                    return Err(GrammarError::new(format!("flag `{name}` can only be tested with `if`"), span));
+                }
+                None => {}
+            }
+        }
+        let kind = match self.peek() {
+            Some(Token::Text(s)) => ExpressionKind::Text(s.clone()),
+            Some(Token::Regex(s)) => ExpressionKind::Regex(s.clone()),
+            Some(Token::Class(s)) => ExpressionKind::CharacterClass(s.clone()),
+            Some(Token::Identifier(s)) if s == "ANY" => ExpressionKind::Any,
+            Some(Token::Identifier(s)) if s == "INDENT" => ExpressionKind::Indentation(Indentation::Indent),
+            Some(Token::Identifier(s)) if s == "SAMEDENT" => ExpressionKind::Indentation(Indentation::Samedent),
+            Some(Token::Identifier(s)) if s == "DEDENT" => ExpressionKind::Indentation(Indentation::Dedent),
+            Some(Token::Identifier(_)) if self.is_path_separator(1) => {
+                let mut name = self.identifier()?.text;
+                while self.is_path_separator(0) {
+                    self.position += 2;
+                    name.push_str("::");
+                    name.push_str(&self.identifier()?.text);
+                }
+                return Ok(Expression { kind: ExpressionKind::Reference(name), span: span.start..self.last_end() });
+            }
+            Some(Token::Identifier(s)) => ExpressionKind::Reference(s.clone()),
+            Some(Token::Punct("@")) => return self.call(),
+            Some(Token::Punct("(")) => {
+                self.position += 1;
+                let inner = self.choice()?;
+                let close = self.expect(")")?;
+                return Ok(Expression { kind: inner.kind, span: span.start..close.end });
+            }
+            _ => return Err(self.unexpected("an expression")),
+        };
+        self.position += 1;
+        Ok(Expression { kind, span })
+    }
+}
+
+/// Give the nodes of a macro expansion that come from the declaration the span of the call
+fn relocate(expression: &mut Expression, declaration: &Range<usize>, call: &Range<usize>) {
+    let inside = |span: &Range<usize>| declaration.start <= span.start && span.end <= declaration.end;
+    if inside(&expression.span) {
+        expression.span = call.clone();
+    }
+    match &mut expression.kind {
+        ExpressionKind::Sequence(items) | ExpressionKind::Choice(items) => {
+            items.iter_mut().for_each(|item| relocate(item, declaration, call))
+        }
+        ExpressionKind::Tagged { tag: name, body } | ExpressionKind::Capture { name, body } => {
+            if inside(&name.span) {
+                name.span = call.clone();
+            }
+            relocate(body, declaration, call)
+        }
+        ExpressionKind::Repeat { body, .. } | ExpressionKind::Lookahead { body, .. } => relocate(body, declaration, call),
+        _ => {}
+    }
+}
+
+/// What an operator table matches: operands with their prefix and postfix operators, separated by infix operators.
+///
+/// Every operator is captured with the snake case name of its tag, the tree stays flat and is folded by binding power
+/// when the typed node is built.
+fn operator_body(operand: &Identifier, levels: &[OperatorLevel], span: Range<usize>) -> Expression {
+    let expression = |kind| Expression { kind, span: span.clone() };
+    let choice = |accept: fn(Fixity) -> bool| {
+        let mut captures: Vec<Expression> = levels
+            .iter()
+            .filter(|level| accept(level.fixity))
+            .flat_map(|level| &level.operators)
+            .map(|operator| {
+                let text = Expression {
+                    kind: ExpressionKind::Text(operator.text.clone()),
+                    span: operator.span.start..operator.span.start + operator.text.len() + 2,
+                };
+                let name = Identifier { text: snake_case(&operator.tag.text), span: operator.tag.span.clone() };
+                Expression { kind: ExpressionKind::Capture { name, body: Box::new(text) }, span: operator.span.clone() }
+            })
+            .collect();
+        match captures.len() {
+            0 => None,
+            1 => Some(captures.remove(0)),
+            _ => Some(expression(ExpressionKind::Choice(captures))),
+        }
+    };
+    let many = |body: Expression| expression(ExpressionKind::Repeat { body: Box::new(body), min: 0, max: None });
+    let reference = Expression { kind: ExpressionKind::Reference(operand.text.clone()), span: operand.span.clone() };
+    let unit: Vec<Expression> =
+        [choice(|f| f == Fixity::Prefix).map(many), Some(reference), choice(|f| f == Fixity::Postfix).map(many)]
+            .into_iter()
+            .flatten()
+            .collect();
+    let mut items = unit.clone();
+    if let Some(infix) = choice(|f| matches!(f, Fixity::InfixLeft | Fixity::InfixRight)) {
+        let mut tail = vec![infix];
+        tail.extend(unit);
+        items.push(many(expression(ExpressionKind::Sequence(tail))));
+    }
+    match items.len() {
+        1 => items.remove(0),
+        _ => expression(ExpressionKind::Sequence(items)),
+    }
+}
@@ -0,0 +1,455 @@
+use crate::{Expression, ExpressionKind, GrammarFile, Rule, RuleKind};
+use std::fmt::Write;
+
+/// Radius of the curves, also the horizontal room a branch needs to leave and join the main line
+const ARC: usize = 10;
+/// Vertical space between stacked branches and loops
+const GAP: usize = 10;
+/// Height of a terminal or non terminal box
+const BOX: usize = 22;
+/// Space around a whole diagram
+const MARGIN: usize = 20;
+
+/// Colors and fonts of railroad diagrams, turned into a stylesheet scoped to `svg.railroad`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RailroadTheme {
+    /// Background of the diagram
+    pub background: String,
+    /// Color of lines and box borders
+    pub stroke: String,
+    /// Color of the text
+    pub text: String,
+    /// Fill of rule references
+    pub nonterminal: String,
+    /// Fill of string literals
+    pub string: String,
+    /// Fill of regular expressions
+    pub regex: String,
+    /// Fill of character classes and `ANY`
+    pub character: String,
+    /// Color of tag and capture labels
+    pub comment: String,
+    /// Monospace font of the boxes
+    pub font_family: String,
+    /// Font size of the boxes in pixels, box widths follow it
+    pub font_size: usize,
+}
+
+impl Default for RailroadTheme {
+    fn default() -> Self {
+        Self::light()
+    }
+}
+
+impl RailroadTheme {
+    /// Dark lines on a light background, the colors of the diagrams shipped with the generated parsers
+    pub fn light() -> Self {
+        Self {
+            background: "hsl(30,20%,95%)".to_string(),
+            stroke: "black".to_string(),
+            text: "black".to_string(),
+            nonterminal: "rgba(90,90,150,.1)".to_string(),
+            string: "cornsilk".to_string(),
+            regex: "honeydew".to_string(),
+            character: "lightcoral".to_string(),
+            comment: "dimgray".to_string(),
+            font_family: "monospace".to_string(),
+            font_size: 14,
+        }
+    }
+    /// Light lines on a dark background
+    pub fn dark() -> Self {
+        Self {
+            background: "#1e1f22".to_string(),
+            stroke: "#bcbec4".to_string(),
+            text: "#dfe1e5".to_string(),
+            nonterminal: "#2b2d30".to_string(),
+            string: "#3b3a24".to_string(),
+            regex: "#243b2c".to_string(),
+            character: "#4a2b2b".to_string(),
+            comment: "#8c8f94".to_string(),
+            font_family: "monospace".to_string(),
+            font_size: 14,
+        }
+    }
+    /// The css rules of the theme
+    pub fn stylesheet(&self) -> String {
+        let Self { background, stroke, text, nonterminal, string, regex, character, comment, font_family, font_size } = self;
+        format!(
+            "svg.railroad{{background-color:{background};}}\
+             svg.railroad path{{stroke-width:2px;stroke:{stroke};fill:transparent;}}\
+             svg.railroad rect{{stroke-width:2px;stroke:{stroke};}}\
+             svg.railroad text{{font-size:{font_size}px;font-family:{font_family};text-anchor:middle;fill:{text};}}\
+             svg.railroad text.comment{{font-size:{}px;font-style:italic;text-anchor:start;fill:{comment};}}\
+             svg.railroad .nonterminal>rect{{fill:{nonterminal};}}\
+             svg.railroad .nonterminal text{{font-weight:bold;}}\
+             svg.railroad .missing>rect{{stroke-dasharray:4px;}}\
+             svg.railroad .string>rect{{fill:{string};}}\
+             svg.railroad .regex>rect{{fill:{regex};}}\
+             svg.railroad .character>rect{{fill:{character};}}",
+            font_size.saturating_sub(2).max(1)
+        )
+    }
+    fn char_width(&self) -> usize {
+        (self.font_size * 6).div_ceil(10)
+    }
+}
+
+/// Draws the rules of a grammar as railroad diagrams.
+///
+/// References to rules of the grammar become links, by default to `#RuleName` so that they work inside
+/// [`RailroadRenderer::html_page`].
+#[derive(Clone, Debug)]
+pub struct RailroadRenderer<'g> {
+    grammar: &'g GrammarFile,
+    theme: RailroadTheme,
+    link_prefix: String,
+    link_suffix: String,
+}
+
+/// A node of the diagram layout
+enum Node {
+    Terminal { text: String, class: &'static str },
+    NonTerminal { name: String, href: Option<String> },
+    Comment(String),
+    Skip,
+    Sequence(Vec<Node>),
+    Choice(Vec<Node>),
+    OneOrMore(Box<Node>),
+}
+
+/// Room a node takes: total width, height above the line it is drawn on, height below it
+#[derive(Copy, Clone)]
+struct Size {
+    width: usize,
+    up: usize,
+    down: usize,
+}
+
+impl<'g> RailroadRenderer<'g> {
+    /// Render the rules of `grammar` with the light theme
+    pub fn new(grammar: &'g GrammarFile) -> Self {
+        Self { grammar, theme: RailroadTheme::default(), link_prefix: "#".to_string(), link_suffix: String::new() }
+    }
+    /// Use other colors and fonts
+    pub fn with_theme(mut self, theme: RailroadTheme) -> Self {
+        self.theme = theme;
+        self
+    }
+    /// Link a reference to `Rule` as `{prefix}Rule{suffix}`, for example `("rules/", ".svg")` for one file per rule
+    pub fn with_links(mut self, prefix: impl Into<String>, suffix: impl Into<String>) -> Self {
+        self.link_prefix = prefix.into();
+        self.link_suffix = suffix.into();
+        self
+    }
+    /// The theme in use
+    pub fn theme(&self) -> &RailroadTheme {
+        &self.theme
+    }
+    /// A standalone SVG document of one rule, `None` if the grammar has no such rule
+    pub fn render_rule(&self, name: &str) -> Option<String> {
+        let rule = self.grammar.rule(name)?;
+        let mut out = String::new();
+        self.write_svg(&mut out, rule, true);
+        Some(out)
+    }
+    /// A standalone SVG document of every rule, with its name, in declaration order
+    pub fn render_all(&self) -> Vec<(String, String)> {
+        self.grammar
+            .rules
+            .iter()
+            .map(|rule| (rule.name.text.clone(), self.render_rule(&rule.name.text).unwrap_or_default()))
+            .collect()
+    }
+    /// A single HTML page with an index, the documentation, a diagram and the users of every rule, links always point
+    /// into the page
+    pub fn html_page(&self) -> String {
+        if self.link_prefix != "#" || !self.link_suffix.is_empty() {
+            return self.clone().with_links("#", "").html_page();
+        }
+        let title = match &self.grammar.name {
+            Some(name) => format!("{} grammar", name.text),
+            None => "Grammar".to_string(),
+        };
+        let theme = &self.theme;
+        let mut out = String::new();
+        out.push_str("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n");
+        writeln!(out, "<title>{}</title>", escape(&title)).unwrap();
+        out.push_str("<style>\n");
+        writeln!(
+            out,
+            "body{{margin:2em;background:{};color:{};font-family:sans-serif;}}a{{color:inherit;}}\
+             section{{margin-bottom:2em;}}h2 small{{font-weight:normal;color:{};}}code{{font-family:{};}}",
+            theme.background, theme.text, theme.comment, theme.font_family
+        )
+        .unwrap();
+        writeln!(out, "{}", theme.stylesheet()).unwrap();
+        out.push_str("</style>\n</head>\n<body>\n");
+        writeln!(out, "<h1>{}</h1>", escape(&title)).unwrap();
+        out.push_str("<nav>\n<ul>\n");
+        for rule in &self.grammar.rules {
+            let name = escape(&rule.name.text);
+            writeln!(out, "<li><a href=\"#{name}\">{name}</a></li>").unwrap();
+        }
+        out.push_str("</ul>\n</nav>\n");
+        for rule in &self.grammar.rules {
+            let name = escape(&rule.name.text);
+            writeln!(out, "<section id=\"{name}\">").unwrap();
+            writeln!(out, "<h2>{name} <small>{}</small></h2>", escape(&modifiers(rule))).unwrap();
+            for paragraph in rule.document.split("\n\n").filter(|p| !p.trim().is_empty()) {
+                writeln!(out, "<p>{}</p>", escape(paragraph)).unwrap();
+            }
+            self.write_svg(&mut out, rule, false);
+            let users: Vec<_> =
+                self.grammar.rules.iter().filter(|r| r.references().contains(&rule.name.text.as_str())).collect();
+            if !users.is_empty() {
+                out.push_str("<p>Used by: ");
+                for (index, user) in users.iter().enumerate() {
+                    if index > 0 {
+                        out.push_str(", ");
+                    }
+                    let user = escape(&user.name.text);
+                    write!(out, "<a href=\"#{user}\">{user}</a>").unwrap();
+                }
+                out.push_str("</p>\n");
+            }
+            out.push_str("</section>\n");
+        }
+        out.push_str("</body>\n</html>\n");
+        out
+    }
+
+    fn write_svg(&self, out: &mut String, rule: &Rule, standalone: bool) {
+        let node = self.build(&rule.body);
+        let size = self.measure(&node);
+        let width = size.width + 2 * MARGIN + 4 * ARC;
+        let height = size.up + size.down + 2 * MARGIN;
+        let y = MARGIN + size.up;
+        write!(out, "<svg class=\"railroad\" width=\"{width}\" height=\"{height}\" viewBox=\"0 0 {width} {height}\"").unwrap();
+        out.push_str(" xmlns=\"http://www.w3.org/2000/svg\" xmlns:xlink=\"http://www.w3.org/1999/xlink\">\n");
+        writeln!(out, "<title>{}</title>", escape(&rule.name.text)).unwrap();
+        if standalone {
+            writeln!(out, "<style type=\"text/css\">{}</style>", self.theme.stylesheet()).unwrap();
+        }
+        // start and end bars, and the lines joining them to the body
+        let start = MARGIN;
+        let end = MARGIN + 4 * ARC + size.width;
+        writeln!(out, "<path d=\"M {start} {} v {BOX} M {start} {y} h {}\"/>", y - BOX / 2, 2 * ARC).unwrap();
+        self.draw(out, &node, size, start + 2 * ARC, y);
+        writeln!(out, "<path d=\"M {} {y} h {} M {end} {} v {BOX}\"/>", end - 2 * ARC, 2 * ARC, y - BOX / 2).unwrap();
+        out.push_str("</svg>\n");
+    }
+
+    fn build(&self, expression: &Expression) -> Node {
+        match &expression.kind {
+            ExpressionKind::Text(text) => Node::Terminal { text: format!("{text:?}"), class: "string" },
+            ExpressionKind::Regex(regex) => Node::Terminal { text: format!("/{regex}/"), class: "regex" },
+            ExpressionKind::CharacterClass(class) => Node::Terminal { text: class.clone(), class: "character" },
+            ExpressionKind::Any => Node::Terminal { text: "ANY".to_string(), class: "character" },
+            ExpressionKind::Indentation(indentation) => Node::Terminal { text: indentation.to_string(), class: "character" },
+            ExpressionKind::External(path) => Node::Terminal { text: path.clone(), class: "regex" },
+            ExpressionKind::Reference(name) => {
+                let href = self.grammar.rule(name).map(|_| format!("{}{name}{}", self.link_prefix, self.link_suffix));
+                Node::NonTerminal { name: name.clone(), href }
+            }
+            ExpressionKind::Sequence(items) => Node::Sequence(items.iter().map(|e| self.build(e)).collect()),
+            ExpressionKind::Choice(items) => Node::Choice(items.iter().map(|e| self.build(e)).collect()),
+            ExpressionKind::Tagged { tag, body } => {
+                Node::Sequence(vec![self.build(body), Node::Comment(format!("#{}", tag.text))])
+            }
+            ExpressionKind::Capture { name, body } => {
+                Node::Sequence(vec![Node::Comment(format!("{}:", name.text)), self.build(body)])
+            }
+            ExpressionKind::Repeat { body, min, max } => {
+                let body = self.build(body);
+                match (min, max) {
+                    (0, Some(1)) => Node::Choice(vec![Node::Skip, body]),
+                    (0, None) => Node::Choice(vec![Node::Skip, Node::OneOrMore(Box::new(body))]),
+                    (1, None) => Node::OneOrMore(Box::new(body)),
+                    (min, Some(max)) => {
+                        Node::Sequence(vec![Node::OneOrMore(Box::new(body)), Node::Comment(format!("{min}..={max}"))])
+                    }
+                    (min, None) => Node::Sequence(vec![Node::OneOrMore(Box::new(body)), Node::Comment(format!("{min}.."))]),
+                }
+            }
+            ExpressionKind::Lookahead { positive, body } => {
+                let label = if *positive { "followed by" } else { "not followed by" };
+                Node::Sequence(vec![Node::Comment(label.to_string()), self.build(body)])
+            }
+        }
+    }
+
+    fn measure(&self, node: &Node) -> Size {
+        let char_width = self.theme.char_width();
+        match node {
+            Node::Terminal { text, .. } | Node::NonTerminal { name: text, .. } => {
+                Size { width: text.chars().count() * char_width + 2 * ARC, up: BOX / 2, down: BOX / 2 }
+            }
+            Node::Comment(text) => Size { width: text.chars().count() * char_width, up: BOX / 2, down: 0 },
+            Node::Skip => Size { width: 0, up: 0, down: 0 },
+            Node::Sequence(items) => {
+                let sizes: Vec<_> = items.iter().map(|n| self.measure(n)).collect();
+                Size {
+                    width: sizes.iter().map(|s| s.width).sum::<usize>() + ARC * sizes.len().saturating_sub(1),
+                    up: sizes.iter().map(|s| s.up).max().unwrap_or(0),
+                    down: sizes.iter().map(|s| s.down).max().unwrap_or(0),
+                }
+            }
+            Node::Choice(items) => {
+                let sizes: Vec<_> = items.iter().map(|n| self.measure(n)).collect();
+                let lines = branch_lines(&sizes);
+                let down = match (sizes.last(), lines.last()) {
+                    (Some(size), Some(line)) => line + size.down,
+                    _ => 0,
+                };
+                Size {
+                    width: sizes.iter().map(|s| s.width).max().unwrap_or(0) + 4 * ARC,
+                    up: sizes.first().map(|s| s.up).unwrap_or(0),
+                    down,
+                }
+            }
+            Node::OneOrMore(item) => {
+                let size = self.measure(item);
+                Size { width: size.width + 2 * ARC, up: size.up, down: loop_line(size) }
+            }
+        }
+    }
+
+    /// Draw `node` with its entry at `(x, y)`, the exit is at `(x + size.width, y)`
+    fn draw(&self, out: &mut String, node: &Node, size: Size, x: usize, y: usize) {
+        match node {
+            Node::Terminal { text, class } => {
+                writeln!(out, "<g class=\"{class}\">").unwrap();
+                writeln!(out, "<rect x=\"{x}\" y=\"{}\" width=\"{}\" height=\"{BOX}\" rx=\"{ARC}\"/>", y - BOX / 2, size.width)
+                    .unwrap();
+                writeln!(out, "<text x=\"{}\" y=\"{}\">{}</text>", x + size.width / 2, y + 5, escape(text)).unwrap();
+                out.push_str("</g>\n");
+            }
+            Node::NonTerminal { name, href } => {
+                if let Some(href) = href {
+                    writeln!(out, "<a class=\"link\" xlink:href=\"{}\">", escape(href)).unwrap();
+                }
+                let class = if href.is_some() { "nonterminal" } else { "nonterminal missing" };
+                writeln!(out, "<g class=\"{class}\">").unwrap();
+                writeln!(out, "<rect x=\"{x}\" y=\"{}\" width=\"{}\" height=\"{BOX}\"/>", y - BOX / 2, size.width).unwrap();
+                writeln!(out, "<text x=\"{}\" y=\"{}\">{}</text>", x + size.width / 2, y + 5, escape(name)).unwrap();
+                out.push_str("</g>\n");
+                if href.is_some() {
+                    out.push_str("</a>\n");
+                }
+            }
+            Node::Comment(text) => {
+                writeln!(out, "<path d=\"M {x} {y} h {}\"/>", size.width).unwrap();
+                writeln!(out, "<text class=\"comment\" x=\"{x}\" y=\"{}\">{}</text>", y - 4, escape(text)).unwrap();
+            }
+            Node::Skip => {}
+            Node::Sequence(items) => {
+                let mut x = x;
+                for (index, item) in items.iter().enumerate() {
+                    if index > 0 {
+                        writeln!(out, "<path d=\"M {x} {y} h {ARC}\"/>").unwrap();
+                        x += ARC;
+                    }
+                    let item_size = self.measure(item);
+                    self.draw(out, item, item_size, x, y);
+                    x += item_size.width;
+                }
+            }
+            Node::Choice(items) => {
+                let sizes: Vec<_> = items.iter().map(|n| self.measure(n)).collect();
+                let inner = size.width - 4 * ARC;
+                for ((item, item_size), line) in items.iter().zip(sizes.iter().copied()).zip(branch_lines(&sizes)) {
+                    let fill = inner - item_size.width;
+                    if line == 0 {
+                        writeln!(out, "<path d=\"M {x} {y} h {}\"/>", 2 * ARC).unwrap();
+                        writeln!(out, "<path d=\"M {} {y} h {}\"/>", x + 2 * ARC + item_size.width, fill + 2 * ARC).unwrap();
+                    }
+                    else {
+                        let drop = line - 2 * ARC;
+                        writeln!(
+                            out,
+                            "<path d=\"M {x} {y} a {ARC} {ARC} 0 0 1 {ARC} {ARC} v {drop} a {ARC} {ARC} 0 0 0 {ARC} {ARC}\"/>"
+                        )
+                        .unwrap();
+                        writeln!(
+                            out,
+                            "<path d=\"M {} {} h {fill} a {ARC} {ARC} 0 0 0 {ARC} -{ARC} v -{drop} a {ARC} {ARC} 0 0 1 {ARC} -{ARC}\"/>",
+                            x + 2 * ARC + item_size.width,
+                            y + line
+                        )
+                        .unwrap();
+                    }
+                    self.draw(out, item, item_size, x + 2 * ARC, y + line);
+                }
+            }
+            Node::OneOrMore(item) => {
+                let item_size = self.measure(item);
+                let after = x + ARC + item_size.width;
+                let line = size.down;
+                let drop = line - 2 * ARC;
+                writeln!(out, "<path d=\"M {x} {y} h {ARC} M {after} {y} h {ARC}\"/>").unwrap();
+                writeln!(
+                    out,
+                    "<path d=\"M {after} {y} a {ARC} {ARC} 0 0 1 {ARC} {ARC} v {drop} a {ARC} {ARC} 0 0 1 -{ARC} {ARC} \
+                     h -{} a {ARC} {ARC} 0 0 1 -{ARC} -{ARC} v -{drop} a {ARC} {ARC} 0 0 1 {ARC} -{ARC}\"/>",
+                    item_size.width
+                )
+                .unwrap();
+                self.draw(out, item, item_size, x + ARC, y);
+            }
+        }
+    }
+}
+
+/// Offset below the main line of every branch of a choice, the first branch is on the main line
+fn branch_lines(sizes: &[Size]) -> Vec<usize> {
+    let mut lines: Vec<usize> = Vec::with_capacity(sizes.len());
+    for (index, size) in sizes.iter().enumerate() {
+        let line = match index {
+            0 => 0,
+            _ => (lines[index - 1] + sizes[index - 1].down + GAP + size.up).max(lines[index - 1] + 2 * ARC),
+        };
+        lines.push(line);
+    }
+    lines
+}
+
+/// Offset below the main line of the line going back to the start of a loop
+fn loop_line(size: Size) -> usize {
+    (size.down + GAP).max(2 * ARC)
+}
+
+fn modifiers(rule: &Rule) -> String {
+    let mut words = vec![];
+    if rule.entry {
+        words.push("entry");
+    }
+    if rule.atomic {
+        words.push("atomic");
+    }
+    if rule.ignored {
+        words.push("ignore");
+    }
+    words.push(match rule.kind {
+        RuleKind::Class => "class",
+        RuleKind::Union => "union",
+        RuleKind::Operators => "operators",
+        RuleKind::External => "external",
+    });
+    words.join(" ")
+}
+
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
@@ -0,0 +1,714 @@
+use grammar_tools::{
+    CstNode, ExpressionKind, Fixity, GrammarFile, Indentation, Interpreter, LintKind, ParseError, RailroadRenderer,
+    RailroadTheme, RuleKind,
+};
+
+const JSON5: &str = include_str!("../../build_by_dep/grammars/json5.ygg");
+
+/// `name = value` lines whose values use the rules of [`JSON5`]
+const SETTINGS: &str = include_str!("../../build_by_dep/grammars/settings.ygg");
+
+/// A configuration format nested by indentation
+const OUTLINE: &str = include_str!("../../outline_config/grammars/outline.ygg");
+
+/// Expressions written in the natural left-recursive style
+const ARITHMETIC: &str = include_str!("arithmetic.ygg");
+
+/// Expressions written as an operator table
+const CALC: &str = include_str!("calc.ygg");
+
+#[test]
+fn ready() {
+    println!("it works!")
+}
+
+#[test]
+fn test_json5_grammar() {
+    let grammar = GrammarFile::parse(JSON5).unwrap();
+    assert_eq!(grammar.name.as_ref().map(|n| n.text.as_str()), Some("Json5"));
+    assert_eq!(grammar.property("file"), Some("*.json5"));
+    let names: Vec<_> = grammar.rules.iter().map(|r| r.name.text.as_str()).collect();
+    assert_eq!(
+        names,
+        [
+            "Value",
+            "Object",
+            "ObjectPair",
+            "ObjectKey",
+            "Array",
+            "String",
+            "StringRaw",
+            "StringText",
+            "StringEscape",
+            "Number",
+            "Integer",
+            "Identifier",
+            "Boolean",
+            "Null",
+            "WhiteSpace"
+        ]
+    );
+    let value = grammar.rule("Value").unwrap();
+    assert!(value.entry && value.kind == RuleKind::Union);
+    let tags: Vec<_> = value.branch_tags().iter().map(|t| t.text.as_str()).collect();
+    assert_eq!(tags, ["Object", "Array", "String", "Number", "Boolean", "Null"]);
+    assert_eq!(&JSON5[value.span.clone()][..11], "entry union");
+    let string = grammar.rule("String").unwrap();
+    assert!(string.atomic);
+    assert_eq!(string.annotations[0].name.text, "style");
+    assert_eq!(string.annotations[0].arguments, ["string"]);
+    assert_eq!(&JSON5[string.span.clone()][..14], "@style(string)");
+    assert_eq!(grammar.rule("Array").unwrap().annotation_arguments("derive"), ["Default"]);
+    let annotated =
+        GrammarFile::parse("@derive(PartialEq, Eq)\n@attr(non_exhaustive)\n@derive(Hash)\nclass A { 'a' }").unwrap();
+    assert_eq!(annotated.rules[0].annotation_arguments("derive"), ["PartialEq", "Eq", "Hash"]);
+    assert_eq!(annotated.rules[0].annotation_arguments("attr"), ["non_exhaustive"]);
+    assert_eq!(grammar.rule("StringRaw").unwrap().redirect.as_ref().map(|r| r.text.as_str()), Some("StringText"));
+    assert!(grammar.rule("WhiteSpace").unwrap().ignored);
+    assert_eq!(grammar.rule("ObjectPair").unwrap().references(), ["ObjectKey", "Value"]);
+    let escape = grammar.rule("StringEscape").unwrap();
+    match &escape.body.kind {
+        ExpressionKind::Sequence(items) => {
+            assert_eq!(items[0].kind, ExpressionKind::Text("\\".to_string()));
+            assert!(
+                matches!(&items[1].kind, ExpressionKind::Capture { name, body } if name.text == "c" && body.kind == ExpressionKind::Any)
+            );
+        }
+        other => panic!("unexpected body {other:?}"),
+    }
+}
+
+#[test]
+fn test_spans_and_documents() {
+    let text = "grammar Demo {}\n\n/// A digit\n/// from 0 to 9\natomic class Digit { [0-9]+ }\n";
+    let grammar = GrammarFile::parse(text).unwrap();
+    let digit = &grammar.rules[0];
+    assert_eq!(digit.document, "A digit\nfrom 0 to 9");
+    assert_eq!(&text[digit.span.clone()], "atomic class Digit { [0-9]+ }");
+    assert_eq!(&text[digit.name.span.clone()], "Digit");
+    assert_eq!(&text[digit.body.span.clone()], "[0-9]+");
+    assert!(matches!(&digit.body.kind, ExpressionKind::Repeat { min: 1, max: None, .. }));
+}
+
+#[test]
+fn test_errors() {
+    let cases = [
+        ("class A { 'a' ", "expected `|` or `}`, found the end of file", (1, 15)),
+        ("class A {}", "rule `A` has an empty body", (1, 9)),
+        (
+            "klass A { 'a' }",
+            "expected `class`, `union`, `operators`, `external` or one of `entry`, `atomic`, `ignore`, found `klass`",
+            (1, 1),
+        ),
+        ("class A { 'a' }\nclass A { 'b' }", "rule `A` is already defined at 6..7", (2, 7)),
+        ("class A { /a }", "unterminated regex", (1, 11)),
+        ("class A { 'a' $ }", "unexpected character `$`", (1, 15)),
+    ];
+    for (text, message, position) in cases {
+        let error = GrammarFile::parse(text).unwrap_err();
+        assert_eq!(error.message, message, "{text}");
+        assert_eq!(error.line_column(text), position, "{text}");
+    }
+}
+
+#[test]
+fn test_leading_bar() {
+    // the single character strings of `projects/build_by_cli`
+    let text = "atomic class String {\n    | \"'\" \"'\"\n    | \"'\" ANY \"'\"\n}";
+    let grammar = GrammarFile::parse(text).unwrap();
+    assert!(matches!(&grammar.rules[0].body.kind, ExpressionKind::Choice(branches) if branches.len() == 2));
+    assert!(Interpreter::new(&grammar).unwrap().parse("'a'", "String").is_ok());
+}
+
+#[test]
+fn test_railroad_rule() {
+    let grammar = GrammarFile::parse(JSON5).unwrap();
+    let renderer = RailroadRenderer::new(&grammar);
+    let svg = renderer.render_rule("ObjectPair").unwrap();
+    assert!(svg.starts_with("<svg class=\"railroad\""));
+    assert!(svg.contains("<style type=\"text/css\">svg.railroad{"));
+    assert!(svg.contains("xlink:href=\"#ObjectKey\""));
+    assert!(svg.contains("xlink:href=\"#Value\""));
+    assert!(svg.contains("<g class=\"string\">"));
+    assert_eq!(svg.matches("<g").count(), svg.matches("</g>").count());
+    assert_eq!(svg.matches("<a ").count(), svg.matches("</a>").count());
+    let value = renderer.render_rule("Value").unwrap();
+    assert!(value.contains(">#Object</text>"));
+    let escape = renderer.render_rule("StringEscape").unwrap();
+    assert!(escape.contains(">c:</text>") && escape.contains(">&quot;\\\\&quot;</text>"));
+    assert!(renderer.render_rule("Missing").is_none());
+    assert_eq!(renderer.render_all().len(), grammar.rules.len());
+}
+
+#[test]
+fn test_railroad_options() {
+    let grammar = GrammarFile::parse("grammar Demo {}\nclass A { B+ C? }\nclass B { 'b' }").unwrap();
+    let theme = RailroadTheme { stroke: "teal".to_string(), ..RailroadTheme::dark() };
+    let renderer = RailroadRenderer::new(&grammar).with_theme(theme).with_links("rules/", ".svg");
+    let svg = renderer.render_rule("A").unwrap();
+    assert!(svg.contains("stroke:teal"));
+    assert!(svg.contains("xlink:href=\"rules/B.svg\""));
+    // undefined rules are drawn without a link
+    assert!(svg.contains("<g class=\"nonterminal missing\">"));
+    assert!(!svg.contains("rules/C.svg"));
+}
+
+#[test]
+fn test_railroad_html() {
+    let grammar = GrammarFile::parse(JSON5).unwrap();
+    let html = RailroadRenderer::new(&grammar).html_page();
+    assert!(html.starts_with("<!DOCTYPE html>"));
+    assert!(html.contains("<title>Json5 grammar</title>"));
+    for rule in &grammar.rules {
+        assert!(html.contains(&format!("<section id=\"{}\">", rule.name.text)));
+        assert!(html.contains(&format!("<li><a href=\"#{0}\">{0}</a></li>", rule.name.text)));
+    }
+    assert!(html.contains("<h2>Value <small>entry union</small></h2>"));
+    assert!(html.contains("Used by: <a href=\"#ObjectPair\">ObjectPair</a>, <a href=\"#Array\">Array</a>"));
+    // one inline stylesheet for the whole page
+    assert_eq!(html.matches("<style").count(), 1);
+}
+
+/// `Rule#tag[start..end](children)`, literals as their text
+fn outline(node: &CstNode, input: &str) -> String {
+    let mut out = match node.rule.as_str() {
+        "IgnoreText" | "IgnoreRegex" => format!("{:?}", node.text(input)),
+        rule => rule.to_string(),
+    };
+    if let Some(tag) = &node.tag {
+        out.push('#');
+        out.push_str(tag);
+    }
+    if !node.children.is_empty() {
+        let children: Vec<_> = node.children.iter().map(|child| outline(child, input)).collect();
+        out.push_str(&format!("({})", children.join(" ")));
+    }
+    out
+}
+
+#[test]
+fn test_interpreter_tree() {
+    let grammar = GrammarFile::parse(JSON5).unwrap();
+    let interpreter = Interpreter::new(&grammar).unwrap();
+    let input = "{a: [1, 'x']}";
+    let tree = interpreter.parse(input, "Value").unwrap();
+    assert_eq!(tree.span, 0..input.len());
+    assert_eq!(
+        outline(&tree, input),
+        concat!(
+            r##"Value(Object#object("{" ObjectPair#object_pair(ObjectKey#object_key(Identifier#identifier) ":" "##,
+            r##"WhiteSpace Value#value(Array#array("[" Value#value(Number#number) "," WhiteSpace "##,
+            r##"Value#value(String#string("'" StringRaw#string_raw("x") "'"#string_0)) "]"))) "}"))"##,
+        )
+    );
+    let escape = interpreter.parse(r#""\n""#, "String").unwrap();
+    assert_eq!(
+        outline(&escape, r#""\n""#),
+        r##"String("\"" StringText#string_text(StringEscape#string_escape("\\" "n"#c)) "\""#string_1)"##
+    );
+    let boolean = interpreter.parse("false", "Boolean").unwrap();
+    assert_eq!(outline(&boolean, "false"), r##"Boolean("false"#boolean_1)"##);
+    // the generated parser neither trims nor requires the whole input
+    assert_eq!(interpreter.parse("1 trailing", "Value").unwrap().span, 0..1);
+    assert!(interpreter.parse(" 1", "Value").is_err());
+}
+
+#[test]
+fn test_interpreter_errors() {
+    let grammar = GrammarFile::parse(JSON5).unwrap();
+    let interpreter = Interpreter::new(&grammar).unwrap();
+    match interpreter.parse("[1, }", "Value").unwrap_err() {
+        ParseError::Mismatch { rule, offset, expected } => {
+            assert_eq!((rule.as_str(), offset), ("Value", 4));
+            assert!(expected.contains(&"\"]\"".to_string()), "{expected:?}");
+            assert!(expected.contains(&"\"null\"".to_string()), "{expected:?}");
+        }
+        other => panic!("unexpected error {other}"),
+    }
+    assert_eq!(interpreter.parse("1", "Missing").unwrap_err(), ParseError::UnknownRule { rule: "Missing".to_string() });
+    let deep = "[".repeat(100);
+    let shallow = Interpreter::new(&grammar).unwrap().with_max_depth(32);
+    assert!(matches!(shallow.parse(&deep, "Value"), Err(ParseError::TooDeep { depth: 32, .. })));
+    let undefined = GrammarFile::parse("class A { B }").unwrap();
+    assert_eq!(Interpreter::new(&undefined).unwrap_err().message, "rule `B` is not defined");
+    let regex = GrammarFile::parse("class A { /(/ }").unwrap();
+    assert!(Interpreter::new(&regex).unwrap_err().message.starts_with("invalid regex"));
+}
+
+/// `kind text: message` of every lint
+fn lints(text: &str) -> Vec<String> {
+    let grammar = GrammarFile::parse(text).unwrap();
+    grammar.lint().iter().map(|lint| format!("{} {}: {}", lint.kind.as_str(), &text[lint.span.clone()], lint.message)).collect()
+}
+
+#[test]
+fn test_lint_json5() {
+    assert_eq!(lints(JSON5), Vec::<String>::new());
+    // an integer branch after the number branch would never be taken for plain numbers
+    let text = JSON5.replace("| Number  #Number", "| Number  #Number\n    | Integer #Integer");
+    assert_eq!(lints(&text), ["choice-shadowing Integer #Integer: branch 4 also matches \"0\", so branch 5 never sees it"]);
+}
+
+#[test]
+fn test_lint_choices() {
+    let text = r#"
+entry union Token {
+    | Identifier
+    | Keyword
+    | 'in'
+    | 'int'
+    | Number
+    | Integer
+    | ''?
+    | 'x'
+}
+class Identifier { /[a-z]+/ }
+class Keyword { 'true' | 'false' | 'true' }
+class Number { /[+-]?[0-9]+/ }
+class Integer { /[0-9]+/ }
+"#;
+    assert_eq!(
+        lints(text),
+        [
+            "choice-shadowing Keyword: branch 1 also matches \"true\", so branch 2 never sees it",
+            "choice-shadowing 'in': branch 1 also matches \"in\", so branch 3 never sees it",
+            "unreachable-alternative 'int': branch 4 is never taken, branch 3 matches its prefix \"in\" first",
+            "choice-shadowing Integer: branch 5 also matches \"0\", so branch 6 never sees it",
+            "unreachable-alternative 'x': branch 8 is never taken, branch 7 can match nothing and always succeeds",
+            "unreachable-alternative 'true': branch 3 is never taken, it is the same as branch 1",
+        ]
+    );
+    let grammar = GrammarFile::parse(text).unwrap();
+    let lint = &grammar.lint()[0];
+    assert_eq!(lint.kind, LintKind::ChoiceShadowing);
+    assert!(!lint.kind.is_error());
+    assert_eq!(&text[lint.related.clone().unwrap()], "Identifier");
+}
+
+#[test]
+fn test_lint_rules() {
+    let text =
+        "entry class A { B* (C | D)+ }\nclass B { /a*/ }\nclass C { 'c' }\nclass Unused { C }\nignore class Space { ' ' }";
+    assert_eq!(
+        lints(text),
+        [
+            "empty-loop B*: the repeated expression can match nothing, so the loop never ends",
+            "undefined-reference D: rule `D` is not defined",
+            "unused-rule Unused: rule `Unused` is never used",
+        ]
+    );
+    let grammar = GrammarFile::parse(text).unwrap();
+    assert!(grammar.lint().iter().filter(|lint| lint.kind.is_error()).count() == 2);
+    // without an `entry` rule, parsing starts at the first rule
+    assert_eq!(lints("class A { B }\nclass B { 'b' }"), Vec::<String>::new());
+    assert_eq!(
+        lints("class A -> Text { 'a' }"),
+        ["undefined-reference Text: rule `A` redirects to `Text`, which is not defined"]
+    );
+    assert_eq!(
+        lints("entry class A { B | C }\nclass B -> C { 'b' D* }\nclass C { D }\nclass D { 'd' }"),
+        ["redirect-mismatch C: rule `B` redirects to `C`, whose node has other fields: `d*` against `d`"]
+    );
+    assert_eq!(lints("entry class A { B | C }\nclass B -> C { 'b' D }\nclass C { D }\nclass D { 'd' }"), Vec::<String>::new());
+}
+
+#[test]
+fn test_left_recursion() {
+    let grammar = GrammarFile::parse(ARITHMETIC).unwrap();
+    let cycles = grammar.left_recursion();
+    let chains: Vec<_> = cycles.iter().map(|cycle| cycle.to_string()).collect();
+    assert_eq!(chains, ["Sum -> Sum", "Product -> Product"]);
+    assert_eq!(&ARITHMETIC[cycles[0].span.clone()], "Sum");
+    assert_eq!(cycles[1].leader(), "Product");
+    // indirect cycles through optional prefixes, reported once from the rule declared first
+    let indirect = GrammarFile::parse("class A { B 'a' | 'a' }\nclass B { C? A 'b' }\nclass C { 'c' }").unwrap();
+    let chains: Vec<_> = indirect.left_recursion().iter().map(|cycle| cycle.to_string()).collect();
+    assert_eq!(chains, ["A -> B -> A"]);
+    assert!(GrammarFile::parse(JSON5).unwrap().left_recursion().is_empty());
+    assert_eq!(
+        lints(ARITHMETIC),
+        [
+            "left-recursion Sum: rule `Sum` is left recursive: Sum -> Sum",
+            "left-recursion Product: rule `Product` is left recursive: Product -> Product",
+        ]
+    );
+}
+
+#[test]
+fn test_seed_growing() {
+    // recursing to the default depth takes more stack than the 2MB of a test thread
+    std::thread::Builder::new().stack_size(16 << 20).spawn(seed_growing).unwrap().join().unwrap();
+}
+
+fn seed_growing() {
+    let grammar = GrammarFile::parse(ARITHMETIC).unwrap();
+    // without seed growing the interpreter recurses like the generated parser
+    let plain = Interpreter::new(&grammar).unwrap();
+    assert!(matches!(plain.parse("1 + 2", "Sum"), Err(ParseError::TooDeep { .. })));
+    let interpreter = Interpreter::new(&grammar).unwrap().with_left_recursion(true);
+    let input = "1 - 2 - 3*4";
+    let tree = interpreter.parse(input, "Sum").unwrap();
+    assert_eq!(tree.span, 0..input.len());
+    assert_eq!(
+        outline(&tree, input),
+        concat!(
+            r##"Sum(Sum#sum(Sum#sum(Product#product(Atom#atom(Number#number))) Space "-" Space "##,
+            r##"Product#sum_1(Atom#atom(Number#number))) Space "-" Space "##,
+            r##"Product#sum_1(Product#product(Atom#atom(Number#number)) "*" Atom#product_0(Number#number)))"##,
+        )
+    );
+    let group = interpreter.parse("(1+2)*3", "Sum").unwrap();
+    assert_eq!(group.span, 0..7);
+    assert!(interpreter.parse("+1", "Sum").is_err());
+}
+
+#[test]
+fn test_operator_table() {
+    let grammar = GrammarFile::parse(CALC).unwrap();
+    let expr = grammar.rule("Expr").unwrap();
+    assert!(expr.entry && expr.kind == RuleKind::Operators);
+    assert_eq!(expr.document, "An arithmetic expression");
+    let table = expr.operators.as_ref().unwrap();
+    assert_eq!(table.operand.text, "Atom");
+    let levels: Vec<_> = table.levels.iter().map(|level| (level.precedence, level.fixity, level.operators.len())).collect();
+    assert_eq!(
+        levels,
+        [
+            (1, Fixity::InfixLeft, 2),
+            (2, Fixity::InfixLeft, 2),
+            (3, Fixity::Prefix, 1),
+            (4, Fixity::InfixRight, 1),
+            (5, Fixity::Postfix, 1)
+        ]
+    );
+    assert_eq!(&CALC[table.levels[3].span.clone()], "infix right '^' #Power");
+    let (level, operator) = table.operator("sub").unwrap();
+    assert_eq!((operator.text.as_str(), level.binding_power()), ("-", (2, 3)));
+    assert_eq!(table.operator("Power").unwrap().0.binding_power(), (9, 8));
+    assert_eq!(table.operator("negative").unwrap().0.fixity, Fixity::Prefix);
+    assert_eq!(
+        expr.body.to_string(),
+        "(negative:'-')* Atom (factorial:'!')* ((add:'+' | sub:'-' | mul:'*' | div:'/' | power:'^') (negative:'-')* Atom (factorial:'!')*)*"
+    );
+    // code generators that only know `class` and `union` get the matching class
+    let lowered = grammar.lower(CALC);
+    assert!(
+        lowered.contains(&format!("/// An arithmetic expression\nentry class Expr {{\n    {}\n}}\nunion Atom {{", expr.body))
+    );
+    let reparsed = GrammarFile::parse(&lowered).unwrap();
+    assert_eq!(reparsed.rule("Expr").unwrap().kind, RuleKind::Class);
+    assert_eq!(reparsed.rule("Expr").unwrap().body.to_string(), expr.body.to_string());
+    assert_eq!(grammar.lint(), []);
+    let errors = [
+        ("operators E { Atom }", "expected `operand`, found `Atom`"),
+        ("operators E { operand A }", "rule `E` has no operators"),
+        ("operators E { operand A infix '+' #Add prefix '+' #Add }", "operator tag `Add` is already used at 35..38"),
+        ("operators E { operand A suffix '!' #Bang }", "expected `prefix`, `infix` or `postfix`, found `suffix`"),
+        ("operators E { operand A infix '+' }", "expected `#`, found `}`"),
+    ];
+    for (text, message) in errors {
+        assert_eq!(GrammarFile::parse(text).unwrap_err().message, message, "{text}");
+    }
+}
+
+#[test]
+fn test_operator_interpreter() {
+    let grammar = GrammarFile::parse(CALC).unwrap();
+    let interpreter = Interpreter::new(&grammar).unwrap();
+    let input = "-1 + 2!^3";
+    let tree = interpreter.parse(input, "Expr").unwrap();
+    assert_eq!(tree.span, 0..input.len());
+    // the tree stays flat, the typed node folds it by binding power
+    assert_eq!(
+        outline(&tree, input),
+        concat!(
+            r##"Expr("-"#negative Atom#atom(Number#number) Space "+"#add Space "##,
+            r##"Atom#atom(Number#number) "!"#factorial "^"#power Atom#atom(Number#number))"##
+        )
+    );
+}
+
+#[test]
+fn test_lookahead() {
+    let grammar = GrammarFile::parse("class A { !'x' ANY &('a' | 'b') !B* }\nclass B { 'b' }").unwrap();
+    let body = &grammar.rules[0].body;
+    assert_eq!(body.to_string(), "!'x' ANY &('a' | 'b') !B*");
+    let ExpressionKind::Sequence(items) = &body.kind
+    else {
+        panic!("{body:?}")
+    };
+    assert!(
+        matches!(&items[3].kind, ExpressionKind::Lookahead { positive: false, body } if matches!(body.kind, ExpressionKind::Repeat { .. }))
+    );
+    assert_eq!(items[3].span, 32..35);
+
+    let grammar = GrammarFile::parse(JSON5).unwrap();
+    let interpreter = Interpreter::new(&grammar).unwrap();
+    // reserved words are identifiers, keywords are whole words
+    assert_eq!(interpreter.parse("null", "Identifier").unwrap().span, 0..4);
+    assert_eq!(interpreter.parse("null", "Null").unwrap().span, 0..4);
+    assert!(interpreter.parse("nullable", "Null").is_err());
+    let input = r"'can\'t'";
+    let string = interpreter.parse(input, "String").unwrap();
+    assert_eq!(
+        outline(&string, input),
+        r##"String("'" StringRaw#string_raw("c" "a" "n" StringEscape#string_escape("\\" "'"#c) "t") "'"#string_0)"##
+    );
+    match interpreter.parse("trueish", "Boolean").unwrap_err() {
+        ParseError::Mismatch { offset, expected, .. } => {
+            assert_eq!((offset, expected), (4, vec!["![\\p{XID_continue}]".to_string()]))
+        }
+        e => panic!("{e}"),
+    }
+}
+
+#[test]
+fn test_lookahead_lowering() {
+    let grammar = GrammarFile::parse(JSON5).unwrap();
+    let lookaheads: Vec<_> = grammar.lookaheads().iter().map(|(rule, e)| format!("{} {e}", rule.name.text)).collect();
+    let keyword = |rule: &str| format!("{rule} ![\\p{{XID_continue}}]");
+    assert_eq!(lookaheads, ["StringRaw !\"'\"".to_string(), keyword("Boolean"), keyword("Boolean"), keyword("Null")]);
+    let lowered = grammar.lower(JSON5);
+    assert!(lowered.contains("(StringEscape | '__ygg_lookahead_0__' ANY)*"));
+    assert!(lowered.contains("\"null\" '__ygg_lookahead_3__'"));
+    let lowered = GrammarFile::parse(&lowered).unwrap();
+    assert!(lowered.lookaheads().is_empty());
+    assert_eq!(lowered.rules.len(), grammar.rules.len());
+
+    assert_eq!(
+        lints("class A { (!'a')* 'b' }"),
+        ["empty-loop (!'a')*: the repeated expression can match nothing, so the loop never ends"]
+    );
+    // a lookahead branch can fail, the branches after it are still reachable
+    assert!(lints("class A { !'a' | 'b' }").iter().all(|lint| !lint.starts_with("unreachable")));
+}
+
+const COMMENTED: &str = "class List { Item (',' Item)* }
+class Item { /[a-z]+/ }
+/// `/* */` comments nest
+ignore external Comment {
+    crate::scanners::nested_comment
+}
+ignore class Space { ' ' }";
+
+/// `/* a /* b */ c */`
+fn nested_comment(input: &str) -> Option<usize> {
+    let (mut depth, mut offset) = (0, 0);
+    loop {
+        let rest = &input[offset..];
+        if rest.starts_with("/*") {
+            (depth, offset) = (depth + 1, offset + 2);
+        }
+        else if depth > 0 && rest.starts_with("*/") {
+            (depth, offset) = (depth - 1, offset + 2);
+            if depth == 0 {
+                return Some(offset);
+            }
+        }
+        else if depth == 0 {
+            return None;
+        }
+        else {
+            offset += rest.chars().next()?.len_utf8();
+        }
+    }
+}
+
+#[test]
+fn test_external_rule() {
+    let grammar = GrammarFile::parse(COMMENTED).unwrap();
+    let comment = grammar.rule("Comment").unwrap();
+    assert_eq!((comment.kind, comment.ignored), (RuleKind::External, true));
+    assert_eq!(comment.body.to_string(), "crate::scanners::nested_comment");
+    assert_eq!(&COMMENTED[comment.body.span.clone()], "crate::scanners::nested_comment");
+    assert!(grammar.lint().is_empty());
+    assert!(grammar.lower(COMMENTED).contains("ignore class Comment {\n    '__ygg_external_Comment__'\n}"));
+
+    let input = "a /* x /* y */ */, b";
+    let interpreter = Interpreter::new(&grammar).unwrap();
+    // without a scanner the comment never matches, the list ends after the skipped space
+    assert_eq!(interpreter.parse(input, "List").unwrap().span, 0..2);
+    let interpreter = interpreter.with_scanner("Comment", nested_comment);
+    let list = interpreter.parse(input, "List").unwrap();
+    assert_eq!(list.span, 0..input.len());
+    assert_eq!(outline(&list, input), r#"List(Item#item Space Comment "," Space Item#item)"#);
+
+    let error = GrammarFile::parse("external A { }").unwrap_err();
+    assert_eq!(error.message, "expected the path of a Rust function, found `}`");
+    assert!(GrammarFile::parse("external A { a::b c }").is_err());
+}
+
+/// `key` of every entry and `-` of every item, indented by depth
+fn outline_keys(node: &CstNode, input: &str, depth: usize, out: &mut Vec<String>) {
+    let depth = match node.rule.as_str() {
+        "Key" => {
+            out.push(format!("{}{}", "  ".repeat(depth), node.text(input)));
+            depth
+        }
+        "Item" => {
+            out.push(format!("{}-", "  ".repeat(depth)));
+            depth
+        }
+        "Block" => depth + 1,
+        _ => depth,
+    };
+    node.children.iter().for_each(|child| outline_keys(child, input, depth, out));
+}
+
+#[test]
+fn test_indentation() {
+    let grammar = GrammarFile::parse(OUTLINE).unwrap();
+    let block = grammar.rule("Block").unwrap();
+    assert_eq!(block.body.to_string(), "INDENT Line (SAMEDENT Line)* DEDENT");
+    assert!(
+        matches!(block.body.kind, ExpressionKind::Sequence(ref items) if items[0].kind == ExpressionKind::Indentation(Indentation::Indent))
+    );
+    assert!(grammar.lint().is_empty());
+    assert!(grammar.uses_indentation());
+    assert!(!GrammarFile::parse(JSON5).unwrap().uses_indentation());
+
+    let interpreter = Interpreter::new(&grammar).unwrap();
+    let input = "server:\n  host: local\n\n  ports:\n    - 80\n    - 443\n  tls: off\nname: demo\n";
+    let tree = interpreter.parse(input, "Outline").unwrap();
+    assert_eq!(tree.span, 0..input.len());
+    let mut keys = vec![];
+    outline_keys(&tree, input, 0, &mut keys);
+    assert_eq!(keys, ["server", "  host", "  ports", "    -", "    -", "  tls", "name"]);
+    // a line indented between two blocks belongs to neither
+    let input = "a:\n    b: 1\n  c: 2";
+    assert_eq!(interpreter.parse(input, "Outline").unwrap().span, 0..14);
+    // a block that fails gives its indentation back, `a` falls through to a scalar
+    let error = interpreter.parse("a:\n  b\n", "Outline").unwrap_err();
+    assert!(
+        matches!(&error, ParseError::Mismatch { offset: 6, expected, .. } if expected.contains(&"\":\"".to_string())),
+        "{error:?}"
+    );
+    let input = "a: 1\nb:\n  c: 2";
+    assert_eq!(interpreter.parse(input, "Outline").unwrap().span, 0..input.len());
+
+    let lowered = grammar.lower(OUTLINE);
+    assert!(lowered.contains("'__ygg_indent__' Line ('__ygg_samedent__' Line)* '__ygg_dedent__'"));
+    assert!(!GrammarFile::parse(&lowered).unwrap().uses_indentation());
+    // `DEDENT` matches nothing but can fail, the branches after it are still reachable
+    assert_eq!(lints("class A { 'a' (DEDENT | 'b') }"), Vec::<String>::new());
+}
+
+#[test]
+fn test_grammar_imports() {
+    let mut grammar = GrammarFile::parse(SETTINGS).unwrap();
+    let import = &grammar.imports[0];
+    assert_eq!((import.path.as_str(), import.namespace.text.as_str()), ("json5.ygg", "json5"));
+    assert_eq!(&SETTINGS[import.span.clone()], "import \"json5.ygg\"");
+    assert_eq!(grammar.rule("Entry").unwrap().body.to_string(), "Key '=' value:json5::Value");
+    let lints: Vec<_> = grammar.lint().into_iter().map(|lint| lint.message).collect();
+    assert_eq!(lints, ["rule `json5::Value` is imported from `json5.ygg`, which is not linked"]);
+
+    grammar.link("json5", &GrammarFile::parse(JSON5).unwrap()).unwrap();
+    let value = grammar.rule("json5::Value").unwrap();
+    assert!(value.is_imported() && !value.entry);
+    assert_eq!(value.span, grammar.imports[0].span);
+    assert_eq!(
+        grammar.rule("json5::Object").unwrap().body.to_string(),
+        "'{' (json5::ObjectPair (',' json5::ObjectPair)* ','?)? '}'"
+    );
+    // only the rules reached from `json5::Value` are copied, whitespace is the one of the importing grammar
+    assert!(grammar.rule("json5::WhiteSpace").is_none());
+    assert!(grammar.lint().is_empty());
+
+    let interpreter = Interpreter::new(&grammar).unwrap();
+    let input = "retries = 3\nserver = { host: 'local', ports: [80, 443] }\n";
+    let tree = interpreter.parse(input, "Settings").unwrap();
+    assert_eq!(tree.span, 0..input.len());
+    let entry = interpreter.parse("retries = 3", "Entry").unwrap();
+    // tags of imported rules are the ones of the lowered names, as in the generated parser
+    assert_eq!(
+        outline(&entry, "retries = 3"),
+        r#"Entry(Key#key WhiteSpace "=" WhiteSpace json5::Value#value(json5::Number#json5_number))"#
+    );
+    // imported rules skip the whitespace of the importing grammar, which has no line breaks
+    let error = interpreter.parse("a = [1,\n2]", "Settings").unwrap_err();
+    assert!(matches!(error, ParseError::Mismatch { offset: 7, .. }), "{error:?}");
+
+    let lowered = grammar.lower(SETTINGS);
+    assert!(!lowered.contains("import"));
+    assert!(lowered.contains("Key '=' value:Json5Value"));
+    assert!(lowered.contains("atomic class Json5Null {\n    'null' '__ygg_lookahead_3__'\n}"));
+    let lowered = GrammarFile::parse(&lowered).unwrap();
+    assert_eq!(lowered.rules.len(), grammar.rules.len());
+    assert!(lowered.lookaheads().is_empty() && lowered.lint().is_empty());
+
+    let json5 = GrammarFile::parse(JSON5).unwrap();
+    let error = GrammarFile::parse("class A { other::B }").unwrap().link("other", &json5).unwrap_err();
+    assert_eq!((error.message.as_str(), error.span), ("namespace `other` is not imported", 10..18));
+    let mut missing = GrammarFile::parse("import 'grammars/json5.ygg' as js\nclass A { js::Missing }").unwrap();
+    let error = missing.link("js", &json5).unwrap_err();
+    assert_eq!(error.message, "rule `Missing` is not defined in `grammars/json5.ygg`");
+    let mut scanner = GrammarFile::parse("import 'c.ygg'\nclass A { c::Comment }").unwrap();
+    let error = scanner.link("c", &GrammarFile::parse(COMMENTED).unwrap()).unwrap_err();
+    assert_eq!(error.message, "rule `c::Comment` is an external scanner, which cannot be imported");
+    let error = GrammarFile::parse("import 'a.ygg'\nimport 'b/a.ygg'").unwrap_err();
+    assert_eq!(error.message, "namespace `a` is already imported at 0..14");
+    assert!(GrammarFile::parse("import '1.ygg'").is_err());
+}
+
+#[test]
+fn test_grammar_macros() {
+    let grammar = GrammarFile::parse(JSON5).unwrap();
+    let separated = grammar.macro_named("separated").unwrap();
+    assert_eq!(separated.document, "`item`s separated by `separator`, possibly none");
+    assert_eq!(JSON5[separated.body.clone()].trim(), "(item (separator item)* if trailing { separator? })?");
+    let flags: Vec<_> = separated.parameters.iter().map(|p| (p.name.text.as_str(), p.is_flag())).collect();
+    assert_eq!(flags, [("item", false), ("separator", false), ("trailing", true)]);
+    let object = grammar.rule("Object").unwrap();
+    assert_eq!(object.body.to_string(), "'{' (ObjectPair (',' ObjectPair)* ','?)? '}'");
+    let call = &object.expansions[0];
+    assert_eq!(&JSON5[call.clone()], "@separated(ObjectPair, trailing: true)");
+    // arguments keep their span, the rest of the expansion takes the one of the call
+    let ExpressionKind::Sequence(items) = &object.body.kind
+    else {
+        panic!("expected a sequence")
+    };
+    let mut spans = vec![];
+    items[1].visit(&mut |e| spans.push(&JSON5[e.span.clone()]));
+    assert!(spans.iter().all(|span| *span == "ObjectPair" || span == &&JSON5[call.clone()]));
+
+    let lowered = grammar.lower(JSON5);
+    assert!(lowered.contains("'{' (ObjectPair (',' ObjectPair)* ','?)? '}'"));
+    assert!(lowered.contains("'[' (Value (',' Value)* ','?)? ']'"));
+    let lowered = GrammarFile::parse(&lowered).unwrap();
+    assert!(lowered.macros.is_empty() && lowered.rules.iter().all(|rule| rule.expansions.is_empty()));
+    assert_eq!(lowered.rules.len(), grammar.rules.len());
+
+    // macros may be declared after their calls and call each other
+    let text = "class Path { @separated(Name, '.') }\nclass Name { /[a-z]+/ }\nmacro list(item) { @separated(item) }\nmacro separated(item, separator: ',', trailing: false) {\n    item (separator item)* if trailing { separator? }\n}\nclass List { '[' @list(Name) ']' }";
+    let grammar = GrammarFile::parse(text).unwrap();
+    assert_eq!(grammar.rule("Path").unwrap().body.to_string(), "Name ('.' Name)*");
+    assert_eq!(grammar.rule("List").unwrap().body.to_string(), "'[' Name (',' Name)* ']'");
+    assert_eq!(Interpreter::new(&grammar).unwrap().parse("[a,b,c]", "List").unwrap().span, 0..7);
+    // a reference from the body is reported at the call
+    let lints = lints("class A { @pair('a') }\nmacro pair(item) { item Missing }");
+    assert_eq!(lints, ["undefined-reference @pair('a'): rule `Missing` is not defined"]);
+
+    let errors = [
+        ("class A { @missing('a') }", "macro `missing` is not declared", "missing"),
+        ("class A { @m(b: 'a') }\nmacro m(a) { a }", "macro `m` has no parameter `b`", "b"),
+        ("class A { @m('a', 'b') }\nmacro m(a) { a }", "macro `m` takes 1 argument(s)", "'b'"),
+        ("class A { @m('a', a: 'b') }\nmacro m(a) { a }", "parameter `a` is given twice", "'b'"),
+        ("class A { @m() }\nmacro m(a) { a }", "`@m` needs an argument for `a`", "@m()"),
+        ("class A { @m('a') }\nmacro m(a: false) { 'a' }", "expected `true` or `false`, found a string", "'a'"),
+        (
+            "class A { @m(false) }\nmacro m(a: false) { if a { 'a' } }",
+            "nothing is left to match when `a` is false",
+            "@m(false)",
+        ),
+        ("class A { @m }\nmacro m(a: false) { a }", "flag `a` can only be tested with `if`", "a"),
+        ("class A { @m('a') }\nmacro m(a) { if a { a } }", "`a` is not a flag of the macro", "a"),
+        ("macro m(a) { @n(a) }\nmacro n(a) { @m(a) }", "macro `m` expands itself: m -> n -> m", "@n(a)"),
+        ("macro m(a) { a }\nmacro m(b) { b }", "macro `m` is already declared at 6..7", "m"),
+        ("macro m(a) { a ) }", "expected `|` or `}`, found `)`", ")"),
+    ];
+    for (text, message, at) in errors {
+        let error = GrammarFile::parse(text).unwrap_err();
+        assert_eq!((error.message.as_str(), &text[error.span.clone()]), (message, at), "{text}");
+    }
+}
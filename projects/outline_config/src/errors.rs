@@ -0,0 +1,41 @@
+use crate::outline::OutlineRule;
+use std::fmt::{Display, Formatter};
+use yggdrasil_rt::YggdrasilError;
+
+/// Errors raised while reading an outline file
+#[derive(Debug, Clone)]
+pub enum Error {
+    /// A line that belongs to no block, such as one indented between two levels
+    UnexpectedLine {
+        /// Byte offset where the line starts
+        offset: usize,
+    },
+    /// A block with both `key: value` and `- value` lines
+    MixedBlock {
+        /// Byte offset of the first line of the other kind
+        offset: usize,
+    },
+    /// The input was rejected by the grammar
+    SyntaxError(YggdrasilError<OutlineRule>),
+}
+
+/// Result type of [`OutlineValue::parse`](crate::OutlineValue::parse)
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl From<YggdrasilError<OutlineRule>> for Error {
+    fn from(value: YggdrasilError<OutlineRule>) -> Self {
+        Self::SyntaxError(value)
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnexpectedLine { offset } => write!(f, "unexpected indentation at offset {offset}"),
+            Self::MixedBlock { offset } => write!(f, "a block mixes entries and list items at offset {offset}"),
+            Self::SyntaxError(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
@@ -0,0 +1,16 @@
+#![deny(missing_debug_implementations)]
+#![warn(missing_docs, rustdoc::missing_crate_level_docs)]
+#![doc = include_str!("../readme.md")]
+#![doc(html_logo_url = "https://raw.githubusercontent.com/oovm/shape-rs/dev/projects/images/Trapezohedron.svg")]
+#![doc(html_favicon_url = "https://raw.githubusercontent.com/oovm/shape-rs/dev/projects/images/Trapezohedron.svg")]
+
+pub use crate::{
+    errors::{Error, Result},
+    value::OutlineValue,
+};
+pub use yggdrasil_rt::{YggdrasilNode, YggdrasilParser};
+
+mod errors;
+mod value;
+
+build_helper::include_parser!("outline");
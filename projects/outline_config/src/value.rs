@@ -0,0 +1,113 @@
+use crate::{
+    Error, Result,
+    outline::{OutlineParser, OutlineRule},
+};
+use yggdrasil_rt::{TokenPair, YggdrasilParser};
+
+/// A decoded outline file, the top level is a map.
+///
+/// A block is a map when its lines are `key: value` and a list when they are `- value`, a block mixing both is an
+/// error.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum OutlineValue {
+    /// The rest of the line after `key:` or `-`, without trailing spaces
+    Scalar(String),
+    /// `key: value` lines, in source order
+    Map(Vec<(String, OutlineValue)>),
+    /// `- value` lines, in source order
+    List(Vec<OutlineValue>),
+}
+
+impl OutlineValue {
+    /// Parse and decode a whole file.
+    pub fn parse(input: &str) -> Result<Self> {
+        let cst = OutlineParser::parse_cst(input, OutlineRule::Outline)?;
+        let mut lines = vec![];
+        let mut end = 0;
+        for pair in cst {
+            end = pair.get_span().end();
+            lines.extend(pair.into_inner().filter(|child| matches!(child.get_rule(), OutlineRule::Entry)));
+        }
+        // the grammar stops in front of a line it cannot place in a block instead of failing
+        if end < input.len() {
+            let offset = input[..end].rfind('\n').map_or(0, |newline| newline + 1);
+            return Err(Error::UnexpectedLine { offset });
+        }
+        block(input, lines)
+    }
+    /// Look up the value of the first entry named `key`, if this is a map.
+    pub fn get(&self, key: &str) -> Option<&OutlineValue> {
+        match self {
+            Self::Map(entries) => entries.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+    /// The text, if this is a scalar.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::Scalar(s) => Some(s),
+            _ => None,
+        }
+    }
+    /// The elements, if this is a list.
+    pub fn as_list(&self) -> Option<&[OutlineValue]> {
+        match self {
+            Self::List(items) => Some(items),
+            _ => None,
+        }
+    }
+}
+
+/// A map of `Entry` lines or a list of `Item` lines
+fn block(input: &str, lines: Vec<TokenPair<OutlineRule>>) -> Result<OutlineValue> {
+    let mut entries = vec![];
+    let mut items = vec![];
+    for line in lines {
+        let offset = line.get_span().start();
+        match line.get_rule() {
+            OutlineRule::Entry => entries.push(entry(input, line)?),
+            OutlineRule::Item => items.push(value(input, line)?),
+            _ => continue,
+        }
+        if !entries.is_empty() && !items.is_empty() {
+            return Err(Error::MixedBlock { offset });
+        }
+    }
+    match items.is_empty() {
+        true => Ok(OutlineValue::Map(entries)),
+        false => Ok(OutlineValue::List(items)),
+    }
+}
+
+fn entry(input: &str, pair: TokenPair<OutlineRule>) -> Result<(String, OutlineValue)> {
+    let mut key = String::new();
+    let mut out = OutlineValue::Scalar(String::new());
+    for child in pair.into_inner() {
+        match child.get_rule() {
+            OutlineRule::Key => key = text(input, &child).to_string(),
+            OutlineRule::Block | OutlineRule::Scalar => out = value(input, child)?,
+            _ => {}
+        }
+    }
+    Ok((key, out))
+}
+
+/// The value of a `Scalar`, a `Block` or the scalar of an `Item`
+fn value(input: &str, pair: TokenPair<OutlineRule>) -> Result<OutlineValue> {
+    match pair.get_rule() {
+        OutlineRule::Scalar => Ok(OutlineValue::Scalar(text(input, &pair).trim_end().to_string())),
+        OutlineRule::Block => {
+            let lines = pair.into_inner().filter(|child| matches!(child.get_rule(), OutlineRule::Line));
+            block(input, lines.flat_map(|line| line.into_inner()).collect())
+        }
+        _ => match pair.into_inner().find(|child| matches!(child.get_rule(), OutlineRule::Scalar)) {
+            Some(scalar) => value(input, scalar),
+            None => Ok(OutlineValue::Scalar(String::new())),
+        },
+    }
+}
+
+fn text<'i>(input: &'i str, pair: &TokenPair<OutlineRule>) -> &'i str {
+    let span = pair.get_span();
+    &input[span.start()..span.end()]
+}
@@ -0,0 +1,120 @@
+use grammar_tools::{CstNode, GrammarFile, Interpreter};
+use outline_config::{
+    Error, OutlineValue,
+    outline::{OutlineParser, OutlineRule},
+};
+use std::fmt::Write;
+use yggdrasil_rt::{TokenPair, YggdrasilParser};
+
+const GRAMMAR: &str = include_str!("../grammars/outline.ygg");
+
+const SERVER: &str = "server:
+  host: localhost
+
+  ports:
+    - 80
+    - 443
+  tls: off
+name: demo
+";
+
+#[test]
+fn ready() {
+    println!("it works!")
+}
+
+#[test]
+fn test_nested_blocks() {
+    let value = OutlineValue::parse(SERVER).unwrap();
+    let scalar = |text: &str| OutlineValue::Scalar(text.to_string());
+    let server = OutlineValue::Map(vec![
+        ("host".to_string(), scalar("localhost")),
+        ("ports".to_string(), OutlineValue::List(vec![scalar("80"), scalar("443")])),
+        ("tls".to_string(), scalar("off")),
+    ]);
+    assert_eq!(value, OutlineValue::Map(vec![("server".to_string(), server), ("name".to_string(), scalar("demo"))]));
+    // several blocks close on the same line
+    let value = OutlineValue::parse("a:\n  b:\n    c:\n      d: 1\ne: 2").unwrap();
+    assert_eq!(value.get("e").and_then(OutlineValue::as_str), Some("2"));
+    let d = value.get("a").and_then(|a| a.get("b")).and_then(|b| b.get("c")).and_then(|c| c.get("d"));
+    assert_eq!(d.and_then(OutlineValue::as_str), Some("1"));
+    // tabs and windows line ends
+    let value = OutlineValue::parse("a:\r\n\tb: 1 \r\n\tc: 2\r\n").unwrap();
+    assert_eq!(value.get("a").and_then(|a| a.get("c")).and_then(OutlineValue::as_str), Some("2"));
+    // each block adds to the indentation of the one around it, outermost first
+    let value = OutlineValue::parse("a:\n\tb:\n\t  c: 1\n\td: 2\n").unwrap();
+    let a = value.get("a").unwrap();
+    assert_eq!(a.get("b").and_then(|b| b.get("c")).and_then(OutlineValue::as_str), Some("1"));
+    assert_eq!(a.get("d").and_then(OutlineValue::as_str), Some("2"));
+    assert!(OutlineValue::parse("a:\n\tb:\n  \tc: 1\n").is_err());
+}
+
+#[test]
+fn test_errors() {
+    // between the two levels, neither block can take the line
+    assert!(matches!(OutlineValue::parse("a:\n    b: 1\n  c: 2"), Err(Error::UnexpectedLine { offset: 12 })));
+    assert!(matches!(OutlineValue::parse("a: 1\n  b: 2\n"), Err(Error::UnexpectedLine { offset: 5 })));
+    assert!(matches!(OutlineValue::parse("a:\n  - 1\n  b: 2\n"), Err(Error::MixedBlock { offset: 11 })));
+    assert!(matches!(OutlineValue::parse("a:\n  b\n"), Err(Error::SyntaxError(_))));
+    assert!(matches!(OutlineValue::parse(""), Err(Error::SyntaxError(_))));
+}
+
+/// The interpreter must accept and reject the same input as the generated parser, with the same tree.
+#[test]
+fn test_interpreter_matches_generated() {
+    let interpreter = Interpreter::new(&GrammarFile::parse(GRAMMAR).unwrap()).unwrap();
+    let inputs = [
+        SERVER,
+        "a:\n    b: 1\n  c: 2",
+        "a:\n  b\n",
+        "a: 1\nb:\n  c: 2",
+        "a:\n\tb: 1\n\tc: 2\n",
+        "a:\n\tb:\n\t  c: 1\n\td: 2\n",
+        "a:\n  -2\n",
+        "a:",
+        "",
+    ];
+    let mut failures = vec![];
+    for input in inputs {
+        for rule in [OutlineRule::Outline, OutlineRule::Entry] {
+            let generated = match OutlineParser::parse_cst(input, rule) {
+                Ok(o) => Some(o.into_iter().map(generated_outline).collect::<Vec<_>>().join("\n")),
+                Err(_) => None,
+            };
+            let interpreted = interpreter.parse(input, rule.name()).ok().map(|node| interpreted_outline(&node));
+            if generated != interpreted {
+                failures.push(format!("{input:?} as {rule:?}\ngenerated:   {generated:?}\ninterpreted: {interpreted:?}"));
+            }
+        }
+    }
+    assert!(failures.is_empty(), "{} difference(s):\n\n{}", failures.len(), failures.join("\n\n"));
+}
+
+/// `Rule#tag@start..end(children)`
+fn generated_outline(pair: TokenPair<OutlineRule>) -> String {
+    let span = pair.get_span();
+    let head = outline_head(pair.get_rule().name(), pair.get_tag().map(|tag| tag.to_string()), span.start(), span.end());
+    let children: Vec<_> = pair.into_inner().map(generated_outline).collect();
+    outline_join(head, children)
+}
+
+fn interpreted_outline(node: &CstNode) -> String {
+    let head = outline_head(&node.rule, node.tag.clone(), node.span.start, node.span.end);
+    outline_join(head, node.children.iter().map(interpreted_outline).collect())
+}
+
+fn outline_head(rule: &str, tag: Option<String>, start: usize, end: usize) -> String {
+    let mut out = rule.to_string();
+    if let Some(tag) = tag {
+        write!(out, "#{tag}").unwrap();
+    }
+    write!(out, "@{start}..{end}").unwrap();
+    out
+}
+
+fn outline_join(head: String, children: Vec<String>) -> String {
+    match children.is_empty() {
+        true => head,
+        false => format!("{head}({})", children.join(" ")),
+    }
+}
@@ -0,0 +1,62 @@
+use crate::json::JsonWriter;
+use grammar_tools::line_column;
+use std::ops::Range;
+
+/// How bad a [`Diagnostic`] is
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Severity {
+    /// The grammar cannot be used
+    Error,
+    /// The grammar works but is likely not what was meant
+    Warning,
+}
+
+/// A problem in the grammar text
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// How bad it is
+    pub severity: Severity,
+    /// What is wrong
+    pub message: String,
+    /// Byte range in the grammar
+    pub span: Range<usize>,
+    /// 1-based line of the start
+    pub line: usize,
+    /// 1-based column of the start, in characters
+    pub column: usize,
+}
+
+impl Severity {
+    /// `error` or `warning`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Error => "error",
+            Self::Warning => "warning",
+        }
+    }
+}
+
+impl Diagnostic {
+    /// An error at `span` of `text`
+    pub fn error(text: &str, message: impl Into<String>, span: Range<usize>) -> Self {
+        Self::new(Severity::Error, text, message, span)
+    }
+    /// A warning at `span` of `text`
+    pub fn warning(text: &str, message: impl Into<String>, span: Range<usize>) -> Self {
+        Self::new(Severity::Warning, text, message, span)
+    }
+    fn new(severity: Severity, text: &str, message: impl Into<String>, span: Range<usize>) -> Self {
+        let (line, column) = line_column(text, span.start);
+        Self { severity, message: message.into(), span, line, column }
+    }
+    pub(crate) fn write_json(&self, json: &mut JsonWriter) {
+        json.object(|json| {
+            json.field("severity", |json| json.string(self.severity.as_str()));
+            json.field("message", |json| json.string(&self.message));
+            json.field("start", |json| json.number(self.span.start));
+            json.field("end", |json| json.number(self.span.end));
+            json.field("line", |json| json.number(self.line));
+            json.field("column", |json| json.number(self.column));
+        })
+    }
+}
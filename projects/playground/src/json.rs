@@ -0,0 +1,70 @@
+use std::fmt::Write;
+
+/// Writes compact JSON, the playground results are small and flat enough to not need serde
+#[derive(Debug, Default)]
+pub(crate) struct JsonWriter {
+    out: String,
+    /// Whether the innermost object or array already has an entry
+    separated: Vec<bool>,
+}
+
+impl JsonWriter {
+    pub fn finish(self) -> String {
+        self.out
+    }
+    pub fn null(&mut self) {
+        self.out.push_str("null");
+    }
+    pub fn boolean(&mut self, value: bool) {
+        self.out.push_str(if value { "true" } else { "false" });
+    }
+    pub fn number(&mut self, value: usize) {
+        write!(self.out, "{value}").unwrap();
+    }
+    pub fn string(&mut self, value: &str) {
+        self.out.push('"');
+        for c in value.chars() {
+            match c {
+                '"' => self.out.push_str("\\\""),
+                '\\' => self.out.push_str("\\\\"),
+                '\n' => self.out.push_str("\\n"),
+                '\r' => self.out.push_str("\\r"),
+                '\t' => self.out.push_str("\\t"),
+                c if (c as u32) < 0x20 => write!(self.out, "\\u{:04x}", c as u32).unwrap(),
+                c => self.out.push(c),
+            }
+        }
+        self.out.push('"');
+    }
+    pub fn object(&mut self, body: impl FnOnce(&mut Self)) {
+        self.out.push('{');
+        self.separated.push(false);
+        body(self);
+        self.separated.pop();
+        self.out.push('}');
+    }
+    pub fn field(&mut self, key: &str, value: impl FnOnce(&mut Self)) {
+        self.separate();
+        self.string(key);
+        self.out.push(':');
+        value(self);
+    }
+    pub fn array<T>(&mut self, items: &[T], mut item: impl FnMut(&mut Self, &T)) {
+        self.out.push('[');
+        self.separated.push(false);
+        for value in items {
+            self.separate();
+            item(self, value);
+        }
+        self.separated.pop();
+        self.out.push(']');
+    }
+    fn separate(&mut self) {
+        if let Some(separated) = self.separated.last_mut() {
+            if *separated {
+                self.out.push(',');
+            }
+            *separated = true;
+        }
+    }
+}
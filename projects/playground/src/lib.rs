@@ -0,0 +1,186 @@
+#![deny(missing_debug_implementations)]
+#![warn(missing_docs, rustdoc::missing_crate_level_docs)]
+#![doc = include_str!("../readme.md")]
+#![doc(html_logo_url = "https://raw.githubusercontent.com/oovm/shape-rs/dev/projects/images/Trapezohedron.svg")]
+#![doc(html_favicon_url = "https://raw.githubusercontent.com/oovm/shape-rs/dev/projects/images/Trapezohedron.svg")]
+
+pub use crate::{
+    diagnostics::{Diagnostic, Severity},
+    preview::ast_preview,
+};
+
+mod diagnostics;
+mod json;
+mod preview;
+
+use crate::json::JsonWriter;
+use grammar_tools::{CstNode, GrammarFile, Interpreter, ParseError, RailroadRenderer, RailroadTheme, RuleKind, line_column};
+use wasm_bindgen::prelude::*;
+
+/// A grammar loaded into the playground
+#[wasm_bindgen]
+#[derive(Debug)]
+pub struct Playground {
+    text: String,
+    grammar: Option<GrammarFile>,
+    interpreter: Option<Interpreter>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+#[wasm_bindgen]
+impl Playground {
+    /// Read and lint a grammar, problems are reported by [`Playground::diagnostics`] instead of failing
+    #[wasm_bindgen(constructor)]
+    pub fn new(grammar: &str) -> Self {
+        let mut diagnostics = vec![];
+        let grammar_file = match GrammarFile::parse(grammar) {
+            Ok(o) => Some(o),
+            Err(e) => {
+                diagnostics.push(Diagnostic::error(grammar, e.message, e.span));
+                None
+            }
+        };
+        for lint in grammar_file.iter().flat_map(GrammarFile::lint) {
+            diagnostics.push(match lint.kind.is_error() {
+                true => Diagnostic::error(grammar, lint.message, lint.span),
+                false => Diagnostic::warning(grammar, lint.message, lint.span),
+            });
+        }
+        // scanners are Rust code compiled into the parser, the playground has none of them
+        for rule in grammar_file.iter().flat_map(|file| &file.rules).filter(|rule| rule.kind == RuleKind::External) {
+            let message =
+                format!("rule `{}` is matched by `{}`, which only runs in the generated parser", rule.name.text, rule.body);
+            diagnostics.push(Diagnostic::warning(grammar, message, rule.body.span.clone()));
+        }
+        let interpreter = grammar_file.as_ref().and_then(|file| match Interpreter::new(file) {
+            // left recursion is reported above but can still be tried out
+            Ok(o) => Some(o.with_left_recursion(true)),
+            // undefined references are already reported by the linter
+            Err(e) => {
+                if !diagnostics.iter().any(|diagnostic| diagnostic.span == e.span) {
+                    diagnostics.push(Diagnostic::error(grammar, e.message, e.span));
+                }
+                None
+            }
+        });
+        diagnostics.sort_by_key(|diagnostic| diagnostic.span.start);
+        Self { text: grammar.to_string(), grammar: grammar_file, interpreter, diagnostics }
+    }
+    /// Grammar problems as a JSON array of `{severity, message, start, end, line, column}`
+    pub fn diagnostics(&self) -> String {
+        let mut json = JsonWriter::default();
+        json.array(&self.diagnostics, |json, diagnostic| diagnostic.write_json(json));
+        json.finish()
+    }
+    /// Names of the rules, in declaration order
+    pub fn rule_names(&self) -> Vec<String> {
+        self.grammar.iter().flat_map(|file| file.rules.iter().map(|rule| rule.name.text.clone())).collect()
+    }
+    /// The first `entry` rule, or the first rule when none is marked
+    pub fn default_rule(&self) -> Option<String> {
+        let rules = &self.grammar.as_ref()?.rules;
+        rules.iter().find(|rule| rule.entry).or(rules.first()).map(|rule| rule.name.text.clone())
+    }
+    /// Parse `input` from `rule`, or from [`Playground::default_rule`], and return the tree and the AST preview as JSON
+    pub fn parse(&self, input: &str, rule: Option<String>) -> String {
+        let mut json = JsonWriter::default();
+        let (Some(grammar), Some(interpreter)) = (&self.grammar, &self.interpreter)
+        else {
+            write_failure(&mut json, "the grammar has errors, see the diagnostics", None, input, &[]);
+            return json.finish();
+        };
+        let Some(rule) = rule.or_else(|| self.default_rule())
+        else {
+            write_failure(&mut json, "the grammar has no rules", None, input, &[]);
+            return json.finish();
+        };
+        match interpreter.parse(input, &rule) {
+            Ok(tree) => {
+                json.object(|json| {
+                    json.field("ok", |json| json.boolean(true));
+                    json.field("rule", |json| json.string(&rule));
+                    json.field("cst", |json| write_node(json, &tree));
+                    json.field("ast", |json| json.string(&ast_preview(grammar, &tree)));
+                });
+            }
+            Err(e) => {
+                let (offset, expected) = match &e {
+                    ParseError::Mismatch { offset, expected, .. } => (Some(*offset), expected.as_slice()),
+                    ParseError::TooDeep { offset, .. } => (Some(*offset), [].as_slice()),
+                    ParseError::UnknownRule { .. } => (None, [].as_slice()),
+                };
+                write_failure(&mut json, &e.to_string(), offset, input, expected);
+            }
+        }
+        json.finish()
+    }
+    /// Railroad diagrams of every rule as a JSON array of `{rule, svg}`
+    pub fn railroad(&self, dark: bool) -> String {
+        let mut json = JsonWriter::default();
+        let diagrams = self.grammar.as_ref().map(|file| self.renderer(file, dark).render_all()).unwrap_or_default();
+        json.array(&diagrams, |json, (rule, svg)| {
+            json.object(|json| {
+                json.field("rule", |json| json.string(rule));
+                json.field("svg", |json| json.string(svg));
+            })
+        });
+        json.finish()
+    }
+    /// An HTML page with the documentation and the diagram of every rule, empty when the grammar has errors
+    pub fn reference_page(&self, dark: bool) -> String {
+        self.grammar.as_ref().map(|file| self.renderer(file, dark).html_page()).unwrap_or_default()
+    }
+}
+
+impl Playground {
+    /// Grammar problems, in source order
+    pub fn diagnostic_list(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
+    /// The grammar text
+    pub fn grammar_text(&self) -> &str {
+        &self.text
+    }
+    fn renderer<'g>(&self, file: &'g GrammarFile, dark: bool) -> RailroadRenderer<'g> {
+        let theme = if dark { RailroadTheme::dark() } else { RailroadTheme::light() };
+        RailroadRenderer::new(file).with_theme(theme)
+    }
+}
+
+fn write_node(json: &mut JsonWriter, node: &CstNode) {
+    json.object(|json| {
+        json.field("rule", |json| json.string(&node.rule));
+        json.field("tag", |json| match &node.tag {
+            Some(tag) => json.string(tag),
+            None => json.null(),
+        });
+        json.field("start", |json| json.number(node.span.start));
+        json.field("end", |json| json.number(node.span.end));
+        json.field("children", |json| json.array(&node.children, write_node));
+    })
+}
+
+fn write_failure(json: &mut JsonWriter, message: &str, offset: Option<usize>, input: &str, expected: &[String]) {
+    json.object(|json| {
+        json.field("ok", |json| json.boolean(false));
+        json.field("error", |json| {
+            json.object(|json| {
+                json.field("message", |json| json.string(message));
+                match offset {
+                    Some(offset) => {
+                        let (line, column) = line_column(input, offset);
+                        json.field("offset", |json| json.number(offset));
+                        json.field("line", |json| json.number(line));
+                        json.field("column", |json| json.number(column));
+                    }
+                    None => {
+                        json.field("offset", |json| json.null());
+                        json.field("line", |json| json.null());
+                        json.field("column", |json| json.null());
+                    }
+                }
+                json.field("expected", |json| json.array(expected, |json, item| json.string(item)));
+            })
+        });
+    })
+}
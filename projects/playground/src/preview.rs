@@ -0,0 +1,290 @@
+use grammar_tools::{CstNode, Expression, ExpressionKind, Fixity, GrammarFile, OperatorLevel, Rule, RuleKind, snake_case};
+use std::{fmt::Write, iter::Peekable, ops::Range, vec::IntoIter};
+
+/// How a field of a typed node holds its values
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Arity {
+    One,
+    Optional,
+    Many,
+}
+
+/// An operand or an operator of a flat `operators` node
+enum Item<'g> {
+    Operand(Shape, Range<usize>),
+    Operator { level: &'g OperatorLevel, name: String, span: Range<usize> },
+}
+
+/// A typed node, ready to be printed
+enum Shape {
+    Struct { name: String, fields: Vec<(String, Shape)>, span: (usize, usize) },
+    Variant { name: String, variant: String, payload: Option<Box<Shape>> },
+    List(Vec<Shape>),
+    Optional(Option<Box<Shape>>),
+    Unknown(String),
+}
+
+/// The typed AST the code generator would build from `tree`, printed like `{:#?}`.
+///
+/// A `class` becomes a struct with a field per rule it references, a `union` an enum with a variant per branch and an
+/// `operators` rule an enum of unary and binary operations.
+pub fn ast_preview(grammar: &GrammarFile, tree: &CstNode) -> String {
+    let mut out = String::new();
+    write_shape(&mut out, &shape(grammar, tree), 0);
+    out
+}
+
+fn shape(grammar: &GrammarFile, node: &CstNode) -> Shape {
+    let Some(rule) = grammar.rule(&node.rule)
+    else {
+        return Shape::Unknown(node.rule.clone());
+    };
+    match rule.kind {
+        RuleKind::Union => union_shape(grammar, rule, node),
+        RuleKind::Operators => operators_shape(grammar, rule, node),
+        RuleKind::Class | RuleKind::External => {
+            let mut fields = vec![];
+            for (field, arity) in class_fields(grammar, rule) {
+                let mut values = node.children.iter().filter(|child| child.tag.as_deref() == Some(field.as_str()));
+                let value = match arity {
+                    Arity::One => {
+                        values.next().map(|child| shape(grammar, child)).unwrap_or(Shape::Unknown("missing".to_string()))
+                    }
+                    Arity::Optional => Shape::Optional(values.next().map(|child| Box::new(shape(grammar, child)))),
+                    Arity::Many => Shape::List(values.map(|child| shape(grammar, child)).collect()),
+                };
+                fields.push((field, value));
+            }
+            Shape::Struct { name: node_name(&rule.name.text), fields, span: (node.span.start, node.span.end) }
+        }
+    }
+}
+
+fn union_shape(grammar: &GrammarFile, rule: &Rule, node: &CstNode) -> Shape {
+    let branches = match &rule.body.kind {
+        ExpressionKind::Choice(branches) => branches.as_slice(),
+        _ => std::slice::from_ref(&rule.body),
+    };
+    let prefix = snake_case(&rule.name.text);
+    for child in &node.children {
+        let Some(tag) = &child.tag
+        else {
+            continue;
+        };
+        for (index, branch) in branches.iter().enumerate() {
+            let branch = match &branch.kind {
+                ExpressionKind::Tagged { body, .. } => body.as_ref(),
+                _ => branch,
+            };
+            let (variant, payload) = match &branch.kind {
+                ExpressionKind::Reference(name) if snake_case(name) == *tag => (name.clone(), Some(child)),
+                ExpressionKind::Reference(_) => continue,
+                _ if *tag == format!("{prefix}_{index}") => {
+                    // a branch with a single reference wraps that node, other branches are unit variants
+                    let references = references(branch);
+                    let payload = match references.as_slice() {
+                        [name] => {
+                            node.children.iter().find(|c| c.rule == *name && grammar.rule(name).is_some_and(|r| !r.ignored))
+                        }
+                        _ => None,
+                    };
+                    (upper_camel(tag), payload)
+                }
+                _ => continue,
+            };
+            return Shape::Variant {
+                name: node_name(&rule.name.text),
+                variant,
+                payload: payload.map(|child| Box::new(shape(grammar, child))),
+            };
+        }
+    }
+    Shape::Unknown(node_name(&rule.name.text))
+}
+
+fn operators_shape(grammar: &GrammarFile, rule: &Rule, node: &CstNode) -> Shape {
+    let Some(table) = &rule.operators
+    else {
+        return Shape::Unknown(node_name(&rule.name.text));
+    };
+    let operand = snake_case(&table.operand.text);
+    let mut items = vec![];
+    for child in &node.children {
+        match child.tag.as_deref() {
+            Some(tag) if tag == operand => items.push(Item::Operand(shape(grammar, child), child.span.clone())),
+            Some(tag) => {
+                if let Some((level, operator)) = table.operator(tag) {
+                    items.push(Item::Operator { level, name: operator.tag.text.clone(), span: child.span.clone() })
+                }
+            }
+            None => {}
+        }
+    }
+    let mut items = items.into_iter().peekable();
+    match fold(&rule.name.text, &table.operand.text, &mut items, 0) {
+        Some((shape, _)) => shape,
+        None => Shape::Unknown(node_name(&rule.name.text)),
+    }
+}
+
+/// Pratt parsing over the flat operands and operators, operators binding looser than `min` are left to the caller
+fn fold(rule: &str, operand: &str, items: &mut Peekable<IntoIter<Item>>, min: u32) -> Option<(Shape, Range<usize>)> {
+    let (mut lhs, mut span) = match items.next()? {
+        Item::Operand(shape, span) => {
+            let shape = Shape::Variant { name: node_name(rule), variant: operand.to_string(), payload: Some(Box::new(shape)) };
+            (shape, span)
+        }
+        Item::Operator { level, name, span } => {
+            let (operand_shape, end) = fold(rule, operand, items, level.binding_power().1)?;
+            let span = span.start..end.end;
+            (unary(rule, &name, operand_shape, &span), span)
+        }
+    };
+    while let Some(Item::Operator { level, .. }) = items.peek() {
+        let (left, right) = level.binding_power();
+        if left < min {
+            break;
+        }
+        let Some(Item::Operator { level, name, span: operator }) = items.next()
+        else {
+            break;
+        };
+        match level.fixity {
+            Fixity::Postfix => {
+                span = span.start..operator.end;
+                lhs = unary(rule, &name, lhs, &span);
+            }
+            _ => {
+                let (rhs, end) = fold(rule, operand, items, right)?;
+                span = span.start..end.end;
+                let fields = vec![
+                    ("operator".to_string(), operator_shape(rule, &name)),
+                    ("lhs".to_string(), lhs),
+                    ("rhs".to_string(), rhs),
+                ];
+                lhs = Shape::Struct { name: format!("{}::Binary", node_name(rule)), fields, span: (span.start, span.end) };
+            }
+        }
+    }
+    Some((lhs, span))
+}
+
+fn unary(rule: &str, operator: &str, operand: Shape, span: &Range<usize>) -> Shape {
+    let fields = vec![("operator".to_string(), operator_shape(rule, operator)), ("operand".to_string(), operand)];
+    Shape::Struct { name: format!("{}::Unary", node_name(rule)), fields, span: (span.start, span.end) }
+}
+
+fn operator_shape(rule: &str, operator: &str) -> Shape {
+    Shape::Variant { name: format!("{rule}Operator"), variant: operator.to_string(), payload: None }
+}
+
+/// Fields of a class, in order of appearance: references by snake case name and captured references by capture name
+fn class_fields(grammar: &GrammarFile, rule: &Rule) -> Vec<(String, Arity)> {
+    let mut out: Vec<(String, Arity)> = vec![];
+    collect_fields(grammar, &rule.body, Arity::One, &mut out);
+    out
+}
+
+fn collect_fields(grammar: &GrammarFile, expression: &Expression, arity: Arity, out: &mut Vec<(String, Arity)>) {
+    match &expression.kind {
+        ExpressionKind::Reference(name) if grammar.rule(name).is_some_and(|r| !r.ignored) => {
+            add_field(out, snake_case(name), arity)
+        }
+        ExpressionKind::Capture { name, body } if matches!(body.kind, ExpressionKind::Reference(_)) => {
+            add_field(out, name.text.clone(), arity)
+        }
+        ExpressionKind::Sequence(items) => items.iter().for_each(|item| collect_fields(grammar, item, arity, out)),
+        ExpressionKind::Choice(items) => {
+            let arity = if arity == Arity::Many { Arity::Many } else { Arity::Optional };
+            items.iter().for_each(|item| collect_fields(grammar, item, arity, out))
+        }
+        ExpressionKind::Tagged { body, .. } | ExpressionKind::Capture { body, .. } => collect_fields(grammar, body, arity, out),
+        ExpressionKind::Repeat { body, max, .. } => {
+            let arity = match (arity, max) {
+                (Arity::Many, _) => Arity::Many,
+                (_, Some(1)) => Arity::Optional,
+                _ => Arity::Many,
+            };
+            collect_fields(grammar, body, arity, out)
+        }
+        _ => {}
+    }
+}
+
+/// A field seen twice holds many values
+fn add_field(out: &mut Vec<(String, Arity)>, name: String, arity: Arity) {
+    match out.iter_mut().find(|(field, _)| *field == name) {
+        Some((_, existing)) => *existing = Arity::Many,
+        None => out.push((name, arity)),
+    }
+}
+
+/// References that make nodes, the ones inside `&e` and `!e` do not
+fn references(expression: &Expression) -> Vec<String> {
+    match &expression.kind {
+        ExpressionKind::Reference(name) => vec![name.clone()],
+        ExpressionKind::Sequence(items) | ExpressionKind::Choice(items) => items.iter().flat_map(references).collect(),
+        ExpressionKind::Tagged { body, .. } | ExpressionKind::Capture { body, .. } | ExpressionKind::Repeat { body, .. } => {
+            references(body)
+        }
+        _ => vec![],
+    }
+}
+
+fn node_name(rule: &str) -> String {
+    format!("{rule}Node")
+}
+
+/// `string_0` to `String0`
+fn upper_camel(tag: &str) -> String {
+    let mut out = String::with_capacity(tag.len());
+    for word in tag.split('_') {
+        let mut chars = word.chars();
+        if let Some(first) = chars.next() {
+            out.extend(first.to_uppercase());
+            out.push_str(chars.as_str());
+        }
+    }
+    out
+}
+
+fn write_shape(out: &mut String, shape: &Shape, indent: usize) {
+    let pad = "    ".repeat(indent + 1);
+    let close = "    ".repeat(indent);
+    match shape {
+        Shape::Struct { name, fields, span } => {
+            writeln!(out, "{name} {{").unwrap();
+            for (field, value) in fields {
+                write!(out, "{pad}{field}: ").unwrap();
+                write_shape(out, value, indent + 1);
+                out.push_str(",\n");
+            }
+            write!(out, "{pad}span: {}..{},\n{close}}}", span.0, span.1).unwrap();
+        }
+        Shape::Variant { name, variant, payload } => {
+            write!(out, "{name}::{variant}").unwrap();
+            if let Some(payload) = payload {
+                out.push('(');
+                write_shape(out, payload, indent);
+                out.push(')');
+            }
+        }
+        Shape::List(items) if items.is_empty() => out.push_str("[]"),
+        Shape::List(items) => {
+            out.push_str("[\n");
+            for item in items {
+                out.push_str(&pad);
+                write_shape(out, item, indent + 1);
+                out.push_str(",\n");
+            }
+            write!(out, "{close}]").unwrap();
+        }
+        Shape::Optional(None) => out.push_str("None"),
+        Shape::Optional(Some(value)) => {
+            out.push_str("Some(");
+            write_shape(out, value, indent);
+            out.push(')');
+        }
+        Shape::Unknown(name) => write!(out, "<{name}>").unwrap(),
+    }
+}
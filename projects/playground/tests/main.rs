@@ -0,0 +1,151 @@
+use playground::{Playground, Severity};
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen_test::wasm_bindgen_test as test;
+
+const JSON5: &str = include_str!("../../build_by_dep/grammars/json5.ygg");
+
+#[test]
+fn ready() {
+    println!("it works!")
+}
+
+#[test]
+fn test_diagnostics() {
+    assert_eq!(Playground::new(JSON5).diagnostics(), "[]");
+    let broken = Playground::new("class A { B }\nclass B { C }");
+    assert_eq!(broken.diagnostic_list()[0].severity, Severity::Error);
+    assert_eq!(
+        broken.diagnostics(),
+        r#"[{"severity":"error","message":"rule `C` is not defined","start":24,"end":25,"line":2,"column":11}]"#
+    );
+    // lints that do not stop the grammar from running are warnings
+    let shadowed = Playground::new("class A { 'a' | 'ab' }\nclass B { 'b' }");
+    let severities: Vec<_> = shadowed.diagnostic_list().iter().map(|d| (d.severity, d.line, d.column)).collect();
+    assert_eq!(severities, [(Severity::Warning, 1, 17), (Severity::Warning, 2, 7)]);
+    assert!(shadowed.parse("a", None).starts_with(r#"{"ok":true"#));
+    let recursive = Playground::new("entry union Sum { | Sum '+' Digit #Add | Digit }\nclass Digit { /[0-9]/ }");
+    assert!(recursive.diagnostics().contains(r#""message":"rule `Sum` is left recursive: Sum -> Sum""#));
+    assert!(recursive.parse("1+2+3", None).starts_with(r#"{"ok":true"#));
+    // scanners only exist in the generated parser
+    let external = Playground::new("class A { 'a' B? }\nexternal B { crate::scan }");
+    assert_eq!(
+        external.diagnostics(),
+        r#"[{"severity":"warning","message":"rule `B` is matched by `crate::scan`, which only runs in the generated parser","start":32,"end":43,"line":2,"column":14}]"#
+    );
+    assert!(external.parse("a", None).starts_with(r#"{"ok":true"#));
+    let syntax = Playground::new("class A {");
+    assert!(syntax.diagnostics().contains(r#""message":"expected an expression, found the end of file""#));
+    assert!(syntax.parse("a", None).starts_with(r#"{"ok":false,"error":{"message":"the grammar has errors"#));
+    assert!(syntax.rule_names().is_empty());
+}
+
+#[test]
+fn test_parse() {
+    let playground = Playground::new(JSON5);
+    assert_eq!(playground.default_rule().as_deref(), Some("Value"));
+    assert_eq!(playground.rule_names().len(), 15);
+    let result = playground.parse("true", None);
+    assert_eq!(
+        result,
+        concat!(
+            r#"{"ok":true,"rule":"Value","cst":{"rule":"Value","tag":null,"start":0,"end":4,"children":["#,
+            r#"{"rule":"Boolean","tag":"boolean","start":0,"end":4,"children":["#,
+            r#"{"rule":"IgnoreText","tag":"boolean_0","start":0,"end":4,"children":[]}]}]},"#,
+            r#""ast":"ValueNode::Boolean(BooleanNode::Boolean0)"}"#
+        )
+    );
+    let error = playground.parse("[1,\n }", Some("Array".to_string()));
+    assert!(error.starts_with(r#"{"ok":false,"error":{"message":"input does not match `Array` at 5"#), "{error}");
+    assert!(error.contains(r#""offset":5,"line":2,"column":2,"expected":["#), "{error}");
+    let unknown = playground.parse("1", Some("Missing".to_string()));
+    assert!(unknown.contains(r#""message":"rule `Missing` is not defined","offset":null"#), "{unknown}");
+}
+
+#[test]
+fn test_ast_preview() {
+    let playground = Playground::new(JSON5);
+    let result = playground.parse("{a: ['x', 2]}", None);
+    let ast = result.split(r#""ast":""#).nth(1).unwrap().trim_end_matches("\"}").replace("\\n", "\n");
+    assert_eq!(
+        ast,
+        r#"ValueNode::Object(ObjectNode {
+    object_pair: [
+        ObjectPairNode {
+            object_key: ObjectKeyNode::Identifier(IdentifierNode {
+                span: 1..2,
+            }),
+            value: ValueNode::Array(ArrayNode {
+                value: [
+                    ValueNode::String(StringNode::String0(StringRawNode {
+                        string_escape: [],
+                        span: 6..7,
+                    })),
+                    ValueNode::Number(NumberNode {
+                        span: 10..11,
+                    }),
+                ],
+                span: 4..12,
+            }),
+            span: 1..12,
+        },
+    ],
+    span: 0..13,
+})"#
+    );
+    // references inside a lookahead make no node and no payload
+    let playground =
+        Playground::new("union Key {\n    | !'_' Word\n    | Digit\n}\nclass Word { /[a-z]+/ }\nclass Digit { /[0-9]/ }");
+    assert!(playground.parse("ab", None).contains(r#""ast":"KeyNode::Key0(WordNode {\n    span: 0..2,\n})""#));
+}
+
+#[test]
+fn test_railroad() {
+    let playground = Playground::new(JSON5);
+    let diagrams = playground.railroad(true);
+    assert!(diagrams.starts_with(r#"[{"rule":"Value","svg":"<svg class=\"railroad\""#));
+    assert_eq!(diagrams.matches(r#"{"rule":"#).count(), 15);
+    assert!(playground.reference_page(false).contains("<section id=\"ObjectPair\">"));
+    assert_eq!(Playground::new("class A {").reference_page(false), "");
+}
+
+#[test]
+fn test_operator_preview() {
+    let playground = Playground::new(include_str!("../../grammar_tools/tests/calc.ygg"));
+    assert_eq!(playground.diagnostics(), "[]");
+    let result = playground.parse("-1+2^3!", None);
+    let ast = result.split(r#""ast":""#).nth(1).unwrap().trim_end_matches("\"}").replace("\\n", "\n");
+    assert_eq!(
+        ast,
+        r#"ExprNode::Binary {
+    operator: ExprOperator::Add,
+    lhs: ExprNode::Unary {
+        operator: ExprOperator::Negative,
+        operand: ExprNode::Atom(AtomNode::Number(NumberNode {
+            span: 1..2,
+        })),
+        span: 0..2,
+    },
+    rhs: ExprNode::Binary {
+        operator: ExprOperator::Power,
+        lhs: ExprNode::Atom(AtomNode::Number(NumberNode {
+            span: 3..4,
+        })),
+        rhs: ExprNode::Unary {
+            operator: ExprOperator::Factorial,
+            operand: ExprNode::Atom(AtomNode::Number(NumberNode {
+                span: 5..6,
+            })),
+            span: 5..7,
+        },
+        span: 3..7,
+    },
+    span: 0..7,
+}"#
+    );
+    // operators of the same level fold to the left
+    let result = playground.parse("1-2-3", None);
+    assert!(
+        result.contains(r#""ast":"ExprNode::Binary {\n    operator: ExprOperator::Sub,\n    lhs: ExprNode::Binary {"#),
+        "{result}"
+    );
+}